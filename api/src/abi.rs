@@ -0,0 +1,332 @@
+// Copyright (c) Move Web Compiler
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::BTreeMap;
+use std::fmt;
+use anyhow::{Result, anyhow};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use move_binary_format::access::ModuleAccess;
+use move_binary_format::file_format::{Ability, AbilitySet, CompiledModule, FunctionDefinitionIndex, Visibility};
+use move_binary_format::normalized;
+use move_bytecode_source_map::source_map::SourceMap;
+use serde::{Deserialize, Serialize};
+use sui_move_build::CompiledPackage;
+
+/// 模块的接口描述
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ModuleAbi {
+    /// 模块地址（十六进制）
+    pub address: String,
+    /// 模块名称
+    pub name: String,
+    /// friend声明，形如`0x0::other`
+    pub friends: Vec<String>,
+    /// 结构体
+    pub structs: Vec<StructAbi>,
+    /// 公开函数和entry函数
+    pub functions: Vec<FunctionAbi>,
+    /// 常量
+    pub constants: Vec<ConstantAbi>,
+}
+
+/// 结构体
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StructAbi {
+    /// 结构体名称
+    pub name: String,
+    /// 能力：`copy`、`drop`、`store`、`key`
+    pub abilities: Vec<String>,
+    /// 类型参数
+    pub type_parameters: Vec<StructTypeParameterAbi>,
+    /// 字段，按声明顺序排列
+    pub fields: Vec<FieldAbi>,
+}
+
+/// 结构体的类型参数
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StructTypeParameterAbi {
+    /// 能力约束
+    pub constraints: Vec<String>,
+    /// 是否为phantom类型参数
+    pub is_phantom: bool,
+}
+
+/// 结构体字段
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FieldAbi {
+    /// 字段名
+    pub name: String,
+    /// 字段类型
+    #[serde(rename = "type")]
+    pub type_: TypeAbi,
+}
+
+/// 函数
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FunctionAbi {
+    /// 函数名
+    pub name: String,
+    /// 可见性：`public`、`friend`或`private`
+    pub visibility: String,
+    /// 是否为entry函数
+    pub is_entry: bool,
+    /// 每个类型参数的能力约束
+    pub type_parameters: Vec<Vec<String>>,
+    /// 参数类型，包括`TxContext`
+    pub parameters: Vec<TypeAbi>,
+    /// 参数名，与`parameters`一一对应，没有源码映射时为空
+    #[serde(default)]
+    pub parameter_names: Vec<String>,
+    /// 返回值类型
+    pub returns: Vec<TypeAbi>,
+}
+
+/// 常量
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConstantAbi {
+    /// 常量名，没有源码映射时为空
+    pub name: Option<String>,
+    /// 常量类型
+    #[serde(rename = "type")]
+    pub type_: TypeAbi,
+    /// 常量值，按Move语法显示
+    pub value: Option<String>,
+}
+
+/// 类型，JSON格式为`"U64"`、`{"Vector": "U8"}`、`{"Struct": {...}}`、`{"TypeParameter": 0}`等
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TypeAbi {
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    U256,
+    Address,
+    Signer,
+    Struct {
+        address: String,
+        module: String,
+        name: String,
+        type_arguments: Vec<TypeAbi>,
+    },
+    Vector(Box<TypeAbi>),
+    TypeParameter(u16),
+    Reference(Box<TypeAbi>),
+    MutableReference(Box<TypeAbi>),
+}
+
+impl From<&normalized::Type> for TypeAbi {
+    fn from(type_: &normalized::Type) -> Self {
+        use normalized::Type;
+        match type_ {
+            Type::Bool => TypeAbi::Bool,
+            Type::U8 => TypeAbi::U8,
+            Type::U16 => TypeAbi::U16,
+            Type::U32 => TypeAbi::U32,
+            Type::U64 => TypeAbi::U64,
+            Type::U128 => TypeAbi::U128,
+            Type::U256 => TypeAbi::U256,
+            Type::Address => TypeAbi::Address,
+            Type::Signer => TypeAbi::Signer,
+            Type::Struct { address, module, name, type_arguments } => TypeAbi::Struct {
+                address: address.to_hex_literal(),
+                module: module.to_string(),
+                name: name.to_string(),
+                type_arguments: type_arguments.iter().map(TypeAbi::from).collect(),
+            },
+            Type::Vector(inner) => TypeAbi::Vector(Box::new(TypeAbi::from(inner.as_ref()))),
+            Type::TypeParameter(index) => TypeAbi::TypeParameter(*index),
+            Type::Reference(inner) => TypeAbi::Reference(Box::new(TypeAbi::from(inner.as_ref()))),
+            Type::MutableReference(inner) => TypeAbi::MutableReference(Box::new(TypeAbi::from(inner.as_ref()))),
+        }
+    }
+}
+
+impl fmt::Display for TypeAbi {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TypeAbi::Bool => write!(f, "bool"),
+            TypeAbi::U8 => write!(f, "u8"),
+            TypeAbi::U16 => write!(f, "u16"),
+            TypeAbi::U32 => write!(f, "u32"),
+            TypeAbi::U64 => write!(f, "u64"),
+            TypeAbi::U128 => write!(f, "u128"),
+            TypeAbi::U256 => write!(f, "u256"),
+            TypeAbi::Address => write!(f, "address"),
+            TypeAbi::Signer => write!(f, "signer"),
+            TypeAbi::Struct { address, module, name, type_arguments } => {
+                write!(f, "{}::{}::{}", address, module, name)?;
+                if !type_arguments.is_empty() {
+                    let arguments: Vec<String> = type_arguments.iter().map(ToString::to_string).collect();
+                    write!(f, "<{}>", arguments.join(", "))?;
+                }
+                Ok(())
+            }
+            TypeAbi::Vector(inner) => write!(f, "vector<{}>", inner),
+            TypeAbi::TypeParameter(index) => write!(f, "T{}", index),
+            TypeAbi::Reference(inner) => write!(f, "&{}", inner),
+            TypeAbi::MutableReference(inner) => write!(f, "&mut {}", inner),
+        }
+    }
+}
+
+impl TypeAbi {
+    /// 是否为`0x2::tx_context::TxContext`的引用，调用时由运行时提供
+    pub fn is_tx_context(&self) -> bool {
+        let inner = match self {
+            TypeAbi::Reference(inner) | TypeAbi::MutableReference(inner) => inner.as_ref(),
+            _ => return false,
+        };
+        matches!(inner, TypeAbi::Struct { address, module, name, .. }
+            if address == "0x2" && module == "tx_context" && name == "TxContext")
+    }
+}
+
+/// 提取模块的接口，有源码映射时补充常量名和参数名
+pub fn module_abi(module: &CompiledModule, source_map: Option<&SourceMap>) -> ModuleAbi {
+    let normalized = normalized::Module::new(module);
+
+    let structs = normalized
+        .structs
+        .iter()
+        .map(|(name, struct_)| StructAbi {
+            name: name.to_string(),
+            abilities: abilities(&struct_.abilities),
+            type_parameters: struct_
+                .type_parameters
+                .iter()
+                .map(|parameter| StructTypeParameterAbi {
+                    constraints: abilities(&parameter.constraints),
+                    is_phantom: parameter.is_phantom,
+                })
+                .collect(),
+            fields: struct_
+                .fields
+                .iter()
+                .map(|field| FieldAbi {
+                    name: field.name.to_string(),
+                    type_: TypeAbi::from(&field.type_),
+                })
+                .collect(),
+        })
+        .collect();
+
+    // 编译后的模块不保存参数名，需要从源码映射中按函数定义下标查找
+    let parameter_names: BTreeMap<String, Vec<String>> = source_map
+        .map(|source_map| {
+            module
+                .function_defs()
+                .iter()
+                .enumerate()
+                .filter_map(|(index, definition)| {
+                    let function_map = source_map
+                        .get_function_source_map(FunctionDefinitionIndex(index as u16))
+                        .ok()?;
+                    let name = module.identifier_at(module.function_handle_at(definition.function).name);
+                    // 编译器给局部变量加了`#`开头的后缀（如`ctx#0#0`），只保留源码中的名称
+                    let parameters = function_map
+                        .parameters
+                        .iter()
+                        .map(|(name, _)| name.split('#').next().unwrap_or(name).to_string())
+                        .collect();
+                    Some((name.to_string(), parameters))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    // 只保留外部可以调用的函数：公开函数和entry函数
+    let functions = normalized
+        .exposed_functions
+        .iter()
+        .filter(|(_, function)| function.visibility == Visibility::Public || function.is_entry)
+        .map(|(name, function)| FunctionAbi {
+            name: name.to_string(),
+            visibility: match function.visibility {
+                Visibility::Public => "public",
+                Visibility::Friend => "friend",
+                Visibility::Private => "private",
+            }
+            .to_string(),
+            is_entry: function.is_entry,
+            type_parameters: function.type_parameters.iter().map(abilities).collect(),
+            parameters: function.parameters.iter().map(TypeAbi::from).collect(),
+            parameter_names: parameter_names.get(name.as_str()).cloned().unwrap_or_default(),
+            returns: function.return_.iter().map(TypeAbi::from).collect(),
+        })
+        .collect();
+
+    // 编译后的模块不保存常量名，需要从源码映射中按常量池下标查找
+    let constant_names: BTreeMap<u16, String> = source_map
+        .map(|source_map| {
+            source_map
+                .constant_map
+                .iter()
+                .map(|(name, index)| (*index, name.0.to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+    let constants = module
+        .constant_pool()
+        .iter()
+        .enumerate()
+        .map(|(index, constant)| ConstantAbi {
+            name: constant_names.get(&(index as u16)).cloned(),
+            type_: TypeAbi::from(&normalized::Type::new(module, &constant.type_)),
+            value: constant.deserialize_constant().map(|value| value.to_string()),
+        })
+        .collect();
+
+    ModuleAbi {
+        address: module.address().to_hex_literal(),
+        name: module.self_id().name().to_string(),
+        friends: normalized
+            .friends
+            .iter()
+            .map(|friend| format!("{}::{}", friend.address().to_hex_literal(), friend.name()))
+            .collect(),
+        structs,
+        functions,
+        constants,
+    }
+}
+
+/// 提取编译后的包中根包每个模块的接口
+pub fn package_abi(package: &CompiledPackage) -> Vec<ModuleAbi> {
+    package
+        .package
+        .root_modules()
+        .map(|unit| module_abi(&unit.unit.module, Some(&unit.unit.source_map)))
+        .collect()
+}
+
+/// 从序列化的模块提取接口，没有常量名和参数名
+pub fn bytes_abi(bytes: &[u8]) -> Result<ModuleAbi> {
+    let module = CompiledModule::deserialize(bytes).map_err(|e| anyhow!("无法解析模块字节码: {}", e))?;
+    Ok(module_abi(&module, None))
+}
+
+/// 从Base64编码的模块提取接口
+pub fn base64_abi(encoded: &str) -> Result<ModuleAbi> {
+    let bytes = BASE64_STANDARD
+        .decode(encoded.trim())
+        .map_err(|e| anyhow!("无效的Base64字节码: {}", e))?;
+    bytes_abi(&bytes)
+}
+
+fn abilities(set: &AbilitySet) -> Vec<String> {
+    set.into_iter()
+        .map(|ability| {
+            match ability {
+                Ability::Copy => "copy",
+                Ability::Drop => "drop",
+                Ability::Store => "store",
+                Ability::Key => "key",
+            }
+            .to_string()
+        })
+        .collect()
+}
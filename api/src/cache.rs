@@ -0,0 +1,221 @@
+// Copyright (c) Move Web Compiler
+// SPDX-License-Identifier: Apache-2.0
+
+use std::path::{Path, PathBuf};
+use anyhow::Result;
+use fastcrypto::hash::{Blake2b256, HashFunction};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::framework::ResolvedFramework;
+use crate::package;
+
+/// 缓存格式版本，缓存内容的结构变化时递增，使旧条目失效
+pub const CACHE_FORMAT_VERSION: u32 = 1;
+
+/// 按内容寻址的编译缓存
+///
+/// 键为包内全部源文件、Move.toml、框架版本和编译选项的Blake2b-256摘要，
+/// 值为序列化后的编译结果（包含各模块的字节码）。
+#[derive(Clone, Debug)]
+pub struct CompileCache {
+    /// 缓存目录
+    pub dir: PathBuf,
+    /// 是否启用
+    pub enabled: bool,
+}
+
+impl Default for CompileCache {
+    fn default() -> Self {
+        let dir = dirs::home_dir()
+            .map(|home| home.join(".move-web-compiler").join("compile-cache"))
+            .unwrap_or_else(|| std::env::temp_dir().join("move-web-compiler-compile-cache"));
+        Self { dir, enabled: true }
+    }
+}
+
+impl CompileCache {
+    /// 从环境变量读取配置：`MOVE_COMPILE_CACHE_DIR`，`MOVE_COMPILE_CACHE=0`时关闭缓存
+    pub fn from_env() -> Self {
+        let mut cache = Self::default();
+        if let Some(dir) = std::env::var_os("MOVE_COMPILE_CACHE_DIR") {
+            cache.dir = PathBuf::from(dir);
+        }
+        if let Ok(enabled) = std::env::var("MOVE_COMPILE_CACHE") {
+            cache.enabled = !matches!(enabled.trim(), "0" | "false" | "no" | "off");
+        }
+        cache
+    }
+
+    /// 计算包目录的缓存键，框架版本没有固定到某个提交时返回空
+    ///
+    /// 未固定的git分支会随上游更新，同样的源码可能得到不同的结果，因此不缓存。
+    pub fn key(&self, package_path: &Path, framework: &ResolvedFramework, options: &str) -> Result<Option<String>> {
+        if !self.enabled {
+            return Ok(None);
+        }
+        let commit = match &framework.commit {
+            Some(commit) => commit,
+            None => return Ok(None),
+        };
+
+        let files = package::collect_package_files(package_path)?;
+        let move_toml = std::fs::read_to_string(package_path.join("Move.toml"))?;
+
+        // 每个字段都带上长度前缀，避免不同的字段划分拼出同样的字节序列
+        let mut hasher = Blake2b256::default();
+        let mut update = |value: &[u8]| {
+            hasher.update((value.len() as u64).to_le_bytes());
+            hasher.update(value);
+        };
+        update(&CACHE_FORMAT_VERSION.to_le_bytes());
+        update(framework.revision.as_bytes());
+        update(commit.as_bytes());
+        update(options.as_bytes());
+        update(move_toml.as_bytes());
+        for (path, content) in &files {
+            update(path.as_bytes());
+            update(content.as_bytes());
+        }
+        Ok(Some(hex::encode(hasher.finalize().digest)))
+    }
+
+    /// 读取缓存条目，不存在或无法解析时返回空
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let content = std::fs::read(self.entry_path(key)).ok()?;
+        serde_json::from_slice(&content).ok()
+    }
+
+    /// 写入缓存条目，先写临时文件再重命名，避免并发请求读到写了一半的条目
+    pub fn put<T: Serialize>(&self, key: &str, value: &T) -> Result<()> {
+        let path = self.entry_path(key);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let staging = path.with_extension(format!("tmp.{}", hex::encode(rand::random::<[u8; 8]>())));
+        std::fs::write(&staging, serde_json::to_vec(value)?)?;
+        std::fs::rename(&staging, &path)?;
+        Ok(())
+    }
+
+    // 按键的前两个字符分目录存放，避免单个目录下文件过多
+    fn entry_path(&self, key: &str) -> PathBuf {
+        let prefix = key.get(..2).unwrap_or("00");
+        self.dir.join(prefix).join(format!("{}.json", key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framework::FrameworkSource;
+
+    // 测试用的包目录，离开作用域时删除
+    struct Package(PathBuf);
+
+    impl Package {
+        fn new() -> Self {
+            let dir = std::env::temp_dir().join(format!("move-cache-test-{:016x}", rand::random::<u64>()));
+            std::fs::create_dir_all(dir.join("sources")).unwrap();
+            std::fs::create_dir_all(dir.join("tests")).unwrap();
+            std::fs::write(dir.join("Move.toml"), "[package]\nname = \"hello\"\n").unwrap();
+            std::fs::write(dir.join("sources/hello.move"), "module hello::hello {}\n").unwrap();
+            std::fs::write(dir.join("tests/hello_tests.move"), "module hello::hello_tests {}\n").unwrap();
+            Self(dir)
+        }
+
+        fn write(&self, path: &str, content: &str) {
+            std::fs::write(self.0.join(path), content).unwrap();
+        }
+    }
+
+    impl Drop for Package {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn framework(revision: &str, commit: Option<&str>) -> ResolvedFramework {
+        ResolvedFramework {
+            revision: revision.to_string(),
+            network: None,
+            commit: commit.map(str::to_string),
+            source: FrameworkSource::Cache,
+            local_dir: None,
+        }
+    }
+
+    fn cache() -> CompileCache {
+        CompileCache {
+            dir: std::env::temp_dir().join("move-cache-test-entries"),
+            enabled: true,
+        }
+    }
+
+    fn key(package: &Package, framework: &ResolvedFramework, options: &str) -> String {
+        cache().key(&package.0, framework, options).unwrap().unwrap()
+    }
+
+    #[test]
+    fn key_is_stable() {
+        let package = Package::new();
+        let testnet = framework("framework/testnet", Some("a1b2c3"));
+        let first = key(&package, &testnet, "compile");
+        assert_eq!(first.len(), 64);
+        assert_eq!(key(&package, &testnet, "compile"), first);
+
+        // 内容相同的另一个目录得到相同的键
+        assert_eq!(key(&Package::new(), &testnet, "compile"), first);
+
+        // 不参与编译的文件不影响键
+        package.write("README.md", "# hello");
+        std::fs::create_dir_all(package.0.join("build")).unwrap();
+        package.write("build/hello.mv", "bytecode");
+        assert_eq!(key(&package, &testnet, "compile"), first);
+    }
+
+    #[test]
+    fn key_changes_with_inputs() {
+        let package = Package::new();
+        let testnet = framework("framework/testnet", Some("a1b2c3"));
+        let base = key(&package, &testnet, "compile");
+
+        assert_ne!(key(&package, &framework("framework/mainnet", Some("a1b2c3")), "compile"), base);
+        assert_ne!(key(&package, &framework("framework/testnet", Some("d4e5f6")), "compile"), base);
+        assert_ne!(key(&package, &testnet, "test"), base);
+
+        package.write("sources/hello.move", "module hello::hello { public fun f() {} }\n");
+        let changed_source = key(&package, &testnet, "compile");
+        assert_ne!(changed_source, base);
+
+        package.write("tests/hello_tests.move", "module hello::hello_tests { }\n");
+        let changed_test = key(&package, &testnet, "compile");
+        assert_ne!(changed_test, changed_source);
+
+        package.write("Move.toml", "[package]\nname = \"hello\"\n\n[addresses]\nhello = \"0x0\"\n");
+        assert_ne!(key(&package, &testnet, "compile"), changed_test);
+    }
+
+    #[test]
+    fn key_is_none_without_a_pinned_commit() {
+        let package = Package::new();
+        assert_eq!(cache().key(&package.0, &framework("framework/testnet", None), "compile").unwrap(), None);
+
+        let disabled = CompileCache { enabled: false, ..cache() };
+        assert_eq!(disabled.key(&package.0, &framework("framework/testnet", Some("a1b2c3")), "compile").unwrap(), None);
+    }
+
+    #[test]
+    fn stores_entries_by_key() {
+        let cache = CompileCache {
+            dir: std::env::temp_dir().join(format!("move-cache-test-{:016x}", rand::random::<u64>())),
+            enabled: true,
+        };
+        let key = "ab".repeat(32);
+        assert_eq!(cache.get::<Vec<String>>(&key), None);
+        cache.put(&key, &vec!["hello".to_string()]).unwrap();
+        assert_eq!(cache.get::<Vec<String>>(&key), Some(vec!["hello".to_string()]));
+        assert!(cache.dir.join("ab").join(format!("{}.json", key)).is_file());
+        let _ = std::fs::remove_dir_all(&cache.dir);
+    }
+}
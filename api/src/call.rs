@@ -0,0 +1,363 @@
+// Copyright (c) Move Web Compiler
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::BTreeMap;
+use move_binary_format::access::ModuleAccess;
+use move_binary_format::file_format::{CompiledModule, SignatureToken};
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::TypeTag;
+use move_core_types::parser::parse_type_tag;
+use serde::Deserialize;
+use sui_json::{resolve_move_function_args, ResolvedCallArg, SuiJsonValue};
+use sui_types::base_types::{ObjectID, ObjectRef, SequenceNumber};
+use sui_types::move_package::MovePackage;
+use sui_types::object::Owner;
+use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
+use sui_types::transaction::{CallArg, ObjectArg, TransactionData};
+
+use crate::operations::{ErrorCode, OperationError, MAX_CALL_ARGS};
+use crate::rpc::SuiRpc;
+use crate::transaction::{self, GasParams, UnsignedTransaction};
+
+/// 构建函数调用交易的请求
+#[derive(Clone, Debug, Deserialize)]
+pub struct CallTransactionRequest {
+    /// 调用的包ID
+    pub package: String,
+    /// 模块名
+    pub module: String,
+    /// 函数名
+    pub function: String,
+    /// 类型参数，例如`0x2::sui::SUI`
+    #[serde(default)]
+    pub type_args: Vec<String>,
+    /// JSON格式的参数，对象参数传对象ID，不包括`TxContext`
+    #[serde(default)]
+    pub args: Vec<serde_json::Value>,
+    /// 可选：编译接口返回的`bytecode_base64`，提供时按本地字节码校验参数，否则读取链上的包
+    #[serde(default)]
+    pub modules: Vec<String>,
+    /// 发送方和gas参数
+    #[serde(flatten)]
+    pub gas: GasParams,
+}
+
+/// 按函数签名校验参数，构建调用函数的未签名交易
+///
+/// 参数由`sui-json`按签名转换为BCS，对象参数从节点读取当前版本和所有者，
+/// 共享对象按签名中的引用类型决定是否可变。
+pub async fn build_call_transaction(
+    rpc: &SuiRpc,
+    request: &CallTransactionRequest,
+) -> Result<UnsignedTransaction, OperationError> {
+    let (sender, gas_payment) = request.gas.resolve()?;
+    let package_id = transaction::parse_object_id(&request.package)?;
+    let module = parse_identifier(&request.module)?;
+    let function = parse_identifier(&request.function)?;
+    let type_args = parse_type_args(&request.type_args)?;
+    let args = parse_json_args(&request.args)?;
+
+    let package = if request.modules.is_empty() {
+        load_package(rpc, package_id).await?
+    } else {
+        local_package(package_id, &request.modules)?
+    };
+    let resolved = resolve_move_function_args(&package, module.clone(), function.clone(), &type_args, args)
+        .map_err(|e| invalid_argument(format!("参数与函数签名不匹配: {}", e)))?;
+
+    let mut builder = ProgrammableTransactionBuilder::new();
+    let mut arguments = Vec::with_capacity(resolved.len());
+    for (arg, signature) in resolved {
+        let mutable = is_mutable(&arg, &signature);
+        let argument = match arg {
+            ResolvedCallArg::Pure(bytes) => builder.input(CallArg::Pure(bytes)),
+            ResolvedCallArg::Object(id) => builder.obj(object_arg(rpc, id, mutable).await?),
+            ResolvedCallArg::ObjVec(ids) => {
+                let mut objects = Vec::with_capacity(ids.len());
+                for id in ids {
+                    objects.push(object_arg(rpc, id, mutable).await?);
+                }
+                builder.make_obj_vec(objects)
+            }
+        }
+        .map_err(|e| invalid_argument(e.to_string()))?;
+        arguments.push(argument);
+    }
+    builder.programmable_move_call(package_id, module, function, type_args, arguments);
+
+    let data = TransactionData::new_programmable(
+        sender,
+        gas_payment,
+        builder.finish(),
+        request.gas.gas_budget,
+        request.gas.gas_price,
+    );
+    UnsignedTransaction::new(&data, &[])
+}
+
+// 从节点读取已发布的包
+async fn load_package(rpc: &SuiRpc, package_id: ObjectID) -> Result<MovePackage, OperationError> {
+    let modules = rpc.get_package_modules(package_id).await?;
+    package_from_modules(package_id, &modules)
+}
+
+// 用本地编译的模块构造包，只用于校验函数签名
+fn local_package(package_id: ObjectID, modules: &[String]) -> Result<MovePackage, OperationError> {
+    package_from_modules(package_id, &transaction::decode_modules(modules)?)
+}
+
+fn package_from_modules(package_id: ObjectID, modules: &[Vec<u8>]) -> Result<MovePackage, OperationError> {
+    let mut module_map = BTreeMap::new();
+    for bytes in modules {
+        let module = CompiledModule::deserialize(bytes)
+            .map_err(|e| invalid_argument(format!("无法解析模块字节码: {}", e)))?;
+        module_map.insert(module.self_id().name().to_string(), bytes.clone());
+    }
+    MovePackage::new(package_id, SequenceNumber::new(), module_map, u64::MAX, vec![], BTreeMap::new())
+        .map_err(|e| invalid_argument(format!("无法构造包: {}", e)))
+}
+
+// 对象参数：共享对象需要初始共享版本，其他对象使用当前的对象引用
+async fn object_arg(rpc: &SuiRpc, id: ObjectID, mutable: bool) -> Result<ObjectArg, OperationError> {
+    let object = rpc.get_object(id).await?;
+    Ok(owned_or_shared(id, object.owner, object.object_ref(), mutable))
+}
+
+fn owned_or_shared(id: ObjectID, owner: Option<Owner>, object_ref: ObjectRef, mutable: bool) -> ObjectArg {
+    match owner {
+        Some(Owner::Shared { initial_shared_version }) => ObjectArg::SharedObject {
+            id,
+            initial_shared_version,
+            mutable,
+        },
+        _ => ObjectArg::ImmOrOwnedObject(object_ref),
+    }
+}
+
+/// 共享对象是否以可变方式传入
+///
+/// 不可变引用`&T`只读取对象；`&mut T`和按值传递的对象需要可变。
+/// 对象数组由`MakeMoveVec`按值移入数组，即使参数是`&vector<T>`也需要可变。
+pub(crate) fn is_mutable(arg: &ResolvedCallArg, signature: &SignatureToken) -> bool {
+    match (arg, signature) {
+        (ResolvedCallArg::ObjVec(_), _) => true,
+        (_, SignatureToken::Reference(_)) => false,
+        _ => true,
+    }
+}
+
+/// 解析模块名或函数名
+pub(crate) fn parse_identifier(name: &str) -> Result<Identifier, OperationError> {
+    Identifier::new(name.trim()).map_err(|_| invalid_argument(format!("无效的Move标识符: {}", name)))
+}
+
+/// 解析类型参数，例如`0x2::sui::SUI`
+pub(crate) fn parse_type_args(type_args: &[String]) -> Result<Vec<TypeTag>, OperationError> {
+    type_args
+        .iter()
+        .map(|type_arg| {
+            parse_type_tag(type_arg).map_err(|_| invalid_argument(format!("无效的类型参数: {}", type_arg)))
+        })
+        .collect()
+}
+
+/// 检查参数数量并转换为`sui-json`的参数
+pub(crate) fn parse_json_args(args: &[serde_json::Value]) -> Result<Vec<SuiJsonValue>, OperationError> {
+    if args.len() > MAX_CALL_ARGS {
+        return Err(invalid_argument(format!("参数过多，最多允许 {} 个", MAX_CALL_ARGS)));
+    }
+    args.iter()
+        .map(|arg| SuiJsonValue::new(arg.clone()).map_err(|e| invalid_argument(format!("无效的参数 {}: {}", arg, e))))
+        .collect()
+}
+
+fn invalid_argument(message: impl Into<String>) -> OperationError {
+    OperationError::new(ErrorCode::InvalidArgument, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use base64::Engine;
+    use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+    use serde_json::json;
+    use sui_types::base_types::{SuiAddress, random_object_ref};
+    use sui_types::transaction::{Command, TransactionDataAPI, TransactionKind};
+
+    use crate::compiler;
+    use crate::operations::DEFAULT_GAS_BUDGET;
+    use crate::rpc::RpcConfig;
+    use crate::transaction::ObjectRefInput;
+
+    const SOURCE: &str = "module 0x0::counter {
+    struct Counter has key, store {
+        id: address,
+        value: u64,
+    }
+
+    public fun add(a: u64, b: u64): u64 {
+        a + b
+    }
+
+    public fun touch(read: &Counter, write: &mut Counter, owned: Counter, all: vector<Counter>): (Counter, vector<Counter>) {
+        write.value = read.value;
+        (owned, all)
+    }
+}
+";
+
+    // 编译测试模块，返回Base64编码的字节码
+    fn compile() -> Vec<String> {
+        let dir = std::env::temp_dir().join(format!("move-call-test-{:016x}", rand::random::<u64>()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path: PathBuf = dir.join("counter.move");
+        std::fs::write(&path, SOURCE).unwrap();
+        let result = compiler::build_sources(&[path], &[], &[], &dir);
+        let _ = std::fs::remove_dir_all(&dir);
+        let (modules, _) = result.unwrap_or_else(|e| panic!("编译失败: {:?}", e.diagnostics));
+        modules.into_iter().map(|module| module.base64).collect()
+    }
+
+    fn request(function: &str, args: Vec<serde_json::Value>) -> CallTransactionRequest {
+        let (gas_id, gas_version, gas_digest) = random_object_ref();
+        CallTransactionRequest {
+            package: ObjectID::random().to_string(),
+            module: "counter".to_string(),
+            function: function.to_string(),
+            type_args: vec![],
+            args,
+            modules: compile(),
+            gas: GasParams {
+                sender: SuiAddress::random_for_testing_only().to_string(),
+                gas_budget: DEFAULT_GAS_BUDGET,
+                gas_price: 1_000,
+                gas_payment: vec![ObjectRefInput {
+                    object_id: gas_id.to_string(),
+                    version: gas_version.value(),
+                    digest: gas_digest.to_string(),
+                }],
+            },
+        }
+    }
+
+    // 使用本地字节码时不需要连接节点
+    fn offline_rpc() -> SuiRpc {
+        SuiRpc::new(RpcConfig::default())
+    }
+
+    #[tokio::test]
+    async fn builds_call_from_local_bytecode() {
+        let request = request("add", vec![json!("1"), json!("2")]);
+        let transaction = build_call_transaction(&offline_rpc(), &request).await.unwrap();
+
+        assert_eq!(transaction.sender, request.gas.sender);
+        assert_eq!(transaction.gas_budget, DEFAULT_GAS_BUDGET);
+        assert!(transaction.dependencies.is_empty());
+
+        let bytes = BASE64_STANDARD.decode(&transaction.tx_bytes).unwrap();
+        let data: TransactionData = bcs::from_bytes(&bytes).unwrap();
+        assert_eq!(data.digest().to_string(), transaction.digest);
+        let TransactionKind::ProgrammableTransaction(programmable) = data.kind() else {
+            panic!("不是可编程交易: {:?}", data.kind());
+        };
+        assert_eq!(
+            programmable.inputs,
+            [CallArg::Pure(bcs::to_bytes(&1u64).unwrap()), CallArg::Pure(bcs::to_bytes(&2u64).unwrap())]
+        );
+        match programmable.commands.as_slice() {
+            [Command::MoveCall(call)] => {
+                assert_eq!(call.package.to_string(), request.package);
+                assert_eq!(call.module.as_str(), "counter");
+                assert_eq!(call.function.as_str(), "add");
+                assert_eq!(call.arguments.len(), 2);
+            }
+            commands => panic!("预期一个函数调用: {:?}", commands),
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_wrong_number_of_arguments() {
+        for args in [vec![json!("1")], vec![json!("1"), json!("2"), json!("3")]] {
+            let error = build_call_transaction(&offline_rpc(), &request("add", args)).await.unwrap_err();
+            assert_eq!(error.code, ErrorCode::InvalidArgument);
+            assert!(error.message.starts_with("参数与函数签名不匹配"), "{}", error.message);
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_arguments_of_the_wrong_type() {
+        let error = build_call_transaction(&offline_rpc(), &request("add", vec![json!(true), json!("2")]))
+            .await
+            .unwrap_err();
+        assert_eq!(error.code, ErrorCode::InvalidArgument);
+        assert!(error.message.starts_with("参数与函数签名不匹配"), "{}", error.message);
+    }
+
+    #[tokio::test]
+    async fn rejects_unknown_functions() {
+        let error = build_call_transaction(&offline_rpc(), &request("missing", vec![])).await.unwrap_err();
+        assert_eq!(error.code, ErrorCode::InvalidArgument);
+    }
+
+    #[test]
+    fn shared_objects_follow_the_reference_kind() {
+        let package_id = ObjectID::random();
+        let package = local_package(package_id, &compile()).unwrap();
+        let ids: Vec<ObjectID> = (0..5).map(|_| ObjectID::random()).collect();
+        let args = [
+            json!(ids[0].to_string()),
+            json!(ids[1].to_string()),
+            json!(ids[2].to_string()),
+            json!([ids[3].to_string(), ids[4].to_string()]),
+        ];
+        let args = parse_json_args(&args).unwrap();
+        let resolved = resolve_move_function_args(
+            &package,
+            parse_identifier("counter").unwrap(),
+            parse_identifier("touch").unwrap(),
+            &[],
+            args,
+        )
+        .unwrap();
+
+        // 每个对象都是共享对象时的可变性：&T、&mut T、按值、对象数组中的两个
+        let shared = Some(Owner::Shared {
+            initial_shared_version: SequenceNumber::from_u64(3),
+        });
+        let mutability: Vec<bool> = resolved
+            .iter()
+            .flat_map(|(arg, signature)| {
+                let ids = match arg {
+                    ResolvedCallArg::Object(id) => vec![*id],
+                    ResolvedCallArg::ObjVec(ids) => ids.clone(),
+                    ResolvedCallArg::Pure(_) => panic!("预期对象参数"),
+                };
+                let mutable = is_mutable(arg, signature);
+                ids.into_iter().map(move |id| (id, mutable))
+            })
+            .map(|(id, mutable)| match owned_or_shared(id, shared.clone(), random_object_ref(), mutable) {
+                ObjectArg::SharedObject { id: shared_id, mutable, .. } => {
+                    assert_eq!(shared_id, id);
+                    mutable
+                }
+                other => panic!("预期共享对象: {:?}", other),
+            })
+            .collect();
+        assert_eq!(mutability, [false, true, true, true, true]);
+
+        // 对象数组按值移入数组，即使参数是不可变引用
+        let vector = SignatureToken::Reference(Box::new(SignatureToken::Vector(Box::new(SignatureToken::U64))));
+        assert!(is_mutable(&ResolvedCallArg::ObjVec(vec![]), &vector));
+    }
+
+    #[test]
+    fn owned_objects_use_their_reference() {
+        let object_ref = random_object_ref();
+        let owner = Some(Owner::AddressOwner(SuiAddress::random_for_testing_only()));
+        assert_eq!(
+            owned_or_shared(object_ref.0, owner, object_ref, false),
+            ObjectArg::ImmOrOwnedObject(object_ref)
+        );
+    }
+}
@@ -0,0 +1,407 @@
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+use anyhow::{Result, anyhow};
+use move_compiler::shared::NumericalAddress;
+use move_web_compiler::config::{parse_origins, ServerConfig};
+use move_web_compiler::disassemble::DisassembleOptions;
+use move_web_compiler::format::{FormatOptions, DEFAULT_INDENT_WIDTH, DEFAULT_LINE_LENGTH};
+use move_web_compiler::gas_profile::GasProfileOptions;
+use move_web_compiler::operations::DEFAULT_GAS_BUDGET;
+use move_web_compiler::simulate::{SimulatedCall, DEFAULT_SIMULATION_SENDER};
+use move_web_compiler::unit_test::{UnitTestOptions, DEFAULT_TEST_GAS_LIMIT};
+
+#[derive(Parser)]
+#[clap(version, author, about, long_about = None)]
+pub struct CliOptions {
+    /// Runs the web server when no subcommand is given
+    #[clap(subcommand)]
+    pub commands: Option<Commands>,
+
+    #[clap(short = 'v', long = "verbose", global = true)]
+    pub verbose: bool,
+}
+
+#[derive(Subcommand)]
+pub enum Commands {
+    /// Compile a Move file or package against the Sui framework
+    #[clap(name = "compile")]
+    Compile {
+        /// A `.move` file, or a package directory containing `sources/`
+        #[clap(short = 's', long = "source")]
+        source: PathBuf,
+
+        /// Framework to compile against: testnet, mainnet, devnet or a git revision
+        #[clap(short = 'f', long = "framework")]
+        framework: Option<String>,
+    },
+    /// Compile Move sources directly, without a Move.toml
+    #[clap(name = "build")]
+    Build {
+        /// Move files or directories to compile
+        #[clap(required = true)]
+        targets: Vec<PathBuf>,
+
+        /// Comma-separated directories of dependency sources, e.g. the Sui framework's `sources/`
+        #[clap(long = "dependency_dirs")]
+        dependency_dirs: Option<String>,
+
+        /// Comma-separated named addresses, e.g. `std=0x1,sui=0x2,examples=0x0`
+        #[clap(long = "address_maps")]
+        address_maps: Option<String>,
+
+        /// Directory to write the compiled `.mv` files to
+        #[clap(long = "out_dir", default_value = "build_out")]
+        out_dir: PathBuf,
+    },
+    #[clap(name = "disassemble")]
+    Disassemble(DisassembleArgs),
+    #[clap(name = "test")]
+    Test(TestArgs),
+    /// Generate TypeScript bindings for the structs and callable functions of a package
+    #[clap(name = "codegen")]
+    Codegen {
+        /// Package directory
+        #[clap(default_value = ".")]
+        path: PathBuf,
+
+        /// Directory to write the generated `.ts` files to
+        #[clap(long = "out_dir", default_value = "ts_out")]
+        out_dir: PathBuf,
+
+        /// Published package ID to call, defaults to the package address
+        #[clap(long = "package_id")]
+        package_id: Option<String>,
+    },
+    #[clap(name = "simulate")]
+    Simulate(SimulateArgs),
+    #[clap(name = "format")]
+    Format(FormatArgs),
+    #[clap(name = "serve")]
+    Serve(ServeArgs),
+    /// Unit test worker process, see `unit_test::WORKER_COMMAND`
+    #[clap(name = "__unit-test", hide = true)]
+    UnitTestWorker {
+        package_path: PathBuf,
+        report_path: PathBuf,
+        gas_limit: u64,
+        filter: Option<String>,
+        /// Move the coverage map written by the test runner to this file
+        #[clap(long = "coverage")]
+        coverage: Option<PathBuf>,
+    },
+}
+
+/// Start the web server
+///
+/// Settings are read from the config file, then `MOVE_SERVER_*` environment variables,
+/// then these flags, each overriding the previous one.
+#[derive(Parser, Default)]
+pub struct ServeArgs {
+    /// TOML config file with a `[server]` table, defaults to `$MOVE_SERVER_CONFIG`
+    #[clap(short = 'c', long = "config")]
+    pub config: Option<PathBuf>,
+
+    /// Address to bind to
+    #[clap(long = "host")]
+    pub host: Option<String>,
+
+    /// Port to listen on
+    #[clap(short = 'p', long = "port")]
+    pub port: Option<u16>,
+
+    /// Directory of the frontend assets served at `/`
+    #[clap(long = "static_dir")]
+    pub static_dir: Option<PathBuf>,
+
+    /// Comma-separated CORS origins, e.g. `https://play.example.com`; `*` allows any origin
+    #[clap(long = "allowed_origins")]
+    pub allowed_origins: Option<String>,
+
+    /// Maximum request body size in bytes
+    #[clap(long = "max_body_bytes")]
+    pub max_body_bytes: Option<usize>,
+
+    /// Number of HTTP worker threads, defaults to the number of CPUs
+    #[clap(long = "workers")]
+    pub workers: Option<usize>,
+}
+
+impl ServeArgs {
+    /// Loads the server config and applies the command line overrides
+    pub fn server_config(&self) -> Result<ServerConfig> {
+        self.apply(ServerConfig::load(self.config.as_deref())?)
+    }
+
+    // Flags take precedence over the config file and the environment
+    fn apply(&self, mut config: ServerConfig) -> Result<ServerConfig> {
+        if let Some(host) = &self.host {
+            config.host = host.clone();
+        }
+        if let Some(port) = self.port {
+            config.port = port;
+        }
+        if let Some(static_dir) = &self.static_dir {
+            config.static_dir = static_dir.clone();
+        }
+        if let Some(origins) = &self.allowed_origins {
+            config.allowed_origins = parse_origins(origins);
+        }
+        if let Some(max_body_bytes) = self.max_body_bytes {
+            config.max_body_bytes = max_body_bytes;
+        }
+        if let Some(workers) = self.workers {
+            config.workers = Some(workers);
+        }
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+/// Publish a package to a local in-memory chain and run one of its functions
+///
+/// Nothing is sent to the network: the package and the framework packages live in
+/// a local object store, and objects created by `init` can be passed as arguments.
+#[derive(Parser)]
+pub struct SimulateArgs {
+    /// Package directory
+    #[clap(default_value = ".")]
+    pub path: PathBuf,
+
+    /// Function to run, e.g. `hello::mint`
+    #[clap(long = "function")]
+    pub function: String,
+
+    /// Arguments as a JSON array, e.g. `[42, "0x..."]`; objects are passed by ID
+    #[clap(long = "args", default_value = "[]")]
+    pub args: String,
+
+    /// Type arguments, e.g. `0x2::sui::SUI`; repeat for several
+    #[clap(long = "type_args", multiple_occurrences = true)]
+    pub type_args: Vec<String>,
+
+    /// Sender address
+    #[clap(long = "sender", default_value = DEFAULT_SIMULATION_SENDER)]
+    pub sender: String,
+
+    /// Gas budget of each transaction in MIST
+    #[clap(long = "gas_budget", default_value_t = DEFAULT_GAS_BUDGET)]
+    pub gas_budget: u64,
+
+    /// Print a per-function gas profile of each transaction
+    #[clap(long = "gas_profile")]
+    pub gas_profile: bool,
+
+    /// Write folded stacks for flamegraph tools into this directory, one file per transaction
+    #[clap(long = "folded_out")]
+    pub folded_out: Option<PathBuf>,
+}
+
+impl SimulateArgs {
+    /// Gas profiling requested by `--gas_profile` or `--folded_out`
+    pub fn profile(&self) -> GasProfileOptions {
+        GasProfileOptions {
+            gas_profile: self.gas_profile,
+            folded_stacks: self.folded_out.is_some(),
+        }
+    }
+
+    /// Splits `--function` into module and function and parses `--args`
+    pub fn call(&self) -> Result<SimulatedCall> {
+        let (module, function) = self
+            .function
+            .split_once("::")
+            .ok_or_else(|| anyhow!("--function must look like module::function, got {}", self.function))?;
+        let args: Vec<serde_json::Value> = serde_json::from_str(&self.args)
+            .map_err(|e| anyhow!("--args must be a JSON array: {}", e))?;
+        Ok(SimulatedCall {
+            module: module.to_string(),
+            function: function.to_string(),
+            type_args: self.type_args.clone(),
+            args,
+        })
+    }
+}
+
+/// Run the Move unit tests of a package
+#[derive(Parser)]
+pub struct TestArgs {
+    /// Package directory
+    #[clap(default_value = ".")]
+    pub path: PathBuf,
+
+    /// Only run tests whose name contains this string
+    #[clap(long = "filter")]
+    pub filter: Option<String>,
+
+    /// Maximum number of instructions a single test may execute
+    #[clap(long = "gas_limit", default_value_t = DEFAULT_TEST_GAS_LIMIT)]
+    pub gas_limit: u64,
+
+    /// Print a per-function gas profile of every test
+    #[clap(long = "gas_profile")]
+    pub gas_profile: bool,
+
+    /// Write folded stacks for flamegraph tools into this directory, one file per test
+    #[clap(long = "folded_out")]
+    pub folded_out: Option<PathBuf>,
+
+    /// Print line coverage of every module and function
+    #[clap(long = "coverage")]
+    pub coverage: bool,
+
+    /// Write an lcov coverage report to this file, implies `--coverage`
+    #[clap(long = "lcov")]
+    pub lcov: Option<PathBuf>,
+}
+
+impl TestArgs {
+    /// Unit test options from the flags
+    pub fn options(&self) -> UnitTestOptions {
+        UnitTestOptions {
+            filter: self.filter.clone(),
+            gas_limit: self.gas_limit,
+            profile: GasProfileOptions {
+                gas_profile: self.gas_profile,
+                folded_stacks: self.folded_out.is_some(),
+            },
+            coverage: self.coverage || self.lcov.is_some(),
+        }
+    }
+}
+
+/// Format a Move source file (2024 edition)
+///
+/// Prints the formatted source unless `--write` is given. Files with syntax errors
+/// are left unchanged and the parser diagnostics are printed instead.
+#[derive(Parser)]
+pub struct FormatArgs {
+    /// Move source file
+    pub path: PathBuf,
+
+    /// Number of spaces per indentation level
+    #[clap(long = "indent_width", default_value_t = DEFAULT_INDENT_WIDTH)]
+    pub indent_width: usize,
+
+    /// Maximum line length before comma-separated lists are split one item per line
+    #[clap(long = "line_length", default_value_t = DEFAULT_LINE_LENGTH)]
+    pub line_length: usize,
+
+    /// Overwrite the file instead of printing the result
+    #[clap(long = "write")]
+    pub write: bool,
+}
+
+impl FormatArgs {
+    /// Format options from the flags
+    pub fn options(&self) -> FormatOptions {
+        FormatOptions {
+            indent_width: self.indent_width,
+            line_length: self.line_length,
+        }
+    }
+}
+
+/// Disassemble a compiled module
+#[derive(Parser)]
+pub struct DisassembleArgs {
+    /// A `.mv` file, a base64-encoded module, or a package directory to disassemble
+    pub input: String,
+
+    /// Source map (`.mvsm`) of the module, used together with `--source`
+    #[clap(long = "source_map")]
+    pub source_map: Option<PathBuf>,
+
+    /// Move source file of the module, used together with `--source_map`
+    #[clap(long = "source")]
+    pub source: Option<PathBuf>,
+
+    /// Print the source line before the instructions it compiles to
+    #[clap(long = "interleave_source")]
+    pub interleave_source: bool,
+
+    /// Do not print function bodies
+    #[clap(long = "skip_code")]
+    pub skip_code: bool,
+
+    /// Do not print locals
+    #[clap(long = "skip_locals")]
+    pub skip_locals: bool,
+
+    /// Do not print basic block labels
+    #[clap(long = "skip_basic_blocks")]
+    pub skip_basic_blocks: bool,
+}
+
+impl DisassembleArgs {
+    pub fn options(&self) -> DisassembleOptions {
+        DisassembleOptions {
+            interleave_source: self.interleave_source,
+            skip_code: self.skip_code,
+            skip_locals: self.skip_locals,
+            skip_basic_blocks: self.skip_basic_blocks,
+        }
+    }
+}
+
+impl CliOptions {
+    pub fn parse_deps(deps: String) -> Vec<PathBuf> {
+        deps.split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(PathBuf::from)
+            .collect()
+    }
+
+    pub fn parse_addresses(addresses: String) -> Result<Vec<(String, NumericalAddress)>> {
+        addresses
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| {
+                let parts: Vec<&str> = s.split('=').collect();
+                if parts.len() != 2 || parts[0].trim().is_empty() {
+                    return Err(anyhow!("invalid address mapping `{}`, expected name=address", s));
+                }
+                let name = parts[0].trim().to_string();
+                let addr = NumericalAddress::parse_str(parts[1].trim())
+                    .map_err(|e| anyhow!("invalid address for `{}`: {}", name, e))?;
+                Ok((name, addr))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_override_file_and_environment() {
+        let dir = std::env::temp_dir().join(format!("move-cli-test-{:016x}", rand::random::<u64>()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("server.toml");
+        std::fs::write(&file, "[server]\nhost = \"127.0.0.1\"\nport = 9000\nworkers = 2\n").unwrap();
+
+        let args = ServeArgs {
+            config: Some(file),
+            host: Some("::1".to_string()),
+            allowed_origins: Some("https://play.example.com/".to_string()),
+            ..Default::default()
+        };
+        let env = |name: &str| match name {
+            "MOVE_SERVER_HOST" => Some("10.0.0.1".to_string()),
+            "MOVE_SERVER_PORT" => Some("9100".to_string()),
+            _ => None,
+        };
+        let config = ServerConfig::load_with(args.config.as_deref(), env).and_then(|config| args.apply(config));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let config = config.unwrap();
+        assert_eq!(config.bind_address(), ("::1".to_string(), 9100));
+        assert_eq!(config.workers, Some(2));
+        assert_eq!(config.allowed_origins, ["https://play.example.com"]);
+
+        let args = ServeArgs { workers: Some(0), ..Default::default() };
+        assert!(args.apply(ServerConfig::default()).is_err());
+    }
+}
@@ -0,0 +1,742 @@
+// Copyright (c) Move Web Compiler
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+use crate::abi::{FunctionAbi, ModuleAbi, StructAbi, TypeAbi};
+
+/// 生成文件的开头注释
+const HEADER: &str = "// 由 move-web-compiler 根据编译后的Move包生成，请勿手动修改";
+
+/// 保存包ID和公共函数的文件名（不含扩展名）
+const PACKAGE_FILE: &str = "_package";
+
+/// 导出所有模块的入口文件名（不含扩展名）
+const INDEX_FILE: &str = "index";
+
+// TypeScript的保留字不能用作导出的函数名或常量名
+const RESERVED_WORDS: &[&str] = &[
+    "break", "case", "catch", "class", "const", "continue", "debugger", "default", "delete", "do",
+    "else", "enum", "export", "extends", "false", "finally", "for", "function", "if", "import",
+    "in", "instanceof", "new", "null", "return", "super", "switch", "this", "throw", "true",
+    "try", "typeof", "var", "void", "while", "with", "implements", "interface", "let", "package",
+    "private", "protected", "public", "static", "yield", "await",
+];
+
+/// TypeScript代码生成选项
+#[derive(Clone, Debug, Default)]
+pub struct CodegenOptions {
+    /// 已发布的包ID，为空时使用模块地址（未发布的包为`0x0`，可在运行时调用`setPackageId`设置）
+    pub package_id: Option<String>,
+}
+
+/// 生成的文件
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GeneratedFile {
+    /// 相对于输出目录的路径，例如`counter.ts`
+    pub path: String,
+    /// 文件内容
+    pub content: String,
+}
+
+/// 代码生成结果
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CodegenOutput {
+    /// 生成的文件：每个模块一个文件，另有`_package.ts`和`index.ts`
+    pub files: Vec<GeneratedFile>,
+    /// 无法生成BCS定义的外部类型等提示
+    pub warnings: Vec<String>,
+}
+
+/// 根据模块接口生成TypeScript客户端绑定
+///
+/// 每个模块生成一个文件，包含每个结构体的BCS定义（基于`@mysten/sui/bcs`），
+/// 以及每个公开函数和entry函数的调用封装，向`Transaction`添加`moveCall`。
+/// `TxContext`参数由运行时提供，不出现在封装的参数中。
+pub fn generate_typescript(modules: &[ModuleAbi], options: &CodegenOptions) -> Result<CodegenOutput> {
+    let first = modules.first().ok_or_else(|| anyhow!("没有可以生成绑定的模块"))?;
+    let package_id = match &options.package_id {
+        Some(package_id) => normalize_package_id(package_id)?,
+        None => first.address.clone(),
+    };
+    for module in modules {
+        if module.name == PACKAGE_FILE || module.name == INDEX_FILE {
+            return Err(anyhow!("模块名 {} 与生成的文件名冲突", module.name));
+        }
+    }
+
+    let structs: BTreeMap<(&str, &str, &str), &StructAbi> = modules
+        .iter()
+        .flat_map(|module| {
+            module
+                .structs
+                .iter()
+                .map(move |struct_| ((module.address.as_str(), module.name.as_str(), struct_.name.as_str()), struct_))
+        })
+        .collect();
+
+    let mut output = CodegenOutput::default();
+    output.files.push(GeneratedFile {
+        path: format!("{}.ts", PACKAGE_FILE),
+        content: package_file(&package_id),
+    });
+    for module in modules {
+        let mut writer = ModuleWriter::new(module, &structs);
+        let content = writer.write();
+        output.warnings.append(&mut writer.warnings);
+        output.files.push(GeneratedFile {
+            path: format!("{}.ts", module.name),
+            content,
+        });
+    }
+    output.files.push(GeneratedFile {
+        path: format!("{}.ts", INDEX_FILE),
+        content: index_file(modules),
+    });
+    Ok(output)
+}
+
+// 包ID只允许十六进制地址，避免生成无效的代码
+fn normalize_package_id(package_id: &str) -> Result<String> {
+    let package_id = package_id.trim();
+    let hex = package_id
+        .strip_prefix("0x")
+        .ok_or_else(|| anyhow!("包ID必须以0x开头: {}", package_id))?;
+    if hex.is_empty() || hex.len() > 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(anyhow!("无效的包ID: {}", package_id));
+    }
+    Ok(package_id.to_lowercase())
+}
+
+fn package_file(package_id: &str) -> String {
+    format!(
+        r#"{HEADER}
+import {{ bcs }} from '@mysten/sui/bcs';
+import type {{ BcsType }} from '@mysten/sui/bcs';
+import type {{ Transaction, TransactionArgument, TransactionObjectInput }} from '@mysten/sui/transactions';
+
+/** 调用使用的包ID，发布或升级后可以通过`setPackageId`修改 */
+export let PACKAGE_ID = '{package_id}';
+
+export function setPackageId(packageId: string) {{
+  PACKAGE_ID = packageId;
+}}
+
+/** 是否为交易中已有的参数，例如之前调用的返回值 */
+export function isArgument(value: unknown): value is TransactionArgument {{
+  return typeof value === 'object' && value !== null && '$kind' in value;
+}}
+
+/** 按BCS类型序列化纯值参数，已有的交易参数原样传递 */
+export function pure<Input>(tx: Transaction, type: BcsType<any, Input>, value: Input | TransactionArgument): TransactionArgument {{
+  return isArgument(value) ? value : tx.pure(type.serialize(value as Input));
+}}
+
+/** 对象数组参数 */
+export function objectVector(tx: Transaction, value: TransactionObjectInput[] | TransactionArgument): TransactionArgument {{
+  return isArgument(value) ? value : tx.makeMoveVec({{ elements: value.map((object) => tx.object(object)) }});
+}}
+
+/** 无法生成定义的外部类型，使用时抛出异常 */
+export function unsupported(type: string): BcsType<any> {{
+  const fail = (): never => {{
+    throw new Error(`无法解析外部类型 ${{type}}，请手动提供BCS定义`);
+  }};
+  return bcs.u8().transform({{ input: fail, output: fail }});
+}}
+"#
+    )
+}
+
+fn index_file(modules: &[ModuleAbi]) -> String {
+    let mut content = String::new();
+    let _ = writeln!(content, "{}", HEADER);
+    let _ = writeln!(content, "export {{ PACKAGE_ID, setPackageId }} from './{}';", PACKAGE_FILE);
+    for module in modules {
+        let _ = writeln!(content, "export * as {} from './{}';", module.name, module.name);
+    }
+    content
+}
+
+// 函数参数在交易中的传递方式
+enum ArgumentKind {
+    // 纯值，按BCS序列化
+    Pure { ts_type: String, bcs: String },
+    // 对象，传对象ID或对象引用
+    Object,
+    // 对象数组
+    ObjectVector,
+    // 无法确定传递方式（例如泛型的值参数），只接受已有的交易参数
+    Argument,
+}
+
+// 生成单个模块的文件，同时记录用到的导入
+struct ModuleWriter<'a> {
+    module: &'a ModuleAbi,
+    structs: &'a BTreeMap<(&'a str, &'a str, &'a str), &'a StructAbi>,
+    // 引用了结构体的其他本地模块
+    module_imports: BTreeSet<String>,
+    // 从`_package`导入的名称
+    package_imports: BTreeSet<&'static str>,
+    // 从`@mysten/sui/transactions`导入的类型
+    transaction_imports: BTreeSet<&'static str>,
+    uses_bcs: bool,
+    uses_bcs_type: bool,
+    warnings: Vec<String>,
+}
+
+impl<'a> ModuleWriter<'a> {
+    fn new(module: &'a ModuleAbi, structs: &'a BTreeMap<(&'a str, &'a str, &'a str), &'a StructAbi>) -> Self {
+        Self {
+            module,
+            structs,
+            module_imports: BTreeSet::new(),
+            package_imports: BTreeSet::new(),
+            transaction_imports: BTreeSet::new(),
+            uses_bcs: false,
+            uses_bcs_type: false,
+            warnings: vec![],
+        }
+    }
+
+    fn write(&mut self) -> String {
+        let mut body = String::new();
+        let _ = writeln!(body, "export const MODULE_NAME = '{}';", self.module.name);
+        for struct_ in self.ordered_structs() {
+            body.push('\n');
+            self.write_struct(&mut body, struct_);
+        }
+        let module = self.module;
+        for function in &module.functions {
+            body.push('\n');
+            self.write_function(&mut body, function);
+        }
+
+        let mut content = String::new();
+        let _ = writeln!(content, "{}", HEADER);
+        if self.uses_bcs {
+            let _ = writeln!(content, "import {{ bcs }} from '@mysten/sui/bcs';");
+        }
+        if self.uses_bcs_type {
+            let _ = writeln!(content, "import type {{ BcsType }} from '@mysten/sui/bcs';");
+        }
+        if !self.transaction_imports.is_empty() {
+            let imports: Vec<&str> = self.transaction_imports.iter().copied().collect();
+            let _ = writeln!(content, "import type {{ {} }} from '@mysten/sui/transactions';", imports.join(", "));
+        }
+        if !self.package_imports.is_empty() {
+            let imports: Vec<&str> = self.package_imports.iter().copied().collect();
+            let _ = writeln!(content, "import {{ {} }} from './{}';", imports.join(", "), PACKAGE_FILE);
+        }
+        for module in &self.module_imports {
+            let _ = writeln!(content, "import * as {} from './{}';", module_alias(module), module);
+        }
+        content.push('\n');
+        content.push_str(&body);
+        content
+    }
+
+    // 结构体按字段依赖排序，被引用的定义在前，避免使用未初始化的常量
+    fn ordered_structs(&self) -> Vec<&'a StructAbi> {
+        fn visit<'s>(
+            struct_: &'s StructAbi,
+            by_name: &BTreeMap<&str, &'s StructAbi>,
+            module: &ModuleAbi,
+            visited: &mut BTreeSet<String>,
+            ordered: &mut Vec<&'s StructAbi>,
+        ) {
+            if !visited.insert(struct_.name.clone()) {
+                return;
+            }
+            let mut dependencies = BTreeSet::new();
+            for field in &struct_.fields {
+                collect_same_module_structs(&field.type_, module, &mut dependencies);
+            }
+            for dependency in dependencies {
+                if let Some(dependency) = by_name.get(dependency.as_str()) {
+                    visit(dependency, by_name, module, visited, ordered);
+                }
+            }
+            ordered.push(struct_);
+        }
+
+        let by_name: BTreeMap<&str, &StructAbi> =
+            self.module.structs.iter().map(|struct_| (struct_.name.as_str(), struct_)).collect();
+        let mut visited = BTreeSet::new();
+        let mut ordered = Vec::with_capacity(self.module.structs.len());
+        for struct_ in &self.module.structs {
+            visit(struct_, &by_name, self.module, &mut visited, &mut ordered);
+        }
+        ordered
+    }
+
+    fn write_struct(&mut self, out: &mut String, struct_: &StructAbi) {
+        self.uses_bcs = true;
+        let _ = writeln!(out, "/** {} */", struct_signature(struct_));
+
+        let name = escape_identifier(&struct_.name);
+        let parameters: Vec<String> = struct_
+            .type_parameters
+            .iter()
+            .enumerate()
+            .filter(|(_, parameter)| !parameter.is_phantom)
+            .map(|(index, _)| format!("T{}", index))
+            .collect();
+        let indent = if parameters.is_empty() {
+            let _ = writeln!(out, "export const {} = bcs.struct('{}', {{", name, struct_.name);
+            "  "
+        } else {
+            self.uses_bcs_type = true;
+            let generics: Vec<String> = parameters.iter().map(|p| format!("{} extends BcsType<any>", p)).collect();
+            let arguments: Vec<String> = parameters.iter().map(|p| format!("{}: {}", p, p)).collect();
+            let _ = writeln!(out, "export function {}<{}>({}) {{", name, generics.join(", "), arguments.join(", "));
+            let _ = writeln!(out, "  return bcs.struct('{}', {{", struct_.name);
+            "    "
+        };
+        for field in &struct_.fields {
+            let bcs = self.bcs_type(&field.type_);
+            let _ = writeln!(out, "{}{}: {},", indent, field.name, bcs);
+        }
+        if parameters.is_empty() {
+            let _ = writeln!(out, "}});");
+        } else {
+            let _ = writeln!(out, "  }});");
+            let _ = writeln!(out, "}}");
+        }
+    }
+
+    fn write_function(&mut self, out: &mut String, function: &FunctionAbi) {
+        self.transaction_imports.insert("Transaction");
+        self.package_imports.insert("PACKAGE_ID");
+        let _ = writeln!(out, "/** {} */", function_signature(function));
+
+        // 参数名来自源码映射，没有时按位置命名
+        let names: Vec<String> = if function.parameter_names.len() == function.parameters.len() {
+            function.parameter_names.clone()
+        } else {
+            (0..function.parameters.len()).map(|index| format!("arg{}", index)).collect()
+        };
+        let parameters: Vec<(&String, ArgumentKind)> = names
+            .iter()
+            .zip(&function.parameters)
+            .filter(|(_, type_)| !type_.is_tx_context())
+            .map(|(name, type_)| (name, self.argument_kind(type_)))
+            .collect();
+
+        let _ = writeln!(out, "export function {}(", escape_identifier(&function.name));
+        let _ = writeln!(out, "  tx: Transaction,");
+        if !parameters.is_empty() {
+            let _ = writeln!(out, "  args: {{");
+            for (name, kind) in &parameters {
+                let ts_type = match kind {
+                    ArgumentKind::Pure { ts_type, .. } => format!("{} | TransactionArgument", ts_type),
+                    ArgumentKind::Object => "TransactionObjectInput".to_string(),
+                    ArgumentKind::ObjectVector => "TransactionObjectInput[] | TransactionArgument".to_string(),
+                    ArgumentKind::Argument => "TransactionArgument".to_string(),
+                };
+                let _ = writeln!(out, "    {}: {};", name, ts_type);
+            }
+            let _ = writeln!(out, "  }},");
+        }
+        if !function.type_parameters.is_empty() {
+            let strings = vec!["string"; function.type_parameters.len()];
+            let _ = writeln!(out, "  typeArguments: [{}],", strings.join(", "));
+        }
+        let _ = writeln!(out, ") {{");
+        let _ = writeln!(out, "  return tx.moveCall({{");
+        let _ = writeln!(out, "    target: `${{PACKAGE_ID}}::{}::{}`,", self.module.name, function.name);
+        if !function.type_parameters.is_empty() {
+            let _ = writeln!(out, "    typeArguments,");
+        }
+        if !parameters.is_empty() {
+            let _ = writeln!(out, "    arguments: [");
+            for (name, kind) in &parameters {
+                let argument = match kind {
+                    ArgumentKind::Pure { bcs, .. } => format!("pure(tx, {}, args.{})", bcs, name),
+                    ArgumentKind::Object => format!("tx.object(args.{})", name),
+                    ArgumentKind::ObjectVector => format!("objectVector(tx, args.{})", name),
+                    ArgumentKind::Argument => format!("args.{}", name),
+                };
+                let _ = writeln!(out, "      {},", argument);
+            }
+            let _ = writeln!(out, "    ],");
+        }
+        let _ = writeln!(out, "  }});");
+        let _ = writeln!(out, "}}");
+    }
+
+    fn argument_kind(&mut self, type_: &TypeAbi) -> ArgumentKind {
+        let kind = match type_ {
+            // 纯值可以按引用传递，其他引用都是对象
+            TypeAbi::Reference(inner) | TypeAbi::MutableReference(inner) => match pure_type(inner) {
+                Some((ts_type, bcs)) => ArgumentKind::Pure { ts_type, bcs },
+                None => ArgumentKind::Object,
+            },
+            TypeAbi::TypeParameter(_) | TypeAbi::Signer => ArgumentKind::Argument,
+            _ => match pure_type(type_) {
+                Some((ts_type, bcs)) => ArgumentKind::Pure { ts_type, bcs },
+                None => match type_ {
+                    TypeAbi::Struct { .. } => ArgumentKind::Object,
+                    TypeAbi::Vector(inner) if matches!(inner.as_ref(), TypeAbi::Struct { .. }) => {
+                        ArgumentKind::ObjectVector
+                    }
+                    _ => ArgumentKind::Argument,
+                },
+            },
+        };
+        match kind {
+            ArgumentKind::Pure { .. } => {
+                self.uses_bcs = true;
+                self.package_imports.insert("pure");
+                self.transaction_imports.insert("TransactionArgument");
+            }
+            ArgumentKind::Object => {
+                self.transaction_imports.insert("TransactionObjectInput");
+            }
+            ArgumentKind::ObjectVector => {
+                self.package_imports.insert("objectVector");
+                self.transaction_imports.insert("TransactionArgument");
+                self.transaction_imports.insert("TransactionObjectInput");
+            }
+            ArgumentKind::Argument => {
+                self.transaction_imports.insert("TransactionArgument");
+            }
+        }
+        kind
+    }
+
+    // 结构体字段的BCS类型表达式
+    fn bcs_type(&mut self, type_: &TypeAbi) -> String {
+        match type_ {
+            TypeAbi::Bool => "bcs.bool()".to_string(),
+            TypeAbi::U8 => "bcs.u8()".to_string(),
+            TypeAbi::U16 => "bcs.u16()".to_string(),
+            TypeAbi::U32 => "bcs.u32()".to_string(),
+            TypeAbi::U64 => "bcs.u64()".to_string(),
+            TypeAbi::U128 => "bcs.u128()".to_string(),
+            TypeAbi::U256 => "bcs.u256()".to_string(),
+            TypeAbi::Address | TypeAbi::Signer => "bcs.Address".to_string(),
+            TypeAbi::Vector(inner) => format!("bcs.vector({})", self.bcs_type(inner)),
+            TypeAbi::TypeParameter(index) => format!("T{}", index),
+            TypeAbi::Reference(inner) | TypeAbi::MutableReference(inner) => self.bcs_type(inner),
+            TypeAbi::Struct { address, module, name, type_arguments } => {
+                let structs = self.structs;
+                if let Some(struct_) = structs.get(&(address.as_str(), module.as_str(), name.as_str())) {
+                    return self.local_struct(module, struct_, type_arguments);
+                }
+                match self.framework_struct(address, module, name, type_arguments) {
+                    Some(bcs) => bcs,
+                    None => {
+                        let warning = format!("模块 {}: 无法生成外部类型 {} 的BCS定义", self.module.name, type_);
+                        if !self.warnings.contains(&warning) {
+                            self.warnings.push(warning);
+                        }
+                        self.package_imports.insert("unsupported");
+                        format!("unsupported('{}')", type_)
+                    }
+                }
+            }
+        }
+    }
+
+    // 引用本包中的结构体，phantom类型参数不影响布局，不需要传入
+    fn local_struct(&mut self, module: &str, struct_: &StructAbi, type_arguments: &[TypeAbi]) -> String {
+        let mut reference = escape_identifier(&struct_.name);
+        if module != self.module.name {
+            reference = format!("{}.{}", module_alias(module), reference);
+            self.module_imports.insert(module.to_string());
+        }
+        let arguments: Vec<String> = type_arguments
+            .iter()
+            .zip(&struct_.type_parameters)
+            .filter(|(_, parameter)| !parameter.is_phantom)
+            .map(|(argument, _)| self.bcs_type(argument))
+            .collect();
+        if struct_.type_parameters.iter().any(|parameter| !parameter.is_phantom) {
+            reference = format!("{}({})", reference, arguments.join(", "));
+        }
+        reference
+    }
+
+    // 常用框架类型的BCS定义，只解析影响布局的类型参数
+    fn framework_struct(&mut self, address: &str, module: &str, name: &str, type_arguments: &[TypeAbi]) -> Option<String> {
+        let balance = "bcs.struct('Balance', { value: bcs.u64() })";
+        let bcs = match (address, module, name) {
+            ("0x1", "string", "String") | ("0x1", "ascii", "String") | ("0x2", "url", "Url") => "bcs.string()".to_string(),
+            ("0x1", "type_name", "TypeName") => "bcs.struct('TypeName', { name: bcs.string() })".to_string(),
+            ("0x1", "option", "Option") => format!("bcs.option({})", self.bcs_type(type_arguments.first()?)),
+            ("0x2", "object", "UID") | ("0x2", "object", "ID") => "bcs.Address".to_string(),
+            ("0x2", "balance", "Balance") => balance.to_string(),
+            ("0x2", "balance", "Supply") => "bcs.struct('Supply', { value: bcs.u64() })".to_string(),
+            ("0x2", "coin", "Coin") => format!("bcs.struct('Coin', {{ id: bcs.Address, balance: {} }})", balance),
+            ("0x2", "table", "Table")
+            | ("0x2", "bag", "Bag")
+            | ("0x2", "object_table", "ObjectTable")
+            | ("0x2", "object_bag", "ObjectBag") => {
+                format!("bcs.struct('{}', {{ id: bcs.Address, size: bcs.u64() }})", name)
+            }
+            ("0x2", "table_vec", "TableVec") => {
+                "bcs.struct('TableVec', { contents: bcs.struct('Table', { id: bcs.Address, size: bcs.u64() }) })".to_string()
+            }
+            ("0x2", "vec_set", "VecSet") => {
+                format!("bcs.struct('VecSet', {{ contents: bcs.vector({}) }})", self.bcs_type(type_arguments.first()?))
+            }
+            ("0x2", "vec_map", "VecMap") => {
+                let key = self.bcs_type(type_arguments.first()?);
+                let value = self.bcs_type(type_arguments.get(1)?);
+                format!(
+                    "bcs.struct('VecMap', {{ contents: bcs.vector(bcs.struct('Entry', {{ key: {}, value: {} }})) }})",
+                    key, value,
+                )
+            }
+            _ => return None,
+        };
+        Some(bcs)
+    }
+}
+
+// 可以作为纯值参数传递的类型，返回TypeScript输入类型和BCS类型表达式
+fn pure_type(type_: &TypeAbi) -> Option<(String, String)> {
+    let pure = |ts_type: &str, bcs: &str| Some((ts_type.to_string(), bcs.to_string()));
+    match type_ {
+        TypeAbi::Bool => pure("boolean", "bcs.bool()"),
+        TypeAbi::U8 => pure("number", "bcs.u8()"),
+        TypeAbi::U16 => pure("number", "bcs.u16()"),
+        TypeAbi::U32 => pure("number", "bcs.u32()"),
+        TypeAbi::U64 => pure("bigint | number | string", "bcs.u64()"),
+        TypeAbi::U128 => pure("bigint | number | string", "bcs.u128()"),
+        TypeAbi::U256 => pure("bigint | number | string", "bcs.u256()"),
+        TypeAbi::Address => pure("string", "bcs.Address"),
+        TypeAbi::Vector(inner) if matches!(inner.as_ref(), TypeAbi::U8) => {
+            pure("number[] | Uint8Array", "bcs.vector(bcs.u8())")
+        }
+        TypeAbi::Vector(inner) => {
+            let (ts_type, bcs) = pure_type(inner)?;
+            Some((format!("Array<{}>", ts_type), format!("bcs.vector({})", bcs)))
+        }
+        TypeAbi::Struct { address, module, name, type_arguments } => {
+            match (address.as_str(), module.as_str(), name.as_str()) {
+                ("0x1", "string", "String") | ("0x1", "ascii", "String") => pure("string", "bcs.string()"),
+                ("0x2", "object", "ID") => pure("string", "bcs.Address"),
+                ("0x1", "option", "Option") => {
+                    let (ts_type, bcs) = pure_type(type_arguments.first()?)?;
+                    Some((format!("{} | null", ts_type), format!("bcs.option({})", bcs)))
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+// 收集类型中引用的同一模块的结构体
+fn collect_same_module_structs(type_: &TypeAbi, module: &ModuleAbi, names: &mut BTreeSet<String>) {
+    match type_ {
+        TypeAbi::Struct { address, module: struct_module, name, type_arguments } => {
+            if *address == module.address && *struct_module == module.name {
+                names.insert(name.clone());
+            }
+            for argument in type_arguments {
+                collect_same_module_structs(argument, module, names);
+            }
+        }
+        TypeAbi::Vector(inner) | TypeAbi::Reference(inner) | TypeAbi::MutableReference(inner) => {
+            collect_same_module_structs(inner, module, names)
+        }
+        _ => {}
+    }
+}
+
+// Move语法的结构体声明，用作注释
+fn struct_signature(struct_: &StructAbi) -> String {
+    let mut signature = format!("struct {}", struct_.name);
+    if !struct_.type_parameters.is_empty() {
+        let parameters: Vec<String> = struct_
+            .type_parameters
+            .iter()
+            .enumerate()
+            .map(|(index, parameter)| {
+                let phantom = if parameter.is_phantom { "phantom " } else { "" };
+                format!("{}T{}{}", phantom, index, constraints(&parameter.constraints))
+            })
+            .collect();
+        let _ = write!(signature, "<{}>", parameters.join(", "));
+    }
+    if !struct_.abilities.is_empty() {
+        let _ = write!(signature, " has {}", struct_.abilities.join(", "));
+    }
+    signature
+}
+
+// Move语法的函数签名，用作注释
+fn function_signature(function: &FunctionAbi) -> String {
+    let mut signature = match function.visibility.as_str() {
+        "public" => "public ".to_string(),
+        "friend" => "public(friend) ".to_string(),
+        _ => String::new(),
+    };
+    if function.is_entry {
+        signature.push_str("entry ");
+    }
+    let _ = write!(signature, "fun {}", function.name);
+    if !function.type_parameters.is_empty() {
+        let parameters: Vec<String> = function
+            .type_parameters
+            .iter()
+            .enumerate()
+            .map(|(index, abilities)| format!("T{}{}", index, constraints(abilities)))
+            .collect();
+        let _ = write!(signature, "<{}>", parameters.join(", "));
+    }
+    let parameters: Vec<String> = function
+        .parameters
+        .iter()
+        .enumerate()
+        .map(|(index, type_)| match function.parameter_names.get(index) {
+            Some(name) => format!("{}: {}", name, type_),
+            None => type_.to_string(),
+        })
+        .collect();
+    let _ = write!(signature, "({})", parameters.join(", "));
+    match function.returns.as_slice() {
+        [] => {}
+        [single] => {
+            let _ = write!(signature, ": {}", single);
+        }
+        returns => {
+            let returns: Vec<String> = returns.iter().map(ToString::to_string).collect();
+            let _ = write!(signature, ": ({})", returns.join(", "));
+        }
+    }
+    signature
+}
+
+fn constraints(abilities: &[String]) -> String {
+    if abilities.is_empty() {
+        String::new()
+    } else {
+        format!(": {}", abilities.join(" + "))
+    }
+}
+
+// 导入其他模块时使用的别名，避免与本模块的函数名冲突
+fn module_alias(module: &str) -> String {
+    format!("{}_module", module)
+}
+
+fn escape_identifier(name: &str) -> String {
+    if RESERVED_WORDS.contains(&name) {
+        format!("{}_", name)
+    } else {
+        name.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use move_compiler::Compiler;
+
+    use crate::abi;
+
+    // test_example.move中examples::hello模块的接口
+    const HELLO_ABI: &str = include_str!("../tests/golden/codegen/hello_abi.json");
+
+    // shop包的源码，依赖只保留声明的框架模块
+    const SHOP_SOURCES: [(&str, &str); 2] = [
+        ("item.move", include_str!("../tests/golden/codegen/shop/item.move")),
+        ("shop.move", include_str!("../tests/golden/codegen/shop/shop.move")),
+    ];
+    const FRAMEWORK_SOURCE: &str = include_str!("../tests/golden/codegen/shop/framework.move");
+
+    // abi.rs从编译后的shop包提取的接口
+    const SHOP_ABI: &str = include_str!("../tests/golden/codegen/shop/abi.json");
+
+    fn golden(path: &str) -> &'static str {
+        match path {
+            // 包ID相同，两个包的_package.ts一致
+            "_package.ts" | "shop/_package.ts" => include_str!("../tests/golden/codegen/_package.ts"),
+            "hello.ts" => include_str!("../tests/golden/codegen/hello.ts"),
+            "index.ts" => include_str!("../tests/golden/codegen/index.ts"),
+            "shop/item.ts" => include_str!("../tests/golden/codegen/shop/item.ts"),
+            "shop/shop.ts" => include_str!("../tests/golden/codegen/shop/shop.ts"),
+            "shop/index.ts" => include_str!("../tests/golden/codegen/shop/index.ts"),
+            _ => panic!("没有 {} 的预期输出", path),
+        }
+    }
+
+    #[test]
+    fn generates_hello_bindings() {
+        let module: ModuleAbi = serde_json::from_str(HELLO_ABI).unwrap();
+        let output = generate_typescript(&[module], &CodegenOptions::default()).unwrap();
+
+        let paths: Vec<&str> = output.files.iter().map(|file| file.path.as_str()).collect();
+        assert_eq!(paths, ["_package.ts", "hello.ts", "index.ts"]);
+        for file in &output.files {
+            assert_eq!(file.content, golden(&file.path), "{} 与预期输出不一致", file.path);
+        }
+        assert!(output.warnings.is_empty(), "{:?}", output.warnings);
+    }
+
+    #[test]
+    fn hello_bindings_use_given_package_id() {
+        let module: ModuleAbi = serde_json::from_str(HELLO_ABI).unwrap();
+        let options = CodegenOptions { package_id: Some("0x2a".to_string()) };
+        let output = generate_typescript(&[module], &options).unwrap();
+
+        let package = output.files.iter().find(|file| file.path == "_package.ts").unwrap();
+        assert!(package.content.contains("export let PACKAGE_ID = '0x2a';"));
+    }
+
+    // 编译shop包，返回按模块名排序的接口
+    fn compile_shop() -> Vec<ModuleAbi> {
+        let dir = std::env::temp_dir().join(format!("move-codegen-test-{:016x}", rand::random::<u64>()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let write = |name: &str, source: &str| {
+            let path = dir.join(name);
+            std::fs::write(&path, source).unwrap();
+            path.display().to_string()
+        };
+        let targets: Vec<String> = SHOP_SOURCES.iter().map(|(name, source)| write(name, source)).collect();
+        let dependencies = vec![write("framework.move", FRAMEWORK_SOURCE)];
+        let result = Compiler::from_files(targets, dependencies, BTreeMap::new()).build();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let (_, units) = result.unwrap();
+        let (units, _) = units.unwrap_or_else(|_| panic!("编译失败"));
+        let mut modules: Vec<ModuleAbi> = units
+            .into_iter()
+            .map(|unit| {
+                let unit = unit.into_compiled_unit();
+                abi::module_abi(&unit.module, Some(&unit.source_map))
+            })
+            .collect();
+        modules.sort_by(|a, b| a.name.cmp(&b.name));
+        modules
+    }
+
+    #[test]
+    fn shop_abi_matches_compiled_package() {
+        // 参数名去掉了编译器的后缀，依赖中的框架模块不输出
+        let expected: serde_json::Value = serde_json::from_str(SHOP_ABI).unwrap();
+        assert_eq!(serde_json::to_value(compile_shop()).unwrap(), expected);
+    }
+
+    #[test]
+    fn generates_shop_bindings() {
+        // 覆盖纯值、对象、可变对象、对象数组、泛型参数、省略的TxContext，以及跨模块和框架类型
+        let modules: Vec<ModuleAbi> = serde_json::from_str(SHOP_ABI).unwrap();
+        let output = generate_typescript(&modules, &CodegenOptions::default()).unwrap();
+
+        let paths: Vec<&str> = output.files.iter().map(|file| file.path.as_str()).collect();
+        assert_eq!(paths, ["_package.ts", "item.ts", "shop.ts", "index.ts"]);
+        for file in &output.files {
+            let path = format!("shop/{}", file.path);
+            assert_eq!(file.content, golden(&path), "{} 与预期输出不一致", path);
+        }
+        assert!(output.warnings.is_empty(), "{:?}", output.warnings);
+    }
+}
@@ -0,0 +1,288 @@
+// Copyright (c) Move Web Compiler
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use anyhow::anyhow;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use fastcrypto::hash::{Blake2b256, HashFunction};
+use move_binary_format::CompiledModule;
+use move_compiler::Compiler;
+use move_compiler::compiled_unit::AnnotatedCompiledUnit;
+use move_compiler::shared::{NumericalAddress, PackageName};
+use move_package::compilation::build_plan::BuildPlan;
+use move_package::compilation::compiled_package::CompiledPackage as MoveCompiledPackage;
+use move_package::resolution::resolution_graph::ResolvedGraph;
+use serde::{Deserialize, Serialize};
+use sui_move_build::{BuildConfig, CompiledPackage};
+use sui_protocol_config::ProtocolConfig;
+use sui_types::move_package::{FnInfo, FnInfoKey, FnInfoMap};
+
+use crate::diagnostics::{self, Diagnostic};
+
+/// 编译成功的结果
+pub struct BuildOutput {
+    /// Sui编译后的包
+    pub package: CompiledPackage,
+    /// 编译过程中产生的诊断（只包含警告等非错误诊断）
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// 根包中单个模块的字节码信息
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CompiledModuleInfo {
+    /// 模块地址（十六进制）
+    pub address: String,
+    /// 模块名称
+    pub name: String,
+    /// 序列化后的字节码长度
+    pub size: usize,
+    /// Base64编码的字节码
+    pub base64: String,
+    /// 字节码的Blake2b-256摘要（十六进制）
+    pub digest: String,
+    /// 定义该模块的源文件（包内文件为相对包根目录的路径）
+    pub source_file: String,
+}
+
+impl CompiledModuleInfo {
+    /// 解码后的字节码
+    pub fn bytes(&self) -> anyhow::Result<Vec<u8>> {
+        BASE64_STANDARD
+            .decode(&self.base64)
+            .map_err(|e| anyhow!("无法解码模块 {} 的字节码: {}", self.name, e))
+    }
+}
+
+/// 编译失败的结果
+#[derive(Debug)]
+pub struct BuildFailure {
+    /// 失败原因
+    pub message: String,
+    /// 结构化诊断，依赖解析等非编译器错误时为空
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl BuildFailure {
+    /// 构造不带诊断的失败结果
+    pub fn message(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            diagnostics: vec![],
+        }
+    }
+}
+
+impl fmt::Display for BuildFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for BuildFailure {}
+
+impl From<anyhow::Error> for BuildFailure {
+    fn from(e: anyhow::Error) -> Self {
+        BuildFailure::message(e.to_string())
+    }
+}
+
+impl From<std::io::Error> for BuildFailure {
+    fn from(e: std::io::Error) -> Self {
+        BuildFailure::message(e.to_string())
+    }
+}
+
+/// 编译Move包并收集结构化诊断
+///
+/// sui-move-build只会把诊断打印到stderr，这里自己驱动编译器编译一遍，
+/// 同时取出诊断和编译结果，再按sui-move-build的方式组装出`CompiledPackage`。
+pub fn build_package(config: BuildConfig, package_path: &Path) -> Result<BuildOutput, BuildFailure> {
+    let run_bytecode_verifier = config.run_bytecode_verifier;
+    let chain_id = config.chain_id.clone();
+
+    let resolution_graph = config
+        .resolution_graph(package_path, chain_id.clone())
+        .map_err(|e| BuildFailure::message(format!("解析包依赖失败: {}", e)))?;
+    let (published_at, dependency_ids) = sui_move_build::gather_published_ids(&resolution_graph, chain_id);
+    let bytecode_deps = bytecode_dependencies(&resolution_graph)?;
+
+    let build_plan = BuildPlan::create(&resolution_graph)?;
+    let mut diagnostics = Vec::new();
+    let mut fn_info = FnInfoMap::new();
+    let result = build_plan.compile_with_driver(&mut std::io::sink(), |compiler| {
+        let (files, units_res) = compiler.build()?;
+        match units_res {
+            Ok((units, warning_diags)) => {
+                diagnostics = diagnostics::from_compiler_diagnostics(&files, warning_diags, package_path);
+                fn_info = function_infos(&units);
+                Ok((files, units))
+            }
+            Err(error_diags) => {
+                diagnostics = diagnostics::from_compiler_diagnostics(&files, error_diags, package_path);
+                anyhow::bail!("编译失败")
+            }
+        }
+    });
+
+    let package = match result {
+        Ok(package) => package,
+        Err(_) if diagnostics.iter().any(Diagnostic::is_error) => {
+            return Err(BuildFailure {
+                message: "编译失败".to_string(),
+                diagnostics,
+            })
+        }
+        Err(e) => {
+            return Err(BuildFailure {
+                message: format!("编译失败: {}", e),
+                diagnostics,
+            })
+        }
+    };
+
+    if run_bytecode_verifier {
+        if let Err(e) = verify_bytecode(&package, &fn_info) {
+            return Err(BuildFailure {
+                message: format!("字节码校验失败: {}", e),
+                diagnostics,
+            });
+        }
+    }
+
+    let package = CompiledPackage {
+        package,
+        published_at,
+        dependency_ids,
+        bytecode_deps,
+    };
+    Ok(BuildOutput { package, diagnostics })
+}
+
+// 只以字节码形式提供的依赖包不会出现在编译结果里，需要单独读出来
+fn bytecode_dependencies(resolution_graph: &ResolvedGraph) -> Result<Vec<(PackageName, CompiledModule)>, BuildFailure> {
+    let mut bytecode_deps = Vec::new();
+    for (name, pkg) in resolution_graph.package_table.iter() {
+        if !pkg.get_sources(&resolution_graph.build_options)?.is_empty() {
+            continue;
+        }
+        for bytes in pkg.get_bytecodes_bytes()? {
+            let module = CompiledModule::deserialize_with_defaults(&bytes)
+                .map_err(|e| BuildFailure::message(format!("无法解析依赖包 {} 的字节码: {}", name, e)))?;
+            bytecode_deps.push((*name, module));
+        }
+    }
+    Ok(bytecode_deps)
+}
+
+// 记录每个函数是否为测试函数，Sui的入口函数校验会跳过测试函数
+fn function_infos(units: &[AnnotatedCompiledUnit]) -> FnInfoMap {
+    let mut fn_info = FnInfoMap::new();
+    for unit in units {
+        let mod_addr = unit.named_module.address.into_inner();
+        for (_, name, info) in &unit.function_infos {
+            fn_info.insert(
+                FnInfoKey { fn_name: name.to_string(), mod_addr },
+                FnInfo { is_test: info.attributes.is_test_or_test_only() },
+            );
+        }
+    }
+    fn_info
+}
+
+// 与发布时相同的Move和Sui字节码校验，只校验根包中的模块
+fn verify_bytecode(package: &MoveCompiledPackage, fn_info: &FnInfoMap) -> anyhow::Result<()> {
+    let verifier_config = ProtocolConfig::get_for_max_version_UNSAFE().verifier_config(/* signing_limits */ None);
+    for unit in package.root_modules() {
+        let module = &unit.unit.module;
+        move_bytecode_verifier::verify_module_unmetered(module)
+            .map_err(|e| anyhow!("模块 {}: {}", unit.unit.name, e))?;
+        sui_bytecode_verifier::sui_verify_module_unmetered(module, fn_info, &verifier_config)
+            .map_err(|e| anyhow!("模块 {}: {}", unit.unit.name, e))?;
+    }
+    Ok(())
+}
+
+/// 不依赖Move.toml，直接用编译器编译源文件
+///
+/// `targets`和`dependencies`可以是`.move`文件或目录，只有`targets`中的模块会输出字节码。
+/// 诊断和模块中的文件路径相对于`root`。
+pub fn build_sources(
+    targets: &[PathBuf],
+    dependencies: &[PathBuf],
+    named_addresses: &[(String, NumericalAddress)],
+    root: &Path,
+) -> Result<(Vec<CompiledModuleInfo>, Vec<Diagnostic>), BuildFailure> {
+    let to_strings = |paths: &[PathBuf]| paths.iter().map(|p| p.display().to_string()).collect::<Vec<_>>();
+    let named_address_map: BTreeMap<String, NumericalAddress> = named_addresses.iter().cloned().collect();
+
+    let (files, units_res) = Compiler::from_files(to_strings(targets), to_strings(dependencies), named_address_map)
+        .build()
+        .map_err(|e| BuildFailure::message(format!("编译失败: {}", e)))?;
+    let (units, warning_diags) = units_res.map_err(|error_diags| BuildFailure {
+        message: "编译失败".to_string(),
+        diagnostics: diagnostics::from_compiler_diagnostics(&files, error_diags, root),
+    })?;
+    let diagnostics = diagnostics::from_compiler_diagnostics(&files, warning_diags, root);
+
+    let mut modules = Vec::new();
+    for annotated in units {
+        let source_path = PathBuf::from(files.filename(&annotated.loc.file_hash()).to_string());
+        let unit = annotated.into_compiled_unit();
+        let mut bytes = Vec::new();
+        unit.module.serialize_with_version(unit.module.version, &mut bytes)?;
+        modules.push(CompiledModuleInfo {
+            address: unit.module.address().to_hex_literal(),
+            name: unit.name.to_string(),
+            size: bytes.len(),
+            digest: hex::encode(Blake2b256::digest(&bytes).digest),
+            base64: BASE64_STANDARD.encode(&bytes),
+            source_file: source_path.strip_prefix(root).unwrap_or(&source_path).display().to_string(),
+        });
+    }
+    Ok((modules, diagnostics))
+}
+
+/// 序列化根包中的每个模块，按发布时的依赖顺序返回
+///
+/// 模块与字节码按内容匹配，不依赖模块名称或迭代顺序。
+pub fn root_modules(package: &CompiledPackage, package_path: &Path) -> anyhow::Result<Vec<CompiledModuleInfo>> {
+    // get_package_bytes按模块间依赖做了拓扑排序，发布时需要这个顺序
+    let publish_order = package.get_package_bytes(false);
+
+    let mut modules = Vec::new();
+    for unit in package.package.root_modules() {
+        let module = &unit.unit.module;
+        let mut bytes = Vec::new();
+        module.serialize_with_version(module.version, &mut bytes)?;
+
+        let position = publish_order.iter().position(|b| *b == bytes).unwrap_or(usize::MAX);
+        modules.push((position, CompiledModuleInfo {
+            address: module.address().to_hex_literal(),
+            name: unit.unit.name.to_string(),
+            size: bytes.len(),
+            digest: hex::encode(Blake2b256::digest(&bytes).digest),
+            base64: BASE64_STANDARD.encode(&bytes),
+            source_file: unit
+                .source_path
+                .strip_prefix(package_path)
+                .unwrap_or(&unit.source_path)
+                .display()
+                .to_string(),
+        }));
+    }
+
+    modules.sort_by_key(|(position, _)| *position);
+    Ok(modules.into_iter().map(|(_, info)| info).collect())
+}
+
+/// 根包依赖的已发布包ID，发布交易需要引用这些包
+pub fn dependency_ids(package: &CompiledPackage) -> Vec<String> {
+    package
+        .get_dependency_storage_package_ids()
+        .iter()
+        .map(|id| id.to_string())
+        .collect()
+}
@@ -0,0 +1,280 @@
+// Copyright (c) Move Web Compiler
+// SPDX-License-Identifier: Apache-2.0
+
+use std::path::{Path, PathBuf};
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+/// 默认监听地址
+pub const DEFAULT_HOST: &str = "0.0.0.0";
+
+/// 默认端口
+pub const DEFAULT_PORT: u16 = 8081;
+
+/// 默认的请求体大小上限（字节）
+pub const DEFAULT_MAX_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+/// 允许任意来源的CORS配置值
+pub const ANY_ORIGIN: &str = "*";
+
+/// Web服务器配置
+///
+/// 按默认值、配置文件（`[server]`段）、环境变量、命令行参数的顺序逐层覆盖。
+/// 配置文件中拼错或不支持的配置项会报错，不会被忽略。
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    /// 监听地址
+    pub host: String,
+    /// 监听端口
+    pub port: u16,
+    /// 前端静态文件目录，配置文件中的相对路径相对于配置文件所在目录
+    pub static_dir: PathBuf,
+    /// 允许的跨域来源，`*`表示允许任意来源
+    pub allowed_origins: Vec<String>,
+    /// 请求体大小上限（字节）
+    pub max_body_bytes: usize,
+    /// 工作线程数，为空时使用CPU核数
+    pub workers: Option<usize>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            host: DEFAULT_HOST.to_string(),
+            port: DEFAULT_PORT,
+            static_dir: PathBuf::from("./static"),
+            allowed_origins: vec![ANY_ORIGIN.to_string()],
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+            workers: None,
+        }
+    }
+}
+
+// 配置文件结构，服务器配置位于[server]段
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    #[serde(default)]
+    server: Option<ServerConfig>,
+}
+
+impl ServerConfig {
+    /// 读取配置：`path`为空时使用环境变量`MOVE_SERVER_CONFIG`指定的文件，都没有时只使用默认值和环境变量
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        Self::load_with(path, env_string)
+    }
+
+    /// 与`load`相同，环境变量的值从`var`读取
+    pub fn load_with(path: Option<&Path>, var: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let path = path
+            .map(Path::to_path_buf)
+            .or_else(|| var("MOVE_SERVER_CONFIG").map(PathBuf::from));
+        let mut config = match path {
+            Some(path) => Self::from_file(&path)?,
+            None => Self::default(),
+        };
+        config.apply_vars(var)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// 从TOML配置文件读取
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path).map_err(|e| anyhow!("无法读取配置文件 {:?}: {}", path, e))?;
+        let file: ConfigFile = toml::from_str(&content).map_err(|e| anyhow!("无法解析配置文件 {:?}: {}", path, e))?;
+        let mut config = file.server.unwrap_or_default();
+        // 与环境变量和命令行的值一样去掉空白和末尾的`/`
+        config.allowed_origins = parse_origins(&config.allowed_origins.join(","));
+        if config.static_dir.is_relative() {
+            if let Some(dir) = path.parent() {
+                config.static_dir = dir.join(&config.static_dir);
+            }
+        }
+        Ok(config)
+    }
+
+    /// 应用环境变量：`MOVE_SERVER_HOST`、`MOVE_SERVER_PORT`、`MOVE_SERVER_STATIC_DIR`、
+    /// `MOVE_SERVER_ALLOWED_ORIGINS`（逗号分隔）、`MOVE_SERVER_MAX_BODY_BYTES`、`MOVE_SERVER_WORKERS`
+    pub fn apply_env(&mut self) -> Result<()> {
+        self.apply_vars(env_string)
+    }
+
+    /// 应用`var`返回的环境变量值，变量名与`apply_env`相同
+    pub fn apply_vars(&mut self, var: impl Fn(&str) -> Option<String>) -> Result<()> {
+        if let Some(host) = var("MOVE_SERVER_HOST") {
+            self.host = host;
+        }
+        if let Some(port) = var("MOVE_SERVER_PORT") {
+            self.port = port.parse().map_err(|_| anyhow!("无效的MOVE_SERVER_PORT: {}", port))?;
+        }
+        if let Some(static_dir) = var("MOVE_SERVER_STATIC_DIR") {
+            self.static_dir = PathBuf::from(static_dir);
+        }
+        if let Some(origins) = var("MOVE_SERVER_ALLOWED_ORIGINS") {
+            self.allowed_origins = parse_origins(&origins);
+        }
+        if let Some(max_body) = var("MOVE_SERVER_MAX_BODY_BYTES") {
+            self.max_body_bytes = max_body
+                .parse()
+                .map_err(|_| anyhow!("无效的MOVE_SERVER_MAX_BODY_BYTES: {}", max_body))?;
+        }
+        if let Some(workers) = var("MOVE_SERVER_WORKERS") {
+            self.workers = Some(workers.parse().map_err(|_| anyhow!("无效的MOVE_SERVER_WORKERS: {}", workers))?);
+        }
+        Ok(())
+    }
+
+    /// 检查配置是否有效
+    pub fn validate(&self) -> Result<()> {
+        if self.host.trim().is_empty() {
+            return Err(anyhow!("监听地址不能为空"));
+        }
+        if self.max_body_bytes == 0 {
+            return Err(anyhow!("请求体大小上限必须大于0"));
+        }
+        if self.workers == Some(0) {
+            return Err(anyhow!("工作线程数必须大于0"));
+        }
+        for origin in &self.allowed_origins {
+            if origin != ANY_ORIGIN && !(origin.starts_with("http://") || origin.starts_with("https://")) {
+                return Err(anyhow!("无效的跨域来源: {}，需要以http://或https://开头", origin));
+            }
+        }
+        Ok(())
+    }
+
+    /// 是否允许任意跨域来源
+    pub fn allows_any_origin(&self) -> bool {
+        self.allowed_origins.iter().any(|origin| origin == ANY_ORIGIN)
+    }
+
+    /// 监听地址，例如`0.0.0.0:8081`
+    pub fn bind_address(&self) -> (String, u16) {
+        (self.host.clone(), self.port)
+    }
+}
+
+/// 解析逗号分隔的跨域来源列表
+pub fn parse_origins(origins: &str) -> Vec<String> {
+    origins
+        .split(',')
+        .map(|origin| origin.trim().trim_end_matches('/').to_string())
+        .filter(|origin| !origin.is_empty())
+        .collect()
+}
+
+fn env_string(name: &str) -> Option<String> {
+    std::env::var(name).ok().map(|value| value.trim().to_string()).filter(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    // 测试用的配置目录，离开作用域时删除
+    struct ConfigDir(PathBuf);
+
+    impl ConfigDir {
+        fn new(content: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("move-config-test-{:016x}", rand::random::<u64>()));
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join("server.toml"), content).unwrap();
+            Self(dir)
+        }
+
+        fn file(&self) -> PathBuf {
+            self.0.join("server.toml")
+        }
+    }
+
+    impl Drop for ConfigDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn vars(pairs: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    #[test]
+    fn uses_defaults_without_file_or_variables() {
+        let config = ServerConfig::load_with(None, vars(&[])).unwrap();
+        assert_eq!(config.bind_address(), (DEFAULT_HOST.to_string(), DEFAULT_PORT));
+        assert_eq!(config.static_dir, PathBuf::from("./static"));
+        assert!(config.allows_any_origin());
+        assert_eq!(config.max_body_bytes, DEFAULT_MAX_BODY_BYTES);
+        assert_eq!(config.workers, None);
+    }
+
+    #[test]
+    fn variables_override_the_file() {
+        let dir = ConfigDir::new("[server]\nhost = \"127.0.0.1\"\nport = 9000\nworkers = 2\n");
+        let config = ServerConfig::load_with(
+            Some(&dir.file()),
+            vars(&[("MOVE_SERVER_PORT", "9100"), ("MOVE_SERVER_MAX_BODY_BYTES", "1024")]),
+        )
+        .unwrap();
+        assert_eq!(config.bind_address(), ("127.0.0.1".to_string(), 9100));
+        assert_eq!(config.max_body_bytes, 1024);
+        assert_eq!(config.workers, Some(2));
+
+        // 没有指定路径时从MOVE_SERVER_CONFIG读取配置文件
+        let file = dir.file().display().to_string();
+        let config = ServerConfig::load_with(None, vars(&[("MOVE_SERVER_CONFIG", file.as_str())])).unwrap();
+        assert_eq!(config.port, 9000);
+    }
+
+    #[test]
+    fn rejects_invalid_variables() {
+        for (name, value) in [
+            ("MOVE_SERVER_PORT", "http"),
+            ("MOVE_SERVER_WORKERS", "0"),
+            ("MOVE_SERVER_MAX_BODY_BYTES", "0"),
+            ("MOVE_SERVER_ALLOWED_ORIGINS", "example.com"),
+        ] {
+            assert!(ServerConfig::load_with(None, vars(&[(name, value)])).is_err(), "{}={}", name, value);
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        for content in ["[server]\nprot = 9000\n", "[sever]\nport = 9000\n", "port = 9000\n"] {
+            let dir = ConfigDir::new(content);
+            assert!(ServerConfig::from_file(&dir.file()).is_err(), "{}", content);
+        }
+    }
+
+    #[test]
+    fn normalizes_origins() {
+        let dir = ConfigDir::new("[server]\nallowed_origins = [\" https://a.example.com/ \", \"\", \"http://localhost:3000\"]\n");
+        let config = ServerConfig::from_file(&dir.file()).unwrap();
+        assert_eq!(config.allowed_origins, ["https://a.example.com", "http://localhost:3000"]);
+        assert!(!config.allows_any_origin());
+
+        let config = ServerConfig::load_with(
+            Some(&dir.file()),
+            vars(&[("MOVE_SERVER_ALLOWED_ORIGINS", "https://b.example.com//, *,")]),
+        )
+        .unwrap();
+        assert_eq!(config.allowed_origins, ["https://b.example.com", "*"]);
+        assert!(config.allows_any_origin());
+    }
+
+    #[test]
+    fn resolves_static_dir_against_the_config_file() {
+        let dir = ConfigDir::new("[server]\nstatic_dir = \"public\"\n");
+        assert_eq!(ServerConfig::from_file(&dir.file()).unwrap().static_dir, dir.0.join("public"));
+
+        let absolute = std::env::temp_dir().join("move-static");
+        let dir = ConfigDir::new(&format!("[server]\nstatic_dir = {:?}\n", absolute.display().to_string()));
+        assert_eq!(ServerConfig::from_file(&dir.file()).unwrap().static_dir, absolute);
+
+        // 环境变量中的相对路径相对于工作目录，不做转换
+        let config = ServerConfig::load_with(Some(&dir.file()), vars(&[("MOVE_SERVER_STATIC_DIR", "dist")])).unwrap();
+        assert_eq!(config.static_dir, PathBuf::from("dist"));
+    }
+}
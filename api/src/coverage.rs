@@ -0,0 +1,400 @@
+// Copyright (c) Move Web Compiler
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::Path;
+use anyhow::{Result, anyhow};
+use move_binary_format::file_format::{CodeOffset, CompiledModule, FunctionDefinitionIndex};
+use move_bytecode_source_map::source_map::SourceMap;
+use move_coverage::coverage_map::{CoverageMap, ModuleCoverageMap};
+use serde::{Deserialize, Serialize};
+use sui_move_build::CompiledPackage;
+
+/// 测试运行器开启覆盖率统计时，在包目录下写入的覆盖率数据，工作进程随后把它移到本次运行的临时目录
+pub const COVERAGE_MAP_FILE: &str = ".coverage_map.mvcov";
+
+/// 一行源码的执行次数
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineHits {
+    /// 行号，从1开始
+    pub line: usize,
+    /// 该行字节码被执行的最多次数，0表示未覆盖
+    pub hits: u64,
+}
+
+/// 单个函数的行覆盖率
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FunctionCoverage {
+    /// 函数名
+    pub name: String,
+    /// 函数名所在的行
+    pub line: usize,
+    /// 函数被调用的次数
+    pub calls: u64,
+    /// 有字节码的行数
+    pub lines: usize,
+    /// 被执行过的行数
+    pub covered_lines: usize,
+    /// 行覆盖率（百分比）
+    pub percent: f64,
+}
+
+/// 单个模块的行覆盖率
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ModuleCoverage {
+    /// 模块地址
+    pub address: String,
+    /// 模块名
+    pub name: String,
+    /// 源文件相对于包目录的路径，例如`sources/hello.move`
+    pub source_file: String,
+    /// 有字节码的行数
+    pub lines: usize,
+    /// 被执行过的行数
+    pub covered_lines: usize,
+    /// 行覆盖率（百分比）
+    pub percent: f64,
+    /// 每个函数的覆盖率，不包括原生函数
+    pub functions: Vec<FunctionCoverage>,
+    /// 每行的执行次数，按行号排列，用于在编辑器中标记已覆盖和未覆盖的行
+    pub line_hits: Vec<LineHits>,
+}
+
+/// 一次测试运行的覆盖率报告
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CoverageReport {
+    /// 所有模块中有字节码的行数
+    pub lines: usize,
+    /// 所有模块中被执行过的行数
+    pub covered_lines: usize,
+    /// 总的行覆盖率（百分比）
+    pub percent: f64,
+    /// 根包中每个模块的覆盖率，不包括`tests/`目录中的测试模块
+    pub modules: Vec<ModuleCoverage>,
+    /// lcov格式的覆盖率报告，源文件路径相对于包目录
+    pub lcov: String,
+}
+
+impl CoverageReport {
+    fn new(modules: Vec<ModuleCoverage>) -> Self {
+        let lines = modules.iter().map(|m| m.lines).sum();
+        let covered_lines = modules.iter().map(|m| m.covered_lines).sum();
+        let lcov = to_lcov(&modules);
+        Self {
+            lines,
+            covered_lines,
+            percent: percent(covered_lines, lines),
+            modules,
+            lcov,
+        }
+    }
+}
+
+/// 读取`map_path`中的覆盖率数据，通过源码映射换算为根包源文件的行覆盖率
+///
+/// `package`必须是与测试运行器相同的测试模式编译结果，否则字节码偏移与覆盖率数据对不上。
+pub fn package_coverage(package: &CompiledPackage, package_path: &Path, map_path: &Path) -> Result<CoverageReport> {
+    let coverage_map = CoverageMap::from_binary_file(map_path)
+        .map_err(|e| anyhow!("无法读取覆盖率数据 {}: {}", map_path.display(), e))?
+        .to_unified_exec_map();
+
+    let mut modules = Vec::new();
+    for unit in package.package.root_modules() {
+        let source_file = unit.source_path.strip_prefix(package_path).unwrap_or(&unit.source_path);
+        if source_file.starts_with("tests") {
+            continue;
+        }
+        let module = &unit.unit.module;
+        let source = std::fs::read_to_string(&unit.source_path)?;
+        let counts = coverage_map
+            .module_maps
+            .get(&(*module.address(), module.self_id().name().to_owned()));
+        modules.push(module_coverage(
+            module,
+            &unit.unit.source_map,
+            counts,
+            &LineIndex::new(&source),
+            source_file.display().to_string(),
+        ));
+    }
+    Ok(CoverageReport::new(modules))
+}
+
+/// 打印每个模块和函数的覆盖率
+pub fn render_summary(report: &CoverageReport) -> String {
+    let mut output = String::new();
+    for module in &report.modules {
+        let _ = writeln!(
+            output,
+            "{}::{} ({}): {:.2}% ({}/{} 行)",
+            module.address, module.name, module.source_file, module.percent, module.covered_lines, module.lines
+        );
+        for function in &module.functions {
+            let _ = writeln!(
+                output,
+                "    {:<40} {:>7.2}% ({}/{} 行, 调用 {} 次)",
+                function.name, function.percent, function.covered_lines, function.lines, function.calls
+            );
+        }
+    }
+    let _ = writeln!(output, "总覆盖率: {:.2}% ({}/{} 行)", report.percent, report.covered_lines, report.lines);
+    output
+}
+
+fn module_coverage(
+    module: &CompiledModule,
+    source_map: &SourceMap,
+    counts: Option<&ModuleCoverageMap>,
+    lines: &LineIndex,
+    source_file: String,
+) -> ModuleCoverage {
+    let mut module_hits: BTreeMap<usize, u64> = BTreeMap::new();
+    let mut functions = Vec::new();
+    for (index, definition) in module.function_defs().iter().enumerate() {
+        let Some(code) = &definition.code else {
+            continue;
+        };
+        let index = FunctionDefinitionIndex(index as u16);
+        let name = module.identifier_at(module.function_handle_at(definition.function).name);
+        let pc_counts = counts.and_then(|counts| counts.function_maps.get(name));
+        let count = |pc: usize| pc_counts.and_then(|pcs| pcs.get(&(pc as u64))).copied().unwrap_or(0);
+
+        // 同一行有多条指令时取执行次数最多的一条
+        let mut function_hits: BTreeMap<usize, u64> = BTreeMap::new();
+        for pc in 0..code.code.len() {
+            if let Ok(loc) = source_map.get_code_location(index, pc as CodeOffset) {
+                let hits = function_hits.entry(lines.line_of(loc.start())).or_default();
+                *hits = (*hits).max(count(pc));
+            }
+        }
+        for (line, hits) in &function_hits {
+            let entry = module_hits.entry(*line).or_default();
+            *entry = (*entry).max(*hits);
+        }
+
+        let covered_lines = function_hits.values().filter(|hits| **hits > 0).count();
+        functions.push(FunctionCoverage {
+            name: name.to_string(),
+            line: source_map
+                .get_function_source_map(index)
+                .map(|function_map| lines.line_of(function_map.definition_location.start()))
+                .ok()
+                .or_else(|| function_hits.keys().next().copied())
+                .unwrap_or(1),
+            calls: count(0),
+            lines: function_hits.len(),
+            covered_lines,
+            percent: percent(covered_lines, function_hits.len()),
+        });
+    }
+
+    let covered_lines = module_hits.values().filter(|hits| **hits > 0).count();
+    ModuleCoverage {
+        address: module.address().to_hex_literal(),
+        name: module.self_id().name().to_string(),
+        source_file,
+        lines: module_hits.len(),
+        covered_lines,
+        percent: percent(covered_lines, module_hits.len()),
+        functions,
+        line_hits: module_hits.into_iter().map(|(line, hits)| LineHits { line, hits }).collect(),
+    }
+}
+
+// 同一源文件中的多个模块各自输出一条记录，lcov工具会按文件合并
+fn to_lcov(modules: &[ModuleCoverage]) -> String {
+    let mut output = String::new();
+    for module in modules {
+        let _ = writeln!(output, "TN:");
+        let _ = writeln!(output, "SF:{}", module.source_file);
+        for function in &module.functions {
+            let _ = writeln!(output, "FN:{},{}::{}", function.line, module.name, function.name);
+        }
+        for function in &module.functions {
+            let _ = writeln!(output, "FNDA:{},{}::{}", function.calls, module.name, function.name);
+        }
+        let _ = writeln!(output, "FNF:{}", module.functions.len());
+        let _ = writeln!(output, "FNH:{}", module.functions.iter().filter(|f| f.calls > 0).count());
+        for line in &module.line_hits {
+            let _ = writeln!(output, "DA:{},{}", line.line, line.hits);
+        }
+        let _ = writeln!(output, "LF:{}", module.lines);
+        let _ = writeln!(output, "LH:{}", module.covered_lines);
+        let _ = writeln!(output, "end_of_record");
+    }
+    output
+}
+
+fn percent(covered: usize, total: usize) -> f64 {
+    if total == 0 {
+        return 100.0;
+    }
+    (covered as f64 * 10000.0 / total as f64).round() / 100.0
+}
+
+// 字节偏移到行号的换算
+struct LineIndex {
+    line_starts: Vec<u32>,
+}
+
+impl LineIndex {
+    fn new(source: &str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(offset, _)| offset as u32 + 1))
+            .collect();
+        Self { line_starts }
+    }
+
+    fn line_of(&self, offset: u32) -> usize {
+        self.line_starts.partition_point(|start| *start <= offset).max(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use move_compiler::Compiler;
+    use move_core_types::identifier::Identifier;
+
+    // 函数按名称排序与声明顺序一致；每个函数的代码只出现在所在的几行中
+    const SOURCE: &str = "module 0x42::math {
+    public fun double(x: u64): u64 { x * 2 }
+
+    public fun guarded_dec(x: u64): u64 { assert!(x > 0, 1);
+        x - 1 }
+
+    public fun unused(): u64 { 42 }
+}
+";
+
+    fn compile() -> (CompiledModule, SourceMap) {
+        let dir = std::env::temp_dir().join(format!("move-coverage-test-{:016x}", rand::random::<u64>()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("math.move");
+        std::fs::write(&path, SOURCE).unwrap();
+        let result = Compiler::from_files(vec![path.display().to_string()], vec![], BTreeMap::new()).build();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let (_, units) = result.unwrap();
+        let (mut units, _) = units.unwrap_or_else(|_| panic!("编译失败"));
+        let unit = units.remove(0).into_compiled_unit();
+        (unit.module, unit.source_map)
+    }
+
+    // 把`function`中位于`line`行的每条指令记为执行`hits`次
+    fn hit_line(
+        counts: &mut ModuleCoverageMap,
+        module: &CompiledModule,
+        source_map: &SourceMap,
+        function: &str,
+        line: usize,
+        hits: u64,
+    ) {
+        let lines = LineIndex::new(SOURCE);
+        let (index, definition) = module
+            .function_defs()
+            .iter()
+            .enumerate()
+            .find(|(_, def)| module.identifier_at(module.function_handle_at(def.function).name).as_str() == function)
+            .unwrap();
+        let code = definition.code.as_ref().unwrap();
+        let function_map = counts.function_maps.entry(Identifier::new(function).unwrap()).or_default();
+        for pc in 0..code.code.len() {
+            let loc = source_map
+                .get_code_location(FunctionDefinitionIndex(index as u16), pc as CodeOffset)
+                .unwrap();
+            if lines.line_of(loc.start()) == line {
+                function_map.insert(pc as u64, hits);
+            }
+        }
+    }
+
+    // double调用3次；guarded_dec调用2次，都在第4行的断言处abort；unused没有调用
+    fn report() -> CoverageReport {
+        let (module, source_map) = compile();
+        let mut counts = ModuleCoverageMap {
+            module_addr: *module.address(),
+            module_name: module.self_id().name().to_owned(),
+            function_maps: BTreeMap::new(),
+        };
+        hit_line(&mut counts, &module, &source_map, "double", 2, 3);
+        hit_line(&mut counts, &module, &source_map, "guarded_dec", 4, 2);
+        let module = module_coverage(
+            &module,
+            &source_map,
+            Some(&counts),
+            &LineIndex::new(SOURCE),
+            "sources/math.move".to_string(),
+        );
+        CoverageReport::new(vec![module])
+    }
+
+    #[test]
+    fn counts_hits_per_line() {
+        let report = report();
+        let module = &report.modules[0];
+        assert_eq!((module.address.as_str(), module.name.as_str()), ("0x42", "math"));
+        let hits: Vec<(usize, u64)> = module.line_hits.iter().map(|line| (line.line, line.hits)).collect();
+        assert_eq!(hits, [(2, 3), (4, 2), (5, 0), (7, 0)]);
+        assert_eq!((module.lines, module.covered_lines, module.percent), (4, 2, 50.0));
+        assert_eq!((report.lines, report.covered_lines, report.percent), (4, 2, 50.0));
+    }
+
+    #[test]
+    fn computes_function_percentages() {
+        let report = report();
+        let functions: Vec<(&str, usize, u64, usize, usize, f64)> = report.modules[0]
+            .functions
+            .iter()
+            .map(|f| (f.name.as_str(), f.line, f.calls, f.lines, f.covered_lines, f.percent))
+            .collect();
+        assert_eq!(functions, [
+            ("double", 2, 3, 1, 1, 100.0),
+            ("guarded_dec", 4, 2, 2, 1, 50.0),
+            ("unused", 7, 0, 1, 0, 0.0),
+        ]);
+    }
+
+    #[test]
+    fn writes_lcov() {
+        assert_eq!(
+            report().lcov,
+            "TN:
+SF:sources/math.move
+FN:2,math::double
+FN:4,math::guarded_dec
+FN:7,math::unused
+FNDA:3,math::double
+FNDA:2,math::guarded_dec
+FNDA:0,math::unused
+FNF:3
+FNH:2
+DA:2,3
+DA:4,2
+DA:5,0
+DA:7,0
+LF:4
+LH:2
+end_of_record
+"
+        );
+    }
+
+    #[test]
+    fn reports_no_hits_without_coverage_data() {
+        let (module, source_map) = compile();
+        let module = module_coverage(&module, &source_map, None, &LineIndex::new(SOURCE), "sources/math.move".to_string());
+        assert_eq!(module.covered_lines, 0);
+        assert!(module.functions.iter().all(|f| f.calls == 0 && f.percent == 0.0));
+    }
+
+    #[test]
+    fn maps_offsets_to_lines() {
+        let lines = LineIndex::new("a\nbc\n\nd");
+        let line_of = |offset| lines.line_of(offset);
+        assert_eq!([0, 1, 2, 4, 5, 6].map(line_of), [1, 1, 2, 2, 3, 4]);
+        assert_eq!(percent(0, 0), 100.0);
+        assert_eq!(percent(1, 3), 33.33);
+    }
+}
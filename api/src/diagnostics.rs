@@ -0,0 +1,228 @@
+// Copyright (c) Move Web Compiler
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use codespan_reporting::diagnostic::{Diagnostic as CodespanDiagnostic, Label as CodespanLabel, Severity as CodespanSeverity};
+use codespan_reporting::files::SimpleFiles;
+use codespan_reporting::term::{self, termcolor::Buffer};
+use move_compiler::diagnostics::Diagnostics;
+use move_compiler::shared::files::MappedFiles;
+use move_ir_types::location::Loc;
+use serde::{Deserialize, Serialize};
+
+/// 诊断的严重程度
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Bug,
+    Error,
+    Warning,
+    Note,
+    Help,
+}
+
+impl From<CodespanSeverity> for Severity {
+    fn from(severity: CodespanSeverity) -> Self {
+        match severity {
+            CodespanSeverity::Bug => Severity::Bug,
+            CodespanSeverity::Error => Severity::Error,
+            CodespanSeverity::Warning => Severity::Warning,
+            CodespanSeverity::Note => Severity::Note,
+            CodespanSeverity::Help => Severity::Help,
+        }
+    }
+}
+
+impl From<Severity> for CodespanSeverity {
+    fn from(severity: Severity) -> Self {
+        match severity {
+            Severity::Bug => CodespanSeverity::Bug,
+            Severity::Error => CodespanSeverity::Error,
+            Severity::Warning => CodespanSeverity::Warning,
+            Severity::Note => CodespanSeverity::Note,
+            Severity::Help => CodespanSeverity::Help,
+        }
+    }
+}
+
+/// 诊断在源文件中标注的一段区间
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Label {
+    /// 源文件路径，包内文件为相对包根目录的路径（如`sources/hello.move`）
+    pub file: String,
+    /// 起始行号（从1开始）
+    pub line: usize,
+    /// 起始列号（从1开始，按字符计数）
+    pub column: usize,
+    /// 结束行号（从1开始）
+    pub end_line: usize,
+    /// 结束列号（从1开始，按字符计数，不包含）
+    pub end_column: usize,
+    /// 起始字节偏移
+    pub start: usize,
+    /// 结束字节偏移（不包含）
+    pub end: usize,
+    /// 标注信息
+    pub message: String,
+}
+
+/// 一条结构化的编译器诊断
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Diagnostic {
+    /// 严重程度
+    pub severity: Severity,
+    /// 错误码，例如`E03002`或`W09001`
+    pub code: String,
+    /// 诊断信息
+    pub message: String,
+    /// 主标注
+    pub primary: Label,
+    /// 次要标注
+    pub secondary: Vec<Label>,
+    /// 附加说明
+    pub notes: Vec<String>,
+}
+
+impl Diagnostic {
+    /// 是否为会导致编译失败的诊断
+    pub fn is_error(&self) -> bool {
+        matches!(self.severity, Severity::Bug | Severity::Error)
+    }
+
+    /// 单行摘要，格式为`file:line:column: severity[code]: message`
+    pub fn summary(&self) -> String {
+        let severity = match self.severity {
+            Severity::Bug => "bug",
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Note => "note",
+            Severity::Help => "help",
+        };
+        format!(
+            "{}:{}:{}: {}[{}]: {}",
+            self.primary.file, self.primary.line, self.primary.column, severity, self.code, self.message
+        )
+    }
+}
+
+/// 将move-compiler的诊断转换为结构化诊断
+pub fn from_compiler_diagnostics(files: &MappedFiles, diags: Diagnostics, package_root: &Path) -> Vec<Diagnostic> {
+    // 编译器看到的可能是规范化后的路径，两种形式都用于计算相对路径
+    let package_roots = [
+        package_root.to_path_buf(),
+        package_root.canonicalize().unwrap_or_else(|_| package_root.to_path_buf()),
+    ];
+    let mut sources = SourceCache::default();
+    let mut result = Vec::new();
+
+    for diag in diags.into_vec() {
+        let (code, _) = diag.info().clone().render();
+        // 每条诊断单独转换，保证错误码与标注一一对应
+        let Some((severity, message, (primary_loc, primary_msg), secondary, notes)) =
+            Diagnostics::from(vec![diag]).into_codespan_format().pop()
+        else {
+            continue;
+        };
+
+        result.push(Diagnostic {
+            severity: severity.into(),
+            code,
+            message: message.to_string(),
+            primary: sources.label(files, &package_roots, primary_loc, primary_msg),
+            secondary: secondary
+                .into_iter()
+                .map(|(loc, msg)| sources.label(files, &package_roots, loc, msg))
+                .collect(),
+            notes,
+        });
+    }
+
+    result
+}
+
+/// 以codespan风格渲染诊断，用于命令行输出
+pub fn render(diagnostics: &[Diagnostic], package_root: &Path) -> String {
+    let mut files = SimpleFiles::new();
+    let mut file_ids = HashMap::new();
+    let mut buffer = Buffer::no_color();
+    let config = term::Config::default();
+
+    for diag in diagnostics {
+        let mut labels = Vec::with_capacity(diag.secondary.len() + 1);
+        for (label, is_primary) in std::iter::once((&diag.primary, true)).chain(diag.secondary.iter().map(|l| (l, false))) {
+            let file_id = *file_ids.entry(label.file.clone()).or_insert_with(|| {
+                let source = std::fs::read_to_string(package_root.join(&label.file)).unwrap_or_default();
+                files.add(label.file.clone(), source)
+            });
+            let codespan_label = if is_primary {
+                CodespanLabel::primary(file_id, label.start..label.end)
+            } else {
+                CodespanLabel::secondary(file_id, label.start..label.end)
+            };
+            labels.push(codespan_label.with_message(label.message.clone()));
+        }
+
+        let codespan_diag = CodespanDiagnostic::new(diag.severity.into())
+            .with_code(diag.code.clone())
+            .with_message(diag.message.clone())
+            .with_labels(labels)
+            .with_notes(diag.notes.clone());
+        if term::emit(&mut buffer, &config, &files, &codespan_diag).is_err() {
+            // 源文件不可读时退回单行摘要
+            buffer.clear();
+            return diagnostics.iter().map(Diagnostic::summary).collect::<Vec<_>>().join("\n");
+        }
+    }
+
+    String::from_utf8_lossy(buffer.as_slice()).to_string()
+}
+
+/// 将字节偏移转换为从1开始的行号和列号
+pub fn offset_to_position(source: &str, offset: usize) -> (usize, usize) {
+    let offset = offset.min(source.len());
+    let prefix = source.get(..offset).unwrap_or(source);
+    let line = prefix.matches('\n').count() + 1;
+    let line_start = prefix.rfind('\n').map_or(0, |i| i + 1);
+    let column = prefix[line_start..].chars().count() + 1;
+    (line, column)
+}
+
+// 按文件名缓存源文件内容，避免每个标注都重新读取文件
+#[derive(Default)]
+struct SourceCache {
+    sources: HashMap<String, String>,
+}
+
+impl SourceCache {
+    fn label(&mut self, files: &MappedFiles, package_roots: &[PathBuf], loc: Loc, message: String) -> Label {
+        let file_name = files.filename(&loc.file_hash()).to_string();
+        let source = self
+            .sources
+            .entry(file_name.clone())
+            .or_insert_with(|| std::fs::read_to_string(&file_name).unwrap_or_default());
+
+        let start = loc.start() as usize;
+        let end = loc.end() as usize;
+        let (line, column) = offset_to_position(source, start);
+        let (end_line, end_column) = offset_to_position(source, end);
+
+        // 包内文件使用相对路径，便于前端与提交的文件对应
+        let file = package_roots
+            .iter()
+            .find_map(|root| Path::new(&file_name).strip_prefix(root).ok())
+            .map(|p| p.display().to_string())
+            .unwrap_or(file_name);
+
+        Label {
+            file,
+            line,
+            column,
+            end_line,
+            end_column,
+            start,
+            end,
+            message,
+        }
+    }
+}
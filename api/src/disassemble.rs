@@ -0,0 +1,464 @@
+// Copyright (c) Move Web Compiler
+// SPDX-License-Identifier: Apache-2.0
+
+use std::path::{Path, PathBuf};
+use anyhow::{Result, anyhow};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use move_binary_format::access::ModuleAccess;
+use move_binary_format::binary_views::BinaryIndexedView;
+use move_binary_format::file_format::{CompiledModule, FunctionDefinitionIndex};
+use move_bytecode_source_map::mapping::SourceMapping;
+use move_bytecode_source_map::source_map::SourceMap;
+use move_bytecode_source_map::utils::source_map_from_file;
+use move_command_line_common::files::FileHash;
+use move_disassembler::disassembler::{Disassembler, DisassemblerOptions};
+use move_ir_types::location::Spanned;
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+use crate::compiler::{self, BuildFailure};
+use crate::diagnostics;
+
+/// 反汇编选项
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DisassembleOptions {
+    /// 在每段指令前插入对应的源码行，需要源码映射
+    pub interleave_source: bool,
+    /// 不输出函数体
+    pub skip_code: bool,
+    /// 不输出局部变量
+    pub skip_locals: bool,
+    /// 不输出基本块标签
+    pub skip_basic_blocks: bool,
+}
+
+/// 单个模块的反汇编结果
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DisassembledModule {
+    /// 模块地址（十六进制）
+    pub address: String,
+    /// 模块名称
+    pub name: String,
+    /// 可读的字节码
+    pub disassembly: String,
+}
+
+/// 模块的源码映射和对应的源文件
+pub struct ModuleSource {
+    /// 编译器生成的源码映射
+    pub source_map: SourceMap,
+    /// 源文件路径，用于显示
+    pub path: String,
+    /// 源文件内容
+    pub content: String,
+}
+
+impl ModuleSource {
+    /// 从`.mvsm`源码映射文件和源文件读取
+    pub fn from_files(source_map_path: &Path, source_path: &Path) -> Result<Self> {
+        Ok(Self {
+            source_map: source_map_from_file(source_map_path)?,
+            path: source_path.display().to_string(),
+            content: std::fs::read_to_string(source_path)?,
+        })
+    }
+}
+
+/// 反汇编一个序列化的模块
+pub fn disassemble_bytes(bytes: &[u8], source: Option<ModuleSource>, options: &DisassembleOptions) -> Result<DisassembledModule> {
+    let module = CompiledModule::deserialize(bytes).map_err(|e| anyhow!("无法解析模块字节码: {}", e))?;
+    disassemble_module(&module, source, options)
+}
+
+/// 反汇编Base64编码的模块
+pub fn disassemble_base64(encoded: &str, options: &DisassembleOptions) -> Result<DisassembledModule> {
+    let bytes = BASE64_STANDARD
+        .decode(encoded.trim())
+        .map_err(|e| anyhow!("无效的Base64字节码: {}", e))?;
+    disassemble_bytes(&bytes, None, options)
+}
+
+/// 反汇编`.mv`文件，可以同时指定源码映射和源文件
+pub fn disassemble_file(
+    path: &Path,
+    source_map: Option<&Path>,
+    source: Option<&Path>,
+    options: &DisassembleOptions,
+) -> Result<DisassembledModule> {
+    let bytes = std::fs::read(path)?;
+    let source = match (source_map, source) {
+        (Some(source_map), Some(source)) => Some(ModuleSource::from_files(source_map, source)?),
+        (None, None) => None,
+        _ => return Err(anyhow!("源码映射和源文件需要同时指定")),
+    };
+    disassemble_bytes(&bytes, source, options)
+}
+
+/// 编译包目录并反汇编根包中的每个模块，带有编译器生成的源码映射
+pub fn disassemble_package(package_path: &Path, options: &DisassembleOptions) -> Result<Vec<DisassembledModule>, BuildFailure> {
+    let output = compiler::build_package(sui_move_build::BuildConfig::new_for_testing(), package_path)?;
+
+    let mut modules = Vec::new();
+    for unit in output.package.package.root_modules() {
+        let source = std::fs::read_to_string(&unit.source_path).ok().map(|content| ModuleSource {
+            source_map: unit.unit.source_map.clone(),
+            path: unit
+                .source_path
+                .strip_prefix(package_path)
+                .unwrap_or(&unit.source_path)
+                .display()
+                .to_string(),
+            content,
+        });
+        modules.push(disassemble_module(&unit.unit.module, source, options)?);
+    }
+    Ok(modules)
+}
+
+/// 反汇编目录中的所有`.mv`文件，例如编译接口保存的`bytecode/`目录
+///
+/// 如果`.mv`文件位于编译输出的`bytecode_modules/`目录中，且`source_maps/`目录中有对应的`.mvsm`文件，
+/// 按源码映射记录的文件哈希在`sources/`目录中找到源文件后一并使用。
+pub fn disassemble_bytecode_dir(dir: &Path, options: &DisassembleOptions) -> Result<Vec<DisassembledModule>> {
+    let mut paths: Vec<PathBuf> = WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.into_path())
+        .filter(|path| path.extension().and_then(|e| e.to_str()) == Some("mv"))
+        .collect();
+    paths.sort();
+    if paths.is_empty() {
+        return Err(anyhow!("目录 {:?} 中没有找到.mv文件", dir));
+    }
+
+    paths
+        .iter()
+        .map(|path| {
+            disassemble_bytes(&std::fs::read(path)?, sibling_source(path), options)
+        })
+        .collect()
+}
+
+/// 根据输入自动选择：包含Move.toml的目录按包编译，其他目录查找`.mv`文件，
+/// `.mv`文件直接读取，否则当作Base64编码的字节码
+pub fn disassemble_input(input: &str, options: &DisassembleOptions) -> Result<Vec<DisassembledModule>> {
+    let path = Path::new(input);
+    if path.is_dir() {
+        if path.join("Move.toml").is_file() {
+            return disassemble_package(path, options).map_err(|e| {
+                let report = diagnostics::render(&e.diagnostics, path);
+                if report.is_empty() {
+                    anyhow!(e)
+                } else {
+                    anyhow!("{}\n{}", e, report)
+                }
+            });
+        }
+        return disassemble_bytecode_dir(path, options);
+    }
+    if path.is_file() {
+        return Ok(vec![disassemble_file(path, None, None, options)?]);
+    }
+    Ok(vec![disassemble_base64(input, options)?])
+}
+
+// 反汇编已解析的模块，有源码时按需插入源码行
+fn disassemble_module(module: &CompiledModule, source: Option<ModuleSource>, options: &DisassembleOptions) -> Result<DisassembledModule> {
+    let view = BinaryIndexedView::Module(module);
+    let (source_mapping, source) = match source {
+        Some(source) => {
+            let mut source_mapping = SourceMapping::new(source.source_map.clone(), view);
+            source_mapping.with_source_code((source.path.clone(), source.content.clone()));
+            (source_mapping, Some(source))
+        }
+        None => (SourceMapping::new_from_view(view, Spanned::unsafe_no_loc(()).loc)?, None),
+    };
+
+    let mut disassembler_options = DisassemblerOptions::new();
+    disassembler_options.print_code = !options.skip_code;
+    disassembler_options.print_locals = !options.skip_locals;
+    disassembler_options.print_basic_blocks = !options.skip_basic_blocks;
+
+    let mut disassembly = Disassembler::new(source_mapping, disassembler_options).disassemble()?;
+    if options.interleave_source && !options.skip_code {
+        if let Some(source) = &source {
+            disassembly = interleave_source(module, source, &disassembly);
+        }
+    }
+
+    Ok(DisassembledModule {
+        address: module.address().to_hex_literal(),
+        name: module.self_id().name().to_string(),
+        disassembly,
+    })
+}
+
+// 在指令前插入对应的源码行
+//
+// 反汇编输出按定义顺序打印有函数体的函数，函数头位于行首，指令行的格式为`\t<偏移>: <指令>`。
+// 按模块中的函数定义依次匹配函数头，只查找下一个函数的名称，不依赖可见性等修饰符的写法；
+// 每条指令通过该函数的源码映射找到源码位置，源码行变化时插入一行`// <文件>:<行>: <源码>`。
+fn interleave_source(module: &CompiledModule, source: &ModuleSource, disassembly: &str) -> String {
+    // 原生函数没有函数体，不会出现在带`{`的函数头中
+    let mut functions = module
+        .function_defs()
+        .iter()
+        .enumerate()
+        .filter(|(_, def)| def.code.is_some())
+        .map(|(index, def)| {
+            let name = module.identifier_at(module.function_handle_at(def.function).name);
+            (FunctionDefinitionIndex(index as u16), name.as_str())
+        })
+        .peekable();
+    let source_lines: Vec<&str> = source.content.lines().collect();
+
+    let mut output = String::new();
+    let mut current_function = None;
+    let mut last_line = None;
+    for line in disassembly.lines() {
+        if let Some((index, _)) = functions.next_if(|(_, name)| is_function_header(line, name)) {
+            current_function = Some(index);
+            last_line = None;
+        } else if line.trim_end() == "}" {
+            current_function = None;
+        } else if let (Some(index), Some(offset)) = (current_function, instruction_offset(line)) {
+            let location = source
+                .source_map
+                .get_function_source_map(index)
+                .ok()
+                .and_then(|function_map| function_map.get_code_location(offset));
+            if let Some(location) = location {
+                let (line_no, _) = diagnostics::offset_to_position(&source.content, location.start() as usize);
+                if last_line != Some(line_no) {
+                    let text = source_lines.get(line_no.saturating_sub(1)).map_or("", |l| l.trim());
+                    output.push_str(&format!("\t// {}:{}: {}\n", source.path, line_no, text));
+                    last_line = Some(line_no);
+                }
+            }
+        }
+        output.push_str(line);
+        output.push('\n');
+    }
+    output
+}
+
+// 函数头形如`public entry mint<T: drop>(Arg0: &mut TxContext) {`：位于行首，以`{`结尾，
+// 参数列表之前有一个词以函数名加`(`或`<`开头
+fn is_function_header(line: &str, name: &str) -> bool {
+    if line.starts_with(char::is_whitespace)
+        || !line.trim_end().ends_with('{')
+        || line.starts_with("struct ")
+        || line.starts_with("enum ")
+    {
+        return false;
+    }
+    // 参数和类型参数约束中的`:`之后可能出现与函数同名的类型
+    let head = line.split(':').next().unwrap_or(line);
+    head.split_whitespace().any(|word| {
+        word.strip_prefix(name)
+            .is_some_and(|after| after.starts_with('(') || after.starts_with('<'))
+    })
+}
+
+// 解析`\t12: MoveLoc[0](...)`中的偏移
+fn instruction_offset(line: &str) -> Option<u16> {
+    let (offset, _) = line.trim_start().split_once(':')?;
+    offset.parse().ok()
+}
+
+// 编译输出中`.mv`文件对应的源码映射和源文件：
+// build/<包>/bytecode_modules/<路径>.mv -> build/<包>/source_maps/<路径>.mvsm，依赖包的模块位于`dependencies/`下。
+// 源文件名不一定与模块名相同，按源码映射中记录的文件哈希在build/<包>/sources/中查找。
+fn sibling_source(path: &Path) -> Option<ModuleSource> {
+    let modules_dir = path.ancestors().skip(1).find(|dir| dir.ends_with("bytecode_modules"))?;
+    let build_dir = modules_dir.parent()?;
+    let source_map_path = build_dir
+        .join("source_maps")
+        .join(path.strip_prefix(modules_dir).ok()?)
+        .with_extension("mvsm");
+    let source_map = source_map_from_file(&source_map_path).ok()?;
+    let file_hash = source_map.definition_location.file_hash();
+
+    WalkDir::new(build_dir.join("sources"))
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().and_then(|e| e.to_str()) == Some("move"))
+        .find_map(|entry| {
+            let content = std::fs::read_to_string(entry.path()).ok()?;
+            (FileHash::new(&content) == file_hash).then(|| ModuleSource {
+                source_map: source_map.clone(),
+                path: entry.path().display().to_string(),
+                content,
+            })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use move_compiler::Compiler;
+
+    const SOURCE: &str = "module 0x42::shapes {
+    struct Box<T> has drop { value: T }
+
+    public fun area(w: u64, h: u64): u64 {
+        w * h
+    }
+
+    public(friend) fun double(x: u64): u64 {
+        x + x
+    }
+
+    public entry fun run() {
+        let a = area(2, 3);
+        assert!(double(a) == 12, 0);
+    }
+
+    fun wrap<T: drop>(value: T): Box<T> {
+        Box { value }
+    }
+
+    public fun area_of_square(x: u64): u64 { area(x, x) }
+}
+";
+
+    // 编译SOURCE，返回模块和带源码映射的源文件
+    fn compile() -> (CompiledModule, ModuleSource) {
+        let dir = std::env::temp_dir().join(format!("move-disassemble-test-{:016x}", rand::random::<u64>()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("shapes.move");
+        std::fs::write(&path, SOURCE).unwrap();
+        let result = Compiler::from_files(vec![path.display().to_string()], vec![], BTreeMap::new()).build();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let (_, units) = result.unwrap();
+        let (mut units, _) = units.unwrap_or_else(|_| panic!("编译失败"));
+        let unit = units.remove(0).into_compiled_unit();
+        let source = ModuleSource {
+            source_map: unit.source_map,
+            path: "sources/shapes.move".to_string(),
+            content: SOURCE.to_string(),
+        };
+        (unit.module, source)
+    }
+
+    // 函数头之后、函数结束之前插入的源码行号
+    fn interleaved_lines(output: &str, name: &str) -> Vec<usize> {
+        output
+            .lines()
+            .skip_while(|line| !is_function_header(line, name))
+            .skip(1)
+            .take_while(|line| line.trim_end() != "}")
+            .filter_map(|line| line.trim_start().strip_prefix("// sources/shapes.move:"))
+            .map(|rest| rest.split_once(':').unwrap().0.parse().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn interleaves_source_lines_per_function() {
+        let (module, source) = compile();
+        let options = DisassembleOptions {
+            interleave_source: true,
+            ..DisassembleOptions::default()
+        };
+        let output = disassemble_module(&module, Some(source), &options).unwrap().disassembly;
+
+        assert!(output.contains("\t// sources/shapes.move:5: w * h\n"), "{}", output);
+        assert!(output.contains("\t// sources/shapes.move:9: x + x\n"), "{}", output);
+        assert!(output.contains("\t// sources/shapes.move:13: let a = area(2, 3);\n"), "{}", output);
+        assert!(output.contains("\t// sources/shapes.move:14: assert!(double(a) == 12, 0);\n"), "{}", output);
+        assert!(output.contains("\t// sources/shapes.move:18: Box { value }\n"), "{}", output);
+
+        // 每个函数只插入自己范围内的源码行
+        for (name, lines) in [
+            ("area", 4..=6),
+            ("double", 8..=10),
+            ("run", 12..=15),
+            ("wrap", 17..=19),
+            ("area_of_square", 21..=21),
+        ] {
+            let interleaved = interleaved_lines(&output, name);
+            assert!(!interleaved.is_empty(), "{}: {}", name, output);
+            assert!(interleaved.iter().all(|line| lines.contains(line)), "{}: {:?}", name, interleaved);
+        }
+    }
+
+    #[test]
+    fn skips_interleaving_without_code() {
+        let (module, source) = compile();
+        let options = DisassembleOptions {
+            interleave_source: true,
+            skip_code: true,
+            ..DisassembleOptions::default()
+        };
+        let output = disassemble_module(&module, Some(source), &options).unwrap().disassembly;
+        assert!(!output.contains("// sources/shapes.move:"));
+    }
+
+    #[test]
+    fn finds_sources_by_file_hash() {
+        let (module, source) = compile();
+        let build = std::env::temp_dir().join(format!("move-disassemble-test-{:016x}", rand::random::<u64>()));
+        let package = build.join("shapes_pkg");
+        for dir in ["bytecode_modules", "source_maps", "sources"] {
+            std::fs::create_dir_all(package.join(dir)).unwrap();
+        }
+        let mut bytes = Vec::new();
+        module.serialize_with_version(module.version, &mut bytes).unwrap();
+        std::fs::write(package.join("bytecode_modules/shapes.mv"), bytes).unwrap();
+        std::fs::write(package.join("source_maps/shapes.mvsm"), bcs::to_bytes(&source.source_map).unwrap()).unwrap();
+        // 源文件名与模块名不同，同目录下还有其他源文件
+        std::fs::write(package.join("sources/other.move"), "module 0x42::other {}\n").unwrap();
+        std::fs::write(package.join("sources/geometry.move"), SOURCE).unwrap();
+
+        let options = DisassembleOptions {
+            interleave_source: true,
+            ..DisassembleOptions::default()
+        };
+        let result = disassemble_bytecode_dir(&package.join("bytecode_modules"), &options);
+        let _ = std::fs::remove_dir_all(&build);
+
+        let modules = result.unwrap();
+        assert_eq!(modules.len(), 1);
+        assert!(modules[0].disassembly.contains("geometry.move:5: w * h\n"), "{}", modules[0].disassembly);
+    }
+
+    #[test]
+    fn matches_function_headers() {
+        for line in [
+            "mint(Arg0: u64) {",
+            "public mint(Arg0: u64): u64 {",
+            "entry mint() {",
+            "public entry mint(Arg0: &mut TxContext) {",
+            "entry public mint() {",
+            "public(friend) mint(Arg0: u64) {",
+            "public(package) mint(Arg0: u64) {",
+            "public mint<T: drop + store>(Arg0: T) {",
+            "mint<T>(Arg0: vector<T>): vector<T> {",
+        ] {
+            assert!(is_function_header(line, "mint"), "{}", line);
+        }
+        for line in [
+            "mint_all(Arg0: u64) {",
+            "public remint(Arg0: u64) {",
+            "burn(Arg0: mint<u8>) {",
+            "native public mint(Arg0: u64): u64;",
+            "\tmint(Arg0: u64) {",
+            "struct mint<T> has drop {",
+        ] {
+            assert!(!is_function_header(line, "mint"), "{}", line);
+        }
+    }
+
+    #[test]
+    fn parses_instruction_offsets() {
+        assert_eq!(instruction_offset("\t0: Ret"), Some(0));
+        assert_eq!(instruction_offset("\t12: MoveLoc[0](Arg0: u64)"), Some(12));
+        assert_eq!(instruction_offset("B0:"), None);
+        assert_eq!(instruction_offset("L0:\tloc1: u64"), None);
+        assert_eq!(instruction_offset("public mint(Arg0: u64) {"), None);
+    }
+}
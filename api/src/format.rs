@@ -0,0 +1,1155 @@
+// Copyright (c) Move Web Compiler
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use anyhow::{Result, anyhow, bail};
+use move_compiler::editions::Edition;
+use move_compiler::shared::{NumericalAddress, PackageConfig};
+use move_compiler::{Compiler, PASS_PARSER};
+use serde::{Deserialize, Serialize};
+
+use crate::compiler::BuildFailure;
+use crate::diagnostics::{self, Diagnostic};
+use crate::package;
+use crate::workspace::WorkspaceConfig;
+
+/// 默认每级缩进的空格数
+pub const DEFAULT_INDENT_WIDTH: usize = 4;
+
+/// 默认行宽
+pub const DEFAULT_LINE_LENGTH: usize = 100;
+
+/// 允许的最大缩进宽度
+pub const MAX_INDENT_WIDTH: usize = 16;
+
+/// 允许的最小行宽
+pub const MIN_LINE_LENGTH: usize = 40;
+
+/// 格式化选项
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FormatOptions {
+    /// 每级缩进的空格数
+    pub indent_width: usize,
+    /// 行宽，放不下的参数、字段等逗号分隔的列表逐项换行
+    pub line_length: usize,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            indent_width: DEFAULT_INDENT_WIDTH,
+            line_length: DEFAULT_LINE_LENGTH,
+        }
+    }
+}
+
+impl FormatOptions {
+    /// 检查选项是否在允许的范围内
+    pub fn validate(&self) -> Result<()> {
+        if self.indent_width == 0 || self.indent_width > MAX_INDENT_WIDTH {
+            bail!("缩进宽度必须在1到{}之间", MAX_INDENT_WIDTH);
+        }
+        if self.line_length < MIN_LINE_LENGTH {
+            bail!("行宽不能小于{}", MIN_LINE_LENGTH);
+        }
+        Ok(())
+    }
+}
+
+/// 格式化单个Move源文件（2024版本）
+///
+/// 先用编译器的解析阶段检查语法，有语法错误时返回诊断而不修改源码。
+/// 格式化只调整空白、缩进和逗号列表末尾的逗号，注释原样保留。
+pub fn format_source(source: &str, file_name: &str, options: &FormatOptions) -> Result<String, BuildFailure> {
+    options.validate()?;
+    let diagnostics = check_syntax(source, file_name)?;
+    if diagnostics.iter().any(Diagnostic::is_error) {
+        return Err(BuildFailure {
+            message: "源码有语法错误".to_string(),
+            diagnostics,
+        });
+    }
+    Ok(format_tokens(source, options)?)
+}
+
+/// 以2024版本解析源文件，返回解析阶段的诊断
+///
+/// 源文件写入临时工作区的`sources/`目录，诊断中的文件路径与编译接口一致。
+pub fn check_syntax(source: &str, file_name: &str) -> Result<Vec<Diagnostic>> {
+    let workspace = WorkspaceConfig::from_env();
+    let package_path = workspace.create()?;
+    let result = parse_package_file(&package_path, source, file_name);
+    let _ = workspace.remove(&package_path);
+    result
+}
+
+fn parse_package_file(package_path: &Path, source: &str, file_name: &str) -> Result<Vec<Diagnostic>> {
+    let mut files = BTreeMap::new();
+    files.insert(package::source_file_path(file_name), source.to_string());
+    let targets = package::write_package_files(package_path, &files)?
+        .into_iter()
+        .map(|path| package_path.join(path).display().to_string())
+        .collect::<Vec<_>>();
+
+    // 只运行解析阶段，不需要依赖和地址
+    let named_address_map: BTreeMap<String, NumericalAddress> = BTreeMap::new();
+    let (files, result) = Compiler::from_files(targets, vec![], named_address_map)
+        .set_default_config(PackageConfig {
+            edition: Edition::E2024_BETA,
+            ..PackageConfig::default()
+        })
+        .run::<PASS_PARSER>()?;
+    Ok(match result {
+        Ok(_) => vec![],
+        Err((_, diags)) => diagnostics::from_compiler_diagnostics(&files, diags, package_path),
+    })
+}
+
+/// 格式化已经通过语法检查的源码
+///
+/// 结果重新分词后与原记号序列比较（忽略列表末尾的逗号），不一致时返回错误而不是错误的代码。
+pub fn format_tokens(source: &str, options: &FormatOptions) -> Result<String> {
+    let tokens = tokenize(source)?;
+    if tokens.is_empty() {
+        return Ok(String::new());
+    }
+    let tree = build_tree(&tokens)?;
+    let builder = DocBuilder {
+        tokens: &tokens,
+        roles: token_roles(&tokens),
+    };
+    let doc = Doc::Concat(builder.lines(&builder.split_statements(&tree)));
+    let formatted = print(&doc, options);
+
+    if normalized(&tokens) != normalized(&tokenize(&formatted)?) {
+        bail!("格式化结果改变了源码的记号序列，已放弃格式化");
+    }
+    Ok(formatted)
+}
+
+// ---------------------------------------------------------------------------
+// 分词
+
+// 多字符的运算符，按长度从长到短匹配
+const PUNCTUATION: &[&str] = &[
+    "<==>", "==>", "<<=", ">>=", "::", "==", "!=", "<=", ">=", "&&", "||", "<<", ">>", "..", "=>", "+=", "-=", "*=",
+    "/=", "%=", "&=", "|=", "^=",
+];
+
+// 影响空格的关键字，`true`和`false`按值处理
+const KEYWORDS: &[&str] = &[
+    "abort", "acquires", "as", "break", "const", "continue", "copy", "else", "entry", "enum", "friend", "fun", "has",
+    "if", "let", "loop", "macro", "match", "module", "move", "mut", "native", "phantom", "public", "return", "spec",
+    "struct", "use", "while",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TokenKind {
+    Ident,
+    Number,
+    ByteString,
+    Label,
+    Punct,
+    LineComment,
+    BlockComment,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Token<'a> {
+    kind: TokenKind,
+    text: &'a str,
+    // 与前一个记号之间的换行数
+    newlines_before: usize,
+    // 与前一个记号之间是否有空白
+    space_before: bool,
+}
+
+impl<'a> Token<'a> {
+    fn is_comment(&self) -> bool {
+        matches!(self.kind, TokenKind::LineComment | TokenKind::BlockComment)
+    }
+
+    fn is_punct(&self, text: &str) -> bool {
+        self.kind == TokenKind::Punct && self.text == text
+    }
+
+    fn is_keyword(&self) -> bool {
+        self.kind == TokenKind::Ident && KEYWORDS.contains(&self.text)
+    }
+
+    fn content(&self) -> &'a str {
+        if self.is_comment() {
+            self.text.trim_end()
+        } else {
+            self.text
+        }
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token<'_>>> {
+    let mut tokens = Vec::new();
+    let mut position = 0;
+    let mut newlines_before = 0;
+    let mut space_before = false;
+    while let Some(c) = source[position..].chars().next() {
+        let rest = &source[position..];
+        if c.is_whitespace() {
+            newlines_before += (c == '\n') as usize;
+            space_before = true;
+            position += c.len_utf8();
+            continue;
+        }
+
+        let (kind, len) = if rest.starts_with("//") {
+            (TokenKind::LineComment, rest.find('\n').unwrap_or(rest.len()))
+        } else if let Some(comment) = rest.strip_prefix("/*") {
+            let end = comment.find("*/").ok_or_else(|| anyhow!("块注释没有结束"))?;
+            (TokenKind::BlockComment, end + 4)
+        } else if rest.starts_with("b\"") || rest.starts_with("x\"") {
+            (TokenKind::ByteString, byte_string_len(rest)?)
+        } else if c.is_ascii_alphabetic() || c == '_' || c == '$' {
+            (TokenKind::Ident, 1 + word_len(&rest[1..]))
+        } else if c.is_ascii_digit() {
+            (TokenKind::Number, 1 + word_len(&rest[1..]))
+        } else if c == '\'' && rest[1..].starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+            (TokenKind::Label, 1 + word_len(&rest[1..]))
+        } else {
+            let len = PUNCTUATION.iter().find(|p| rest.starts_with(**p)).map_or(c.len_utf8(), |p| p.len());
+            (TokenKind::Punct, len)
+        };
+
+        tokens.push(Token {
+            kind,
+            text: &rest[..len],
+            newlines_before,
+            space_before,
+        });
+        position += len;
+        newlines_before = 0;
+        space_before = false;
+    }
+    Ok(tokens)
+}
+
+fn word_len(text: &str) -> usize {
+    text.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(text.len())
+}
+
+// `b"..."`或`x"..."`的长度，包括前缀和引号
+fn byte_string_len(text: &str) -> Result<usize> {
+    let mut escaped = false;
+    for (offset, c) in text.char_indices().skip(2) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return Ok(offset + 1),
+            _ => {}
+        }
+    }
+    bail!("字节字符串没有结束")
+}
+
+// 用于校验的记号序列：代码记号去掉右括号前的逗号，注释单独比较，可以移到逗号之后
+fn normalized<'a>(tokens: &[Token<'a>]) -> (Vec<&'a str>, Vec<&'a str>) {
+    let (comments, code): (Vec<&Token<'a>>, Vec<&Token<'a>>) = tokens.iter().partition(|t| t.is_comment());
+    let code = code
+        .iter()
+        .enumerate()
+        .filter(|(index, token)| {
+            !(token.is_punct(",")
+                && code
+                    .get(index + 1)
+                    .is_some_and(|next| next.is_punct(")") || next.is_punct("]") || next.is_punct("}")))
+        })
+        .map(|(_, token)| token.text)
+        .collect();
+    (code, comments.iter().map(|t| t.content()).collect())
+}
+
+// ---------------------------------------------------------------------------
+// 括号树和记号的作用
+
+// 按括号嵌套组织的记号
+enum Node {
+    Token(usize),
+    Group {
+        open: usize,
+        close: usize,
+        children: Vec<Node>,
+    },
+}
+
+impl Node {
+    fn first(&self) -> usize {
+        match self {
+            Node::Token(index) => *index,
+            Node::Group { open, .. } => *open,
+        }
+    }
+
+    fn last(&self) -> usize {
+        match self {
+            Node::Token(index) => *index,
+            Node::Group { close, .. } => *close,
+        }
+    }
+}
+
+fn build_tree(tokens: &[Token]) -> Result<Vec<Node>> {
+    let mut stack: Vec<(usize, Vec<Node>)> = Vec::new();
+    let mut current = Vec::new();
+    for (index, token) in tokens.iter().enumerate() {
+        if token.kind != TokenKind::Punct {
+            current.push(Node::Token(index));
+            continue;
+        }
+        match token.text {
+            "(" | "[" | "{" => stack.push((index, std::mem::take(&mut current))),
+            ")" | "]" | "}" => {
+                let (open, parent) = stack.pop().ok_or_else(|| anyhow!("多余的 `{}`", token.text))?;
+                let expected = match tokens[open].text {
+                    "(" => ")",
+                    "[" => "]",
+                    _ => "}",
+                };
+                if token.text != expected {
+                    bail!("`{}` 与 `{}` 不匹配", tokens[open].text, token.text);
+                }
+                let children = std::mem::replace(&mut current, parent);
+                current.push(Node::Group {
+                    open,
+                    close: index,
+                    children,
+                });
+            }
+            _ => current.push(Node::Token(index)),
+        }
+    }
+    if let Some((open, _)) = stack.pop() {
+        bail!("`{}` 没有闭合", tokens[open].text);
+    }
+    Ok(current)
+}
+
+// 记号在表达式中的作用，决定两侧是否留空格
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Role {
+    Default,
+    // 前缀运算符：`&x`、`*x`、`!x`、`@0x1`
+    Prefix,
+    GenericOpen,
+    GenericClose,
+    LambdaOpen,
+    LambdaClose,
+    // 宏调用的`!`，例如`assert!`
+    MacroBang,
+}
+
+fn token_roles(tokens: &[Token]) -> Vec<Role> {
+    let mut roles = vec![Role::Default; tokens.len()];
+    let significant: Vec<usize> = (0..tokens.len()).filter(|i| !tokens[*i].is_comment()).collect();
+    for (position, &index) in significant.iter().enumerate() {
+        let token = &tokens[index];
+        if roles[index] != Role::Default || token.kind != TokenKind::Punct {
+            continue;
+        }
+        let prev = position.checked_sub(1).map(|p| significant[p]);
+        let next = significant.get(position + 1).map(|n| &tokens[*n]);
+        let after_value = prev.is_some_and(|p| ends_value(&tokens[p], roles[p]));
+        let after_name = prev.is_some_and(|p| tokens[p].kind == TokenKind::Ident && !tokens[p].is_keyword());
+        let after_macro = prev.is_some_and(|p| roles[p] == Role::MacroBang);
+
+        match token.text {
+            // 与编译器的规则一致：名称后紧跟的`<`开始类型参数，有空白时是比较运算符
+            "<" if (after_name && !token.space_before) || after_macro => {
+                if let Some(marks) = generic_tokens(tokens, &significant[position + 1..]) {
+                    roles[index] = Role::GenericOpen;
+                    for mark in marks {
+                        roles[mark] = if tokens[mark].is_punct("<") { Role::GenericOpen } else { Role::GenericClose };
+                    }
+                }
+            }
+            "!" if after_name && !token.space_before && next.is_some_and(|n| n.is_punct("(") || n.is_punct("<")) => {
+                roles[index] = Role::MacroBang;
+            }
+            "|" if !after_value => {
+                roles[index] = Role::LambdaOpen;
+                if let Some(close) = lambda_close(tokens, &significant[position + 1..]) {
+                    roles[close] = Role::LambdaClose;
+                }
+            }
+            "&" | "&&" | "*" | "!" | "-" if !after_value => roles[index] = Role::Prefix,
+            "@" => roles[index] = Role::Prefix,
+            _ => {}
+        }
+    }
+    roles
+}
+
+// 记号之后是否可以接二元运算符
+fn ends_value(token: &Token, role: Role) -> bool {
+    match token.kind {
+        TokenKind::Ident => !token.is_keyword(),
+        TokenKind::Number | TokenKind::ByteString | TokenKind::Label => true,
+        TokenKind::Punct => matches!(token.text, ")" | "]" | "}") || role == Role::GenericClose,
+        TokenKind::LineComment | TokenKind::BlockComment => false,
+    }
+}
+
+// 名称后的`<`是否开始类型参数，是时返回其中的`<`和`>`
+fn generic_tokens(tokens: &[Token], rest: &[usize]) -> Option<Vec<usize>> {
+    let mut depth = 1;
+    let mut marks = Vec::new();
+    for &index in rest {
+        let token = &tokens[index];
+        match (token.kind, token.text) {
+            (TokenKind::Ident | TokenKind::Number, _) => {}
+            (TokenKind::Punct, "::" | "," | ":" | "+") => {}
+            (TokenKind::Punct, "<") => {
+                depth += 1;
+                marks.push(index);
+            }
+            (TokenKind::Punct, ">" | ">>") => {
+                let closes = token.text.len();
+                if closes > depth {
+                    return None;
+                }
+                depth -= closes;
+                marks.push(index);
+                if depth == 0 {
+                    return Some(marks);
+                }
+            }
+            _ => return None,
+        }
+    }
+    None
+}
+
+// lambda参数列表结束的`|`
+fn lambda_close(tokens: &[Token], rest: &[usize]) -> Option<usize> {
+    let mut depth = 0usize;
+    for &index in rest {
+        match tokens[index].text {
+            _ if tokens[index].kind != TokenKind::Punct => {}
+            "(" | "[" | "{" => depth += 1,
+            ")" | "]" | "}" if depth == 0 => return None,
+            ")" | "]" | "}" => depth -= 1,
+            "|" if depth == 0 => return Some(index),
+            _ => {}
+        }
+    }
+    None
+}
+
+// ---------------------------------------------------------------------------
+// 排版
+
+#[derive(Clone, Debug)]
+enum Doc {
+    Text(String),
+    // 不换行时为空格
+    Line,
+    // 不换行时为空
+    SoftLine,
+    HardLine,
+    // 使所在的组换行
+    BreakParent,
+    // 只在所在的组换行时输出
+    IfBreak(&'static str),
+    Indent(Vec<Doc>),
+    Concat(Vec<Doc>),
+    // 内容和是否必须换行
+    Group(Vec<Doc>, bool),
+}
+
+impl Doc {
+    fn text(text: &str) -> Self {
+        Doc::Text(text.to_string())
+    }
+
+    fn group(docs: Vec<Doc>) -> Self {
+        let hard = docs.iter().any(Doc::forces_break);
+        Doc::Group(docs, hard)
+    }
+
+    fn forces_break(&self) -> bool {
+        match self {
+            Doc::HardLine | Doc::BreakParent => true,
+            Doc::Indent(docs) | Doc::Concat(docs) => docs.iter().any(Doc::forces_break),
+            Doc::Group(_, hard) => *hard,
+            _ => false,
+        }
+    }
+}
+
+// 花括号的排版方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum BraceKind {
+    // 函数体、模块、控制流的代码块：每条语句一行
+    Block,
+    // 结构体字段、枚举变体、match分支：每项一行
+    List,
+    // 结构体字面量、解构、use分组：放得下时保持在一行
+    Inline,
+}
+
+// 逗号分隔的一项，以及紧跟其后的注释
+struct Item<'n> {
+    nodes: &'n [Node],
+    comments: Vec<&'n Node>,
+}
+
+struct DocBuilder<'a> {
+    tokens: &'a [Token<'a>],
+    roles: Vec<Role>,
+}
+
+impl DocBuilder<'_> {
+    fn token(&self, node: &Node) -> Option<&Token<'_>> {
+        match node {
+            Node::Token(index) => Some(&self.tokens[*index]),
+            Node::Group { .. } => None,
+        }
+    }
+
+    fn is_comment(&self, node: &Node) -> bool {
+        self.token(node).is_some_and(Token::is_comment)
+    }
+
+    fn is_punct(&self, node: &Node, text: &str) -> bool {
+        self.token(node).is_some_and(|t| t.is_punct(text))
+    }
+
+    fn is_word(&self, node: &Node, words: &[&str]) -> bool {
+        self.token(node).is_some_and(|t| t.kind == TokenKind::Ident && words.contains(&t.text))
+    }
+
+    fn is_group(&self, node: &Node, open_text: &str) -> bool {
+        matches!(node, Node::Group { open, .. } if self.tokens[*open].text == open_text)
+    }
+
+    fn significant<'n>(&self, nodes: &'n [Node]) -> Vec<&'n Node> {
+        nodes.iter().filter(|node| !self.is_comment(node)).collect()
+    }
+
+    // 根据花括号前面的记号判断排版方式
+    fn brace_kind(&self, children: &[Node], prefix: &[Node]) -> BraceKind {
+        if children.iter().any(|node| self.is_punct(node, ";")) {
+            return BraceKind::Block;
+        }
+        let prefix = self.significant(prefix);
+        match prefix.as_slice() {
+            [.., last] if self.is_punct(last, "::") => return BraceKind::Inline,
+            [.., last] if self.is_word(last, &["else", "loop"]) => return BraceKind::Block,
+            [.., label, colon] if self.is_punct(colon, ":") && self.token(label).is_some_and(|t| t.kind == TokenKind::Label) => {
+                return BraceKind::Block;
+            }
+            [.., keyword, condition] if self.is_group(condition, "(") => {
+                if self.is_word(keyword, &["if", "while"]) {
+                    return BraceKind::Block;
+                }
+                if self.is_word(keyword, &["match"]) {
+                    return BraceKind::List;
+                }
+            }
+            _ => {}
+        }
+        for node in prefix {
+            if self.is_word(node, &["struct", "enum"]) {
+                return BraceKind::List;
+            }
+            if self.is_word(node, &["fun", "module", "spec"]) {
+                return BraceKind::Block;
+            }
+        }
+        BraceKind::Inline
+    }
+
+    // 按分号和代码块把节点分为语句，同一行的注释留在语句末尾
+    fn split_statements<'n>(&self, nodes: &'n [Node]) -> Vec<&'n [Node]> {
+        let mut statements = Vec::new();
+        let mut start = 0;
+        let mut index = 0;
+        while index < nodes.len() {
+            let node = &nodes[index];
+            let ends = if (index == start && self.is_comment(node)) || self.is_punct(node, ";") {
+                true
+            } else if let Node::Group { children, .. } = node {
+                self.is_group(node, "{")
+                    && self.brace_kind(children, &nodes[start..index]) != BraceKind::Inline
+                    && !self.continues_statement(&nodes[index + 1..])
+            } else {
+                false
+            };
+            index += 1;
+            if !ends {
+                continue;
+            }
+            if !self.is_comment(node) {
+                while index < nodes.len()
+                    && self.is_comment(&nodes[index])
+                    && self.tokens[nodes[index].first()].newlines_before == 0
+                {
+                    index += 1;
+                }
+            }
+            statements.push(&nodes[start..index]);
+            start = index;
+        }
+        if start < nodes.len() {
+            statements.push(&nodes[start..]);
+        }
+        statements
+    }
+
+    // 代码块之后的记号是否属于同一条语句，例如`else`、`;`和二元运算符
+    fn continues_statement(&self, rest: &[Node]) -> bool {
+        match rest.iter().find(|node| !self.is_comment(node)) {
+            Some(node) => match self.token(node) {
+                Some(token) => token.text == "else" || (token.kind == TokenKind::Punct && token.text != "#"),
+                None => false,
+            },
+            None => false,
+        }
+    }
+
+    // 按逗号分项，注释放在逗号之后
+    fn split_items<'n>(&self, nodes: &'n [Node]) -> Vec<Item<'n>> {
+        let mut items = Vec::new();
+        let mut start = 0;
+        let mut index = 0;
+        while index <= nodes.len() {
+            let at_end = index == nodes.len();
+            if !at_end && !self.is_punct(&nodes[index], ",") {
+                index += 1;
+                continue;
+            }
+            let mut end = index;
+            while end > start && self.is_comment(&nodes[end - 1]) {
+                end -= 1;
+            }
+            let mut comments: Vec<&Node> = nodes[end..index].iter().collect();
+            index += 1;
+            if !at_end {
+                while index < nodes.len()
+                    && self.is_comment(&nodes[index])
+                    && self.tokens[nodes[index].first()].newlines_before == 0
+                {
+                    comments.push(&nodes[index]);
+                    index += 1;
+                }
+            }
+            if end > start || !comments.is_empty() {
+                items.push(Item {
+                    nodes: &nodes[start..end],
+                    comments,
+                });
+            }
+            start = index;
+        }
+        items
+    }
+
+    // 一行一条语句，保留原来语句之间的空行
+    fn lines(&self, statements: &[&[Node]]) -> Vec<Doc> {
+        let mut docs = Vec::new();
+        for (index, statement) in statements.iter().enumerate() {
+            if index > 0 {
+                docs.push(Doc::HardLine);
+                if self.tokens[statement[0].first()].newlines_before > 1 {
+                    docs.push(Doc::HardLine);
+                }
+            }
+            docs.extend(self.seq(statement));
+        }
+        docs
+    }
+
+    // 一串节点，按记号的作用决定之间是否留空格
+    fn seq(&self, nodes: &[Node]) -> Vec<Doc> {
+        let mut docs = Vec::new();
+        let mut prev: Option<usize> = None;
+        let mut force_break = false;
+        for (index, node) in nodes.iter().enumerate() {
+            let first = &self.tokens[node.first()];
+            if let Some(prev) = prev {
+                if force_break || (first.is_comment() && first.newlines_before > 0) {
+                    docs.push(Doc::HardLine);
+                } else if self.space_between(prev, node.first()) {
+                    docs.push(Doc::text(" "));
+                }
+            }
+            match node {
+                Node::Token(token) => {
+                    docs.push(self.token_doc(&self.tokens[*token]));
+                    force_break = self.tokens[*token].kind == TokenKind::LineComment;
+                }
+                Node::Group { .. } => {
+                    docs.push(self.group_doc(node, &nodes[..index]));
+                    // 属性单独占一行
+                    force_break = self.is_group(node, "[")
+                        && self.significant(&nodes[..index]).last().is_some_and(|n| self.is_punct(n, "#"));
+                }
+            }
+            prev = Some(node.last());
+        }
+        docs
+    }
+
+    fn token_doc(&self, token: &Token) -> Doc {
+        match token.kind {
+            TokenKind::LineComment => Doc::Concat(vec![Doc::text(token.content()), Doc::BreakParent]),
+            _ => Doc::text(token.content()),
+        }
+    }
+
+    // 项之后的注释，`attached`为假时该项只有注释，第一条注释前不再换行
+    fn comment_docs(&self, comments: &[&Node], attached: bool) -> Vec<Doc> {
+        let mut docs = Vec::new();
+        for (index, comment) in comments.iter().enumerate() {
+            let token = &self.tokens[comment.first()];
+            if attached || index > 0 {
+                docs.push(if token.newlines_before > 0 { Doc::HardLine } else { Doc::text(" ") });
+            }
+            docs.push(self.token_doc(token));
+        }
+        docs
+    }
+
+    fn group_doc(&self, node: &Node, prefix: &[Node]) -> Doc {
+        let Node::Group { open, close, children } = node else {
+            return Doc::Concat(vec![]);
+        };
+        let (open, close) = (self.tokens[*open].text, self.tokens[*close].text);
+        if children.is_empty() {
+            return Doc::text(&format!("{}{}", open, close));
+        }
+        if open != "{" {
+            return self.list(open, close, children, false);
+        }
+        match self.brace_kind(children, prefix) {
+            BraceKind::Block => {
+                let (open, children) = self.open_brace(children);
+                if children.is_empty() {
+                    return Doc::Concat(vec![open, Doc::HardLine, Doc::text("}")]);
+                }
+                Doc::Concat(vec![
+                    open,
+                    Doc::Indent([vec![Doc::HardLine], self.lines(&self.split_statements(children))].concat()),
+                    Doc::HardLine,
+                    Doc::text("}"),
+                ])
+            }
+            BraceKind::List => {
+                let (open, children) = self.open_brace(children);
+                if children.is_empty() {
+                    return Doc::Concat(vec![open, Doc::HardLine, Doc::text("}")]);
+                }
+                let items = self.split_items(children);
+                let mut docs = Vec::new();
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        docs.push(Doc::HardLine);
+                        let first = item.nodes.first().map_or_else(|| item.comments[0].first(), Node::first);
+                        if self.tokens[first].newlines_before > 1 {
+                            docs.push(Doc::HardLine);
+                        }
+                    }
+                    docs.extend(self.seq(item.nodes));
+                    if !item.nodes.is_empty() {
+                        docs.push(Doc::text(","));
+                    }
+                    docs.extend(self.comment_docs(&item.comments, !item.nodes.is_empty()));
+                }
+                Doc::Concat(vec![open, Doc::Indent([vec![Doc::HardLine], docs].concat()), Doc::HardLine, Doc::text("}")])
+            }
+            BraceKind::Inline => {
+                let padded = !self.significant(prefix).last().is_some_and(|n| self.is_punct(n, "::"));
+                self.list(open, close, children, padded)
+            }
+        }
+    }
+
+    // 每项一行的花括号，与`{`同一行的注释留在`{`之后
+    fn open_brace<'n>(&self, children: &'n [Node]) -> (Doc, &'n [Node]) {
+        match children.split_first() {
+            Some((first, rest)) if self.is_comment(first) && self.tokens[first.first()].newlines_before == 0 => {
+                (Doc::Concat(vec![Doc::text("{ "), self.token_doc(&self.tokens[first.first()])]), rest)
+            }
+            _ => (Doc::text("{"), children),
+        }
+    }
+
+    // 放得下时保持在一行，否则每项一行并在最后一项后加逗号
+    fn list(&self, open: &str, close: &str, children: &[Node], padded: bool) -> Doc {
+        let has_comma = children.iter().any(|node| self.is_punct(node, ","));
+        let has_comment = children.iter().any(|node| self.is_comment(node));
+        if !has_comma && !has_comment && !padded {
+            return Doc::Concat([vec![Doc::text(open)], self.seq(children), vec![Doc::text(close)]].concat());
+        }
+
+        let line = if padded { Doc::Line } else { Doc::SoftLine };
+        let items = self.split_items(children);
+        let last_item = items.iter().rposition(|item| !item.nodes.is_empty());
+        let mut docs = vec![line.clone()];
+        for (index, item) in items.iter().enumerate() {
+            if index > 0 {
+                docs.push(Doc::Line);
+            }
+            docs.extend(self.seq(item.nodes));
+            if Some(index) == last_item {
+                if has_comma {
+                    docs.push(Doc::IfBreak(","));
+                }
+            } else if !item.nodes.is_empty() {
+                docs.push(Doc::text(","));
+            }
+            docs.extend(self.comment_docs(&item.comments, !item.nodes.is_empty()));
+        }
+        Doc::group(vec![Doc::text(open), Doc::Indent(docs), line, Doc::text(close)])
+    }
+
+    fn space_between(&self, prev: usize, next: usize) -> bool {
+        let (a, b) = (&self.tokens[prev], &self.tokens[next]);
+        let (a_role, b_role) = (self.roles[prev], self.roles[next]);
+        if a.is_comment() || b.is_comment() {
+            return true;
+        }
+        let attached = (b.kind == TokenKind::Punct && matches!(b.text, "," | ";" | ")" | "]" | "." | "::" | ":"))
+            || (a.kind == TokenKind::Punct && matches!(a.text, "(" | "[" | "." | "::" | "#"))
+            || matches!(a_role, Role::Prefix | Role::GenericOpen | Role::LambdaOpen | Role::MacroBang)
+            || matches!(b_role, Role::GenericOpen | Role::GenericClose | Role::LambdaClose | Role::MacroBang);
+        let space = if attached {
+            false
+        } else if b.is_punct("(") {
+            // `if (`、`while (`等关键字后留空格，函数调用和`public(package)`不留
+            match a.kind {
+                TokenKind::Ident => a.is_keyword() && a.text != "public",
+                TokenKind::Punct => !ends_value(a, a_role),
+                _ => false,
+            }
+        } else if b.is_punct("[") {
+            !ends_value(a, a_role)
+        } else {
+            true
+        };
+        // 去掉空格后两个记号不能合成一个，例如`& &x`
+        space || (a.kind == TokenKind::Punct && b.kind == TokenKind::Punct && merges(a.text, b.text))
+    }
+}
+
+fn merges(a: &str, b: &str) -> bool {
+    let joined = format!("{}{}", a, b);
+    !matches!(tokenize(&joined).as_deref(), Ok([x, y]) if x.text == a && y.text == b)
+}
+
+// ---------------------------------------------------------------------------
+// 输出
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Mode {
+    Flat,
+    Break,
+}
+
+fn print(doc: &Doc, options: &FormatOptions) -> String {
+    let mut output = String::new();
+    let mut column = 0;
+    let mut stack: Vec<(usize, Mode, &Doc)> = vec![(0, Mode::Break, doc)];
+    while let Some((indent, mode, doc)) = stack.pop() {
+        match doc {
+            Doc::Text(text) => {
+                output.push_str(text);
+                column = match text.rfind('\n') {
+                    Some(newline) => text[newline + 1..].chars().count(),
+                    None => column + text.chars().count(),
+                };
+            }
+            Doc::Line if mode == Mode::Flat => {
+                output.push(' ');
+                column += 1;
+            }
+            Doc::SoftLine if mode == Mode::Flat => {}
+            Doc::Line | Doc::SoftLine | Doc::HardLine => {
+                let trimmed = output.trim_end_matches(' ').len();
+                output.truncate(trimmed);
+                output.push('\n');
+                column = indent * options.indent_width;
+                output.push_str(&" ".repeat(column));
+            }
+            Doc::BreakParent => {}
+            Doc::IfBreak(text) => {
+                if mode == Mode::Break {
+                    output.push_str(text);
+                    column += text.len();
+                }
+            }
+            Doc::Indent(docs) => stack.extend(docs.iter().rev().map(|doc| (indent + 1, mode, doc))),
+            Doc::Concat(docs) => stack.extend(docs.iter().rev().map(|doc| (indent, mode, doc))),
+            Doc::Group(docs, hard) => {
+                let remaining = options.line_length as isize - column as isize;
+                let mode = if mode == Mode::Flat || (!*hard && fits(remaining, docs, &stack)) {
+                    Mode::Flat
+                } else {
+                    Mode::Break
+                };
+                stack.extend(docs.iter().rev().map(|doc| (indent, mode, doc)));
+            }
+        }
+    }
+
+    let mut formatted = output.lines().map(str::trim_end).collect::<Vec<_>>().join("\n");
+    formatted.truncate(formatted.trim_end().len());
+    formatted.push('\n');
+    formatted
+}
+
+// 组的内容不换行时，到下一个换行处为止是否放得下
+fn fits(mut remaining: isize, docs: &[Doc], rest: &[(usize, Mode, &Doc)]) -> bool {
+    let mut queue: Vec<(Mode, &Doc)> = docs.iter().rev().map(|doc| (Mode::Flat, doc)).collect();
+    let mut rest = rest.iter().rev();
+    loop {
+        let (mode, doc) = match queue.pop() {
+            Some(next) => next,
+            None => match rest.next() {
+                Some((_, mode, doc)) => (*mode, *doc),
+                None => return true,
+            },
+        };
+        match doc {
+            Doc::Text(text) => remaining -= text.chars().count() as isize,
+            Doc::Line if mode == Mode::Flat => remaining -= 1,
+            Doc::SoftLine if mode == Mode::Flat => {}
+            Doc::Line | Doc::SoftLine | Doc::HardLine => return true,
+            Doc::BreakParent => {}
+            Doc::IfBreak(text) => {
+                if mode == Mode::Break {
+                    remaining -= text.len() as isize;
+                }
+            }
+            Doc::Indent(docs) | Doc::Concat(docs) => queue.extend(docs.iter().rev().map(|doc| (mode, doc))),
+            Doc::Group(docs, hard) => {
+                let mode = if *hard { Mode::Break } else { mode };
+                queue.extend(docs.iter().rev().map(|doc| (mode, doc)));
+            }
+        }
+        if remaining < 0 {
+            return false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(lines: &[&str]) -> String {
+        lines.iter().map(|line| format!("{}\n", line)).collect()
+    }
+
+    // 格式化结果与预期一致，再次格式化不变
+    fn assert_formats(name: &str, source: &str, expected: &str, options: &FormatOptions) {
+        let formatted = format_tokens(source, options).unwrap();
+        assert_eq!(formatted, expected, "{}", name);
+        assert_eq!(format_tokens(&formatted, options).unwrap(), formatted, "{}: 格式化结果不是幂等的", name);
+    }
+
+    #[test]
+    fn formats_default_options() {
+        let cases = [
+            (
+                "注释",
+                lines(&[
+                    "module 0x1::m{",
+                    "// leading comment",
+                    "    /// doc comment",
+                    "  public fun f(x:u64):u64{ // trailing",
+                    "      /* block */ x+1",
+                    "  }",
+                    "    const A: u64 = 1; // after item",
+                    "",
+                    "",
+                    "    struct S has drop { // only comment",
+                    "    }",
+                    "}",
+                ]),
+                lines(&[
+                    "module 0x1::m {",
+                    "    // leading comment",
+                    "    /// doc comment",
+                    "    public fun f(x: u64): u64 { // trailing",
+                    "        /* block */",
+                    "        x + 1",
+                    "    }",
+                    "    const A: u64 = 1; // after item",
+                    "",
+                    "    struct S has drop { // only comment",
+                    "    }",
+                    "}",
+                ]),
+            ),
+            (
+                "属性",
+                lines(&[
+                    "module 0x1::m {",
+                    "    #[test_only] use std::debug;",
+                    "    #[test, expected_failure(abort_code = 1)]",
+                    "    fun t() { abort 1 }",
+                    "}",
+                ]),
+                lines(&[
+                    "module 0x1::m {",
+                    "    #[test_only]",
+                    "    use std::debug;",
+                    "    #[test, expected_failure(abort_code = 1)]",
+                    "    fun t() {",
+                    "        abort 1",
+                    "    }",
+                    "}",
+                ]),
+            ),
+            (
+                "lambda",
+                lines(&[
+                    "module 0x1::m {",
+                    "    macro fun apply($f: |u64| -> u64, $x: u64): u64 { $f($x) }",
+                    "    fun g(v: vector<u64>): u64 { apply!(|x| x*2, 3) + v.fold!(0, |acc,e| acc+e) }",
+                    "}",
+                ]),
+                lines(&[
+                    "module 0x1::m {",
+                    "    macro fun apply($f: |u64| -> u64, $x: u64): u64 {",
+                    "        $f($x)",
+                    "    }",
+                    "    fun g(v: vector<u64>): u64 {",
+                    "        apply!(|x| x * 2, 3) + v.fold!(0, |acc, e| acc + e)",
+                    "    }",
+                    "}",
+                ]),
+            ),
+            (
+                "宏",
+                lines(&[
+                    "module 0x1::m {",
+                    "    fun f(x: u64) { assert!(x>0,1); std::debug::print(&x); let v = vector[1,2,3]; }",
+                    "}",
+                ]),
+                lines(&[
+                    "module 0x1::m {",
+                    "    fun f(x: u64) {",
+                    "        assert!(x > 0, 1);",
+                    "        std::debug::print(&x);",
+                    "        let v = vector[1, 2, 3];",
+                    "    }",
+                    "}",
+                ]),
+            ),
+            (
+                "match",
+                lines(&[
+                    "module 0x1::m {",
+                    "    public enum Shape has drop { Circle(u64), Rect { w: u64, h: u64 } }",
+                    "    fun area(s: &Shape): u64 { match (s) { Shape::Circle(r) => *r * *r * 3, Shape::Rect { w, h } => *w * *h } }",
+                    "}",
+                ]),
+                lines(&[
+                    "module 0x1::m {",
+                    "    public enum Shape has drop {",
+                    "        Circle(u64),",
+                    "        Rect { w: u64, h: u64 },",
+                    "    }",
+                    "    fun area(s: &Shape): u64 {",
+                    "        match (s) {",
+                    "            Shape::Circle(r) => *r * *r * 3,",
+                    "            Shape::Rect { w, h } => *w * *h,",
+                    "        }",
+                    "    }",
+                    "}",
+                ]),
+            ),
+            (
+                "比较运算符与类型参数",
+                lines(&[
+                    "module 0x1::m {",
+                    "    fun f(a: u64, b: u64, c: u64, d: u64): bool { g(a < b, c > d) && a<=b }",
+                    "    fun h(): vector<u8> { let v: vector<vector<u8>> = vector[]; vector<u8>[1, 2] }",
+                    "}",
+                ]),
+                lines(&[
+                    "module 0x1::m {",
+                    "    fun f(a: u64, b: u64, c: u64, d: u64): bool {",
+                    "        g(a < b, c > d) && a <= b",
+                    "    }",
+                    "    fun h(): vector<u8> {",
+                    "        let v: vector<vector<u8>> = vector[];",
+                    "        vector<u8>[1, 2]",
+                    "    }",
+                    "}",
+                ]),
+            ),
+        ];
+        for (name, source, expected) in &cases {
+            assert_formats(name, source, expected, &FormatOptions::default());
+        }
+    }
+
+    #[test]
+    fn formats_with_indent_and_line_length() {
+        let source = lines(&[
+            "module 0x1::m {",
+            "    fun long_function_name(first_argument: u64, second_argument: u64, third_argument: u64): u64 {",
+            "        if (first_argument > 0) { first_argument } else { 0 }",
+            "    }",
+            "}",
+        ]);
+
+        let default = lines(&[
+            "module 0x1::m {",
+            "    fun long_function_name(first_argument: u64, second_argument: u64, third_argument: u64): u64 {",
+            "        if (first_argument > 0) {",
+            "            first_argument",
+            "        } else {",
+            "            0",
+            "        }",
+            "    }",
+            "}",
+        ]);
+        assert_formats("默认选项", &source, &default, &FormatOptions::default());
+
+        let narrow = lines(&[
+            "module 0x1::m {",
+            "  fun long_function_name(",
+            "    first_argument: u64,",
+            "    second_argument: u64,",
+            "    third_argument: u64,",
+            "  ): u64 {",
+            "    if (first_argument > 0) {",
+            "      first_argument",
+            "    } else {",
+            "      0",
+            "    }",
+            "  }",
+            "}",
+        ]);
+        assert_formats("缩进2、行宽60", &source, &narrow, &FormatOptions { indent_width: 2, line_length: 60 });
+    }
+
+    #[test]
+    fn rejects_invalid_options() {
+        assert!(FormatOptions { indent_width: 0, ..FormatOptions::default() }.validate().is_err());
+        assert!(FormatOptions { line_length: MIN_LINE_LENGTH - 1, ..FormatOptions::default() }.validate().is_err());
+    }
+
+    #[test]
+    fn reports_syntax_errors() {
+        let source = lines(&["module 0x1::m {", "    fun f() { let x = ; }", "}"]);
+        let failure = format_source(&source, "m.move", &FormatOptions::default()).unwrap_err();
+        assert_eq!(failure.message, "源码有语法错误");
+        let error = failure.diagnostics.iter().find(|diagnostic| diagnostic.is_error()).unwrap();
+        assert_eq!(error.primary.file, "sources/m.move");
+        assert_eq!(error.primary.line, 2);
+    }
+}
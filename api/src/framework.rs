@@ -0,0 +1,318 @@
+// Copyright (c) Move Web Compiler
+// SPDX-License-Identifier: Apache-2.0
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+/// Sui框架仓库地址，缓存缺失且允许联网时使用
+pub const SUI_REPOSITORY: &str = "https://github.com/MystenLabs/sui.git";
+
+/// 默认使用的框架版本
+pub const DEFAULT_FRAMEWORK_REVISION: &str = "framework/testnet";
+
+/// 框架包：Move.toml中的依赖名和在`crates/sui-framework/packages/`下的目录名
+pub const MOVE_STDLIB_PACKAGE: (&str, &str) = ("MoveStdlib", "move-stdlib");
+pub const SUI_FRAMEWORK_PACKAGE: (&str, &str) = ("Sui", "sui-framework");
+pub const DEEPBOOK_PACKAGE: (&str, &str) = ("DeepBook", "deepbook");
+
+/// 缓存目录中记录固定提交哈希的文件名
+pub const COMMIT_FILE: &str = "COMMIT";
+
+/// Sui网络
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Network {
+    Testnet,
+    Mainnet,
+    Devnet,
+}
+
+impl Network {
+    /// 网络名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Network::Testnet => "testnet",
+            Network::Mainnet => "mainnet",
+            Network::Devnet => "devnet",
+        }
+    }
+}
+
+/// 编译使用的框架：按网络选择，或显式指定git版本
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Framework {
+    /// 对应网络当前部署的框架，即`framework/<network>`分支
+    Network(Network),
+    /// 显式的git分支、标签或提交
+    Revision(String),
+}
+
+impl Framework {
+    /// 对应的git版本
+    pub fn revision(&self) -> String {
+        match self {
+            Framework::Network(network) => format!("framework/{}", network.as_str()),
+            Framework::Revision(revision) => revision.clone(),
+        }
+    }
+
+    /// 对应的网络，显式版本时为空
+    pub fn network(&self) -> Option<Network> {
+        match self {
+            Framework::Network(network) => Some(*network),
+            Framework::Revision(_) => None,
+        }
+    }
+}
+
+impl FromStr for Framework {
+    type Err = anyhow::Error;
+
+    /// 接受`testnet`、`mainnet`、`devnet`或git版本（如`framework/mainnet`、`mainnet-v1.38.1`、提交哈希）
+    fn from_str(s: &str) -> Result<Self> {
+        let value = s.trim();
+        match value.to_ascii_lowercase().as_str() {
+            "testnet" | "framework/testnet" => return Ok(Framework::Network(Network::Testnet)),
+            "mainnet" | "framework/mainnet" => return Ok(Framework::Network(Network::Mainnet)),
+            "devnet" | "framework/devnet" => return Ok(Framework::Network(Network::Devnet)),
+            _ => {}
+        }
+
+        // 版本会被写入Move.toml和缓存目录名，只允许git引用中常见的字符
+        let valid = !value.is_empty()
+            && value.len() <= 128
+            && !value.starts_with('-')
+            && !value.contains("..")
+            && value
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | '/'));
+        if !valid {
+            return Err(anyhow!("无效的框架版本: {}", s));
+        }
+        Ok(Framework::Revision(value.to_string()))
+    }
+}
+
+impl fmt::Display for Framework {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Framework::Network(network) => write!(f, "{}", network.as_str()),
+            Framework::Revision(revision) => write!(f, "{}", revision),
+        }
+    }
+}
+
+/// 框架依赖的来源
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FrameworkSource {
+    /// 本地缓存
+    Cache,
+    /// 从GitHub拉取
+    Git,
+}
+
+/// 框架缓存配置
+#[derive(Clone, Debug)]
+pub struct FrameworkConfig {
+    /// 缓存根目录，每个框架版本占用一个子目录
+    pub cache_dir: PathBuf,
+    /// 默认框架版本（网络名、git分支、标签或提交）
+    pub default_revision: String,
+    /// 离线模式：缓存中没有对应版本时直接报错，不回退到git
+    pub offline: bool,
+}
+
+impl Default for FrameworkConfig {
+    fn default() -> Self {
+        let cache_dir = dirs::home_dir()
+            .map(|home| home.join(".move-web-compiler").join("framework"))
+            .unwrap_or_else(|| std::env::temp_dir().join("move-web-compiler-framework"));
+        Self {
+            cache_dir,
+            default_revision: DEFAULT_FRAMEWORK_REVISION.to_string(),
+            offline: false,
+        }
+    }
+}
+
+impl FrameworkConfig {
+    /// 从环境变量读取配置：
+    /// `MOVE_FRAMEWORK_CACHE`、`MOVE_FRAMEWORK_REVISION`、`MOVE_FRAMEWORK_OFFLINE`
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Some(dir) = std::env::var_os("MOVE_FRAMEWORK_CACHE") {
+            config.cache_dir = PathBuf::from(dir);
+        }
+        if let Ok(revision) = std::env::var("MOVE_FRAMEWORK_REVISION") {
+            if !revision.trim().is_empty() {
+                config.default_revision = revision.trim().to_string();
+            }
+        }
+        if let Ok(offline) = std::env::var("MOVE_FRAMEWORK_OFFLINE") {
+            config.offline = matches!(offline.trim(), "1" | "true" | "yes");
+        }
+        config
+    }
+
+    /// 某个框架版本在缓存中的目录
+    pub fn revision_dir(&self, revision: &str) -> PathBuf {
+        self.cache_dir.join(revision_dir_name(revision))
+    }
+
+    /// 解析框架版本：优先使用本地缓存，缓存缺失时按配置回退到git或报错
+    pub fn resolve(&self, framework: &Framework) -> Result<ResolvedFramework> {
+        let revision = framework.revision();
+        let dir = self.revision_dir(&revision);
+        if is_seeded(&dir) {
+            let commit = std::fs::read_to_string(dir.join(COMMIT_FILE))
+                .ok()
+                .map(|c| c.trim().to_string())
+                .filter(|c| !c.is_empty());
+            return Ok(ResolvedFramework {
+                revision,
+                network: framework.network(),
+                commit,
+                source: FrameworkSource::Cache,
+                local_dir: Some(dir),
+            });
+        }
+
+        if self.offline {
+            return Err(anyhow!(
+                "框架缓存 {:?} 中没有版本 {}，请先运行 scripts/seed-framework.sh {}",
+                self.cache_dir,
+                revision,
+                revision
+            ));
+        }
+
+        Ok(ResolvedFramework {
+            revision,
+            network: framework.network(),
+            commit: None,
+            source: FrameworkSource::Git,
+            local_dir: None,
+        })
+    }
+
+    /// 解析默认框架版本
+    pub fn resolve_default(&self) -> Result<ResolvedFramework> {
+        self.resolve(&self.default_revision.parse()?)
+    }
+
+    /// 解析请求指定的框架版本，未指定时使用默认版本
+    pub fn resolve_or_default(&self, framework: Option<&Framework>) -> Result<ResolvedFramework> {
+        match framework {
+            Some(framework) => self.resolve(framework),
+            None => self.resolve_default(),
+        }
+    }
+}
+
+/// 解析后的框架依赖
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ResolvedFramework {
+    /// 框架版本（git分支、标签或提交）
+    pub revision: String,
+    /// 按网络选择时对应的网络
+    pub network: Option<Network>,
+    /// 缓存固定的提交哈希，来自git时为空
+    pub commit: Option<String>,
+    /// 依赖来源
+    pub source: FrameworkSource,
+    /// 本地缓存目录，来自git时为空
+    #[serde(skip)]
+    pub local_dir: Option<PathBuf>,
+}
+
+impl ResolvedFramework {
+    /// 是否来自本地缓存
+    pub fn is_local(&self) -> bool {
+        self.local_dir.is_some()
+    }
+
+    /// 生成Move.toml中的`[dependencies]`段
+    ///
+    /// MoveStdlib由Sui框架自身的Move.toml引入；DeepBook只在源码引用时加入，避免拖慢普通编译。
+    pub fn dependencies_toml(&self, with_deepbook: bool) -> Result<String> {
+        let mut packages = vec![SUI_FRAMEWORK_PACKAGE];
+        if with_deepbook {
+            packages.push(DEEPBOOK_PACKAGE);
+        }
+
+        let mut toml = String::from("[dependencies]\n");
+        for (name, dir) in packages {
+            match &self.local_dir {
+                Some(local_dir) => {
+                    let package_dir = local_dir.join(dir);
+                    if !package_dir.join("Move.toml").is_file() {
+                        return Err(anyhow!("框架缓存 {:?} 中缺少 {} 包", local_dir, name));
+                    }
+                    toml.push_str(&format!(
+                        "{} = {{ local = \"{}\" }}\n",
+                        name,
+                        package_dir.display().to_string().replace('\\', "/")
+                    ))
+                }
+                None => toml.push_str(&format!(
+                    "{} = {{ git = \"{}\", subdir = \"crates/sui-framework/packages/{}\", rev = \"{}\" }}\n",
+                    name, SUI_REPOSITORY, dir, self.revision
+                )),
+            }
+        }
+        Ok(toml)
+    }
+}
+
+/// 源码是否引用了DeepBook
+pub fn references_deepbook<'a>(sources: impl IntoIterator<Item = &'a String>) -> bool {
+    sources.into_iter().any(|source| source.contains("deepbook::"))
+}
+
+// 缓存目录中是否已经包含编译所需的框架包
+fn is_seeded(dir: &Path) -> bool {
+    [MOVE_STDLIB_PACKAGE, SUI_FRAMEWORK_PACKAGE]
+        .iter()
+        .all(|(_, package_dir)| dir.join(package_dir).join("Move.toml").is_file())
+}
+
+// 将git版本名转换为目录名，[A-Za-z0-9._-]以外的字节按%XX编码，例如framework/testnet -> framework%2Ftestnet
+// 开头的`.`也编码，避免`.`、`..`和隐藏目录；编码可逆，不同版本不会共用目录
+fn revision_dir_name(revision: &str) -> String {
+    let mut name = String::with_capacity(revision.len());
+    for (index, byte) in revision.bytes().enumerate() {
+        let keep = byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_' || (byte == b'.' && index > 0);
+        if keep {
+            name.push(byte as char);
+        } else {
+            name.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn revision_dir_names_are_escaped() {
+        assert_eq!(revision_dir_name("framework/testnet"), "framework%2Ftestnet");
+        assert_eq!(revision_dir_name("mainnet-v1.30.2"), "mainnet-v1.30.2");
+        assert_eq!(revision_dir_name("a%2Fb"), "a%252Fb");
+        assert_eq!(revision_dir_name(".."), "%2E.");
+        assert_eq!(revision_dir_name(".hidden"), "%2Ehidden");
+    }
+
+    #[test]
+    fn distinct_revisions_use_distinct_dirs() {
+        let revisions = ["framework/testnet", "framework-testnet", "framework%2Ftestnet", "framework_testnet", ".", "%2E"];
+        let names: BTreeSet<String> = revisions.iter().map(|revision| revision_dir_name(revision)).collect();
+        assert_eq!(names.len(), revisions.len());
+    }
+}
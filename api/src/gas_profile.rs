@@ -0,0 +1,424 @@
+// Copyright (c) Move Web Compiler
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use sui_protocol_config::ProtocolConfig;
+
+/// 开启Move虚拟机gas分析器的环境变量，测试运行器据此把分析结果写入工作目录
+pub const PROFILER_ENV_VAR: &str = "MOVE_VM_PROFILE";
+
+// 分析器输出的文件名前缀
+const PROFILE_FILE_PREFIX: &str = "gas_profile_";
+
+/// gas分析选项
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasProfileOptions {
+    /// 按函数统计gas、指令数和原生函数调用
+    #[serde(default)]
+    pub gas_profile: bool,
+    /// 同时输出火焰图使用的折叠调用栈，隐含`gas_profile`
+    #[serde(default)]
+    pub folded_stacks: bool,
+}
+
+impl GasProfileOptions {
+    /// 是否需要开启分析器
+    pub fn enabled(&self) -> bool {
+        self.gas_profile || self.folded_stacks
+    }
+}
+
+/// 单个函数的gas统计
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FunctionGasProfile {
+    /// 函数名，例如`0x2::coin::mint`
+    pub function: String,
+    /// 调用次数
+    pub calls: u64,
+    /// 包括被调用函数在内的gas，递归调用只计算最外层
+    pub gas: u64,
+    /// 函数自身的gas，不包括被调用的函数
+    pub self_gas: u64,
+    /// 执行的字节码指令数，分析器未记录指令时为0
+    pub instructions: u64,
+    /// 调用原生函数的次数
+    pub native_calls: u64,
+    /// 是否为原生函数
+    pub native: bool,
+}
+
+/// 一次执行（一个测试或一笔交易）的gas分析结果
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GasProfile {
+    /// 分析器记录的名称：测试函数名或交易摘要
+    pub name: String,
+    /// 执行消耗的gas（虚拟机内部单位，与`gas_used`一致）
+    pub total_gas: u64,
+    /// 执行的字节码指令总数
+    pub instructions: u64,
+    /// 原生函数调用总数
+    pub native_calls: u64,
+    /// 交易写入对象的字节数，只有本地执行的交易有，无法归属到单个函数
+    pub storage_bytes: Option<u64>,
+    /// 按自身gas从高到低排列的函数统计
+    pub functions: Vec<FunctionGasProfile>,
+    /// 折叠调用栈，每行为`调用栈 自身gas`，可直接交给`flamegraph.pl`或`inferno`
+    pub folded_stacks: Option<String>,
+}
+
+impl GasProfile {
+    /// 解析分析器输出的speedscope格式文件，一个文件只包含一次执行
+    ///
+    /// 名称包含`::`的帧为函数，其他帧为字节码指令，指令的gas计入所在函数自身。
+    pub fn from_speedscope(content: &str, options: &GasProfileOptions) -> Result<Self> {
+        let file: SpeedscopeFile = serde_json::from_str(content)?;
+        let profile = file
+            .profiles
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("gas分析结果中没有执行记录"))?;
+        let frame_name = |index: usize| {
+            file.shared
+                .frames
+                .get(index)
+                .map(|frame| frame.name.as_str())
+                .ok_or_else(|| anyhow!("gas分析结果引用了不存在的帧 {}", index))
+        };
+        let natives = native_functions();
+
+        let mut functions: HashMap<&str, FunctionGasProfile> = HashMap::new();
+        let mut stacks: HashMap<String, u64> = HashMap::new();
+        let mut open: Vec<OpenFrame> = Vec::new();
+        let mut total_gas = 0;
+        for event in &profile.events {
+            let name = frame_name(event.frame)?;
+            if event.type_ == "O" {
+                open.push(OpenFrame {
+                    frame: event.frame,
+                    opened_at: event.at,
+                    child_gas: 0,
+                });
+                continue;
+            }
+
+            let frame = open
+                .pop()
+                .filter(|frame| frame.frame == event.frame)
+                .ok_or_else(|| anyhow!("gas分析结果的帧 {} 没有正确嵌套", name))?;
+            let gas = event.at.saturating_sub(frame.opened_at);
+            let parent = open.iter().rposition(|frame| is_function(frame_name(frame.frame).unwrap_or_default()));
+
+            if !is_function(name) {
+                if let Some(parent) = parent {
+                    let parent_name = frame_name(open[parent].frame)?;
+                    function_entry(&mut functions, parent_name, natives).instructions += 1;
+                }
+                continue;
+            }
+
+            let self_gas = gas.saturating_sub(frame.child_gas);
+            let recursive = open.iter().any(|frame| frame.frame == event.frame);
+            let native = {
+                let entry = function_entry(&mut functions, name, natives);
+                entry.calls += 1;
+                entry.self_gas += self_gas;
+                if !recursive {
+                    entry.gas += gas;
+                }
+                entry.native
+            };
+
+            let mut stack = open
+                .iter()
+                .filter_map(|frame| frame_name(frame.frame).ok())
+                .filter(|name| is_function(name))
+                .collect::<Vec<_>>();
+            stack.push(name);
+            *stacks.entry(stack.join(";")).or_default() += self_gas;
+
+            match parent {
+                Some(parent) => {
+                    open[parent].child_gas += gas;
+                    if native {
+                        let parent_name = frame_name(open[parent].frame)?;
+                        function_entry(&mut functions, parent_name, natives).native_calls += 1;
+                    }
+                }
+                None => total_gas += gas,
+            }
+        }
+
+        let mut functions: Vec<FunctionGasProfile> = functions.into_values().collect();
+        functions.sort_by(|a, b| b.self_gas.cmp(&a.self_gas).then_with(|| a.function.cmp(&b.function)));
+        let folded_stacks = options.folded_stacks.then(|| {
+            let mut stacks: Vec<_> = stacks.into_iter().filter(|(_, gas)| *gas > 0).collect();
+            stacks.sort();
+            stacks.iter().fold(String::new(), |mut output, (stack, gas)| {
+                let _ = writeln!(output, "{} {}", stack, gas);
+                output
+            })
+        });
+
+        Ok(Self {
+            name: profile.name,
+            total_gas: profile.end_value.saturating_sub(profile.start_value).max(total_gas),
+            instructions: functions.iter().map(|f| f.instructions).sum(),
+            native_calls: functions.iter().map(|f| f.native_calls).sum(),
+            storage_bytes: None,
+            functions,
+            folded_stacks,
+        })
+    }
+}
+
+/// 分析器的输出目录，删除时一并删除其中的文件
+pub struct ProfileDir {
+    path: PathBuf,
+}
+
+impl ProfileDir {
+    /// 在系统临时目录下创建空目录
+    pub fn create() -> Result<Self> {
+        let path = std::env::temp_dir().join(format!("move-gas-profile-{:016x}", rand::random::<u64>()));
+        std::fs::create_dir_all(&path)?;
+        Ok(Self { path })
+    }
+
+    /// 目录路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 读取并删除目录中的分析结果，按文件名（包含写入时间）排序
+    pub fn take_profiles(&self, options: &GasProfileOptions) -> Result<Vec<GasProfile>> {
+        let mut paths = std::fs::read_dir(&self.path)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with(PROFILE_FILE_PREFIX) && name.ends_with(".json"))
+            })
+            .collect::<Vec<_>>();
+        paths.sort();
+
+        let mut profiles = Vec::with_capacity(paths.len());
+        for path in paths {
+            let content = std::fs::read_to_string(&path);
+            let _ = std::fs::remove_file(&path);
+            profiles.push(GasProfile::from_speedscope(&content?, options)?);
+        }
+        Ok(profiles)
+    }
+}
+
+impl Drop for ProfileDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
+
+/// 打印函数统计表，最多`limit`行
+pub fn render_table(profile: &GasProfile, limit: usize) -> String {
+    let mut output = format!(
+        "{:<48} {:>8} {:>12} {:>12} {:>10} {:>8}\n",
+        "函数", "调用", "gas", "自身gas", "指令", "原生调用"
+    );
+    for function in profile.functions.iter().take(limit) {
+        let name = if function.native {
+            format!("{} [native]", function.function)
+        } else {
+            function.function.clone()
+        };
+        let _ = writeln!(
+            output,
+            "{:<48} {:>8} {:>12} {:>12} {:>10} {:>8}",
+            name, function.calls, function.gas, function.self_gas, function.instructions, function.native_calls
+        );
+    }
+    if profile.functions.len() > limit {
+        let _ = writeln!(output, "... 另有 {} 个函数", profile.functions.len() - limit);
+    }
+    output
+}
+
+// 尚未关闭的帧
+struct OpenFrame {
+    frame: usize,
+    opened_at: u64,
+    child_gas: u64,
+}
+
+#[derive(Deserialize)]
+struct SpeedscopeFile {
+    shared: SpeedscopeShared,
+    profiles: Vec<SpeedscopeProfile>,
+}
+
+#[derive(Deserialize)]
+struct SpeedscopeShared {
+    frames: Vec<SpeedscopeFrame>,
+}
+
+#[derive(Deserialize)]
+struct SpeedscopeFrame {
+    name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SpeedscopeProfile {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    start_value: u64,
+    #[serde(default)]
+    end_value: u64,
+    events: Vec<SpeedscopeEvent>,
+}
+
+#[derive(Deserialize)]
+struct SpeedscopeEvent {
+    #[serde(rename = "type")]
+    type_: String,
+    frame: usize,
+    at: u64,
+}
+
+fn is_function(name: &str) -> bool {
+    name.contains("::")
+}
+
+fn function_entry<'a, 'b>(
+    functions: &'b mut HashMap<&'a str, FunctionGasProfile>,
+    name: &'a str,
+    natives: &HashSet<String>,
+) -> &'b mut FunctionGasProfile {
+    functions.entry(name).or_insert_with(|| FunctionGasProfile {
+        function: name.to_string(),
+        calls: 0,
+        gas: 0,
+        self_gas: 0,
+        instructions: 0,
+        native_calls: 0,
+        native: natives.contains(short_function_name(name)),
+    })
+}
+
+// 去掉地址后的`module::function`，分析器可能输出带或不带地址的函数名
+fn short_function_name(name: &str) -> &str {
+    match name.rmatch_indices("::").nth(1) {
+        Some((index, _)) => &name[index + 2..],
+        None => name,
+    }
+}
+
+// 测试和本地执行中注册的原生函数，格式为`module::function`
+fn native_functions() -> &'static HashSet<String> {
+    static NATIVES: OnceLock<HashSet<String>> = OnceLock::new();
+    NATIVES.get_or_init(|| {
+        sui_move_natives::all_natives(/* silent */ true, &ProtocolConfig::get_for_max_version_UNSAFE())
+            .into_iter()
+            .map(|(_, module, function, _)| format!("{}::{}", module, function))
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 一个测试的分析结果：测试函数调用`coin::mint`（其中调用原生函数`event::emit`）和递归的`helper`
+    const SPEEDSCOPE: &str = include_str!("../tests/golden/gas_profile/test_mint.speedscope.json");
+
+    const TEST: &str = "0x0::hello::test_mint";
+    const MINT: &str = "0x2::coin::mint";
+    const EMIT: &str = "0x2::event::emit";
+    const HELPER: &str = "0x0::hello::helper";
+
+    fn parse(folded_stacks: bool) -> GasProfile {
+        let options = GasProfileOptions {
+            gas_profile: true,
+            folded_stacks,
+        };
+        GasProfile::from_speedscope(SPEEDSCOPE, &options).unwrap()
+    }
+
+    fn function<'a>(profile: &'a GasProfile, name: &str) -> &'a FunctionGasProfile {
+        profile.functions.iter().find(|f| f.function == name).unwrap()
+    }
+
+    #[test]
+    fn sums_gas_per_function() {
+        let profile = parse(false);
+        assert_eq!(profile.name, "test_mint");
+        assert_eq!(profile.total_gas, 40);
+
+        // (函数, 调用次数, gas, 自身gas, 指令数, 原生调用, 是否原生)
+        let functions: Vec<_> = profile
+            .functions
+            .iter()
+            .map(|f| (f.function.as_str(), f.calls, f.gas, f.self_gas, f.instructions, f.native_calls, f.native))
+            .collect();
+        assert_eq!(functions, [
+            (EMIT, 1, 20, 20, 0, 0, true),
+            (TEST, 1, 40, 11, 2, 0, false),
+            (HELPER, 2, 5, 5, 2, 0, false),
+            (MINT, 1, 24, 4, 2, 1, false),
+        ]);
+    }
+
+    #[test]
+    fn sums_totals() {
+        let profile = parse(false);
+        assert_eq!(profile.instructions, 6);
+        assert_eq!(profile.native_calls, 1);
+        // 写入对象的字节数只有本地执行的交易才有
+        assert_eq!(profile.storage_bytes, None);
+        assert_eq!(profile.functions.iter().map(|f| f.self_gas).sum::<u64>(), profile.total_gas);
+        // 递归调用只计算最外层
+        assert_eq!(function(&profile, HELPER).gas, 5);
+        assert_eq!(profile.folded_stacks, None);
+    }
+
+    #[test]
+    fn folds_stacks() {
+        let profile = parse(true);
+        let expected = [
+            format!("{} 11", TEST),
+            format!("{};{} 2", TEST, HELPER),
+            format!("{};{};{} 3", TEST, HELPER, HELPER),
+            format!("{};{} 4", TEST, MINT),
+            format!("{};{};{} 20", TEST, MINT, EMIT),
+        ];
+        assert_eq!(profile.folded_stacks.unwrap(), expected.join("\n") + "\n");
+    }
+
+    #[test]
+    fn rejects_malformed_profiles() {
+        let options = GasProfileOptions::default();
+        let frames = r#""shared": { "frames": [{ "name": "0x2::coin::mint" }, { "name": "RET" }] }"#;
+        for profiles in [
+            r#"[]"#,
+            r#"[{ "events": [{ "type": "O", "frame": 0, "at": 0 }, { "type": "C", "frame": 1, "at": 1 }] }]"#,
+            r#"[{ "events": [{ "type": "O", "frame": 2, "at": 0 }] }]"#,
+        ] {
+            let content = format!(r#"{{ {}, "profiles": {} }}"#, frames, profiles);
+            assert!(GasProfile::from_speedscope(&content, &options).is_err(), "{}", profiles);
+        }
+    }
+
+    #[test]
+    fn renders_table() {
+        let table = render_table(&parse(false), 2);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[1].starts_with(&format!("{} [native]", EMIT)));
+        assert!(lines[2].starts_with(TEST));
+        assert_eq!(lines[3], "... 另有 2 个函数");
+    }
+}
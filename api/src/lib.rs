@@ -1,10 +1,40 @@
 // Copyright (c) Move Web Compiler
 // SPDX-License-Identifier: Apache-2.0
 
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use anyhow::{Result, anyhow};
 use serde::{Deserialize, Serialize};
 
+pub mod abi;
+pub mod cache;
+pub mod call;
+pub mod codegen;
+pub mod compiler;
+pub mod config;
+pub mod coverage;
+pub mod diagnostics;
+pub mod disassemble;
+pub mod format;
+pub mod framework;
+pub mod gas_profile;
+pub mod operations;
+pub mod package;
+pub mod rpc;
+pub mod scheduler;
+pub mod session;
+pub mod simulate;
+pub mod transaction;
+pub mod unit_test;
+pub mod upgrade;
+pub mod workspace;
+
+use cache::CompileCache;
+use compiler::BuildFailure;
+use diagnostics::Diagnostic;
+use framework::{Framework, FrameworkConfig, ResolvedFramework};
+use workspace::WorkspaceConfig;
+
 // 重新导出Sui编译相关的关键类型
 pub use sui_move_build;
 pub use sui_types::move_package;
@@ -23,6 +53,22 @@ pub struct CompileOptions {
     pub generate_struct_layouts: bool,
     /// 链ID，用于解析依赖项
     pub chain_id: Option<String>,
+    /// 编译使用的Sui框架（网络或git版本），为空时使用配置的默认版本
+    pub framework: Option<Framework>,
+}
+
+impl CompileOptions {
+    /// 影响编译结果的选项，作为编译缓存键的一部分（框架版本单独计入）
+    pub fn cache_fingerprint(&self) -> String {
+        format!(
+            "with_unpublished_dependencies={};dump_bytecode_as_base64={};ignore_chain={};generate_struct_layouts={};chain_id={}",
+            self.with_unpublished_dependencies,
+            self.dump_bytecode_as_base64,
+            self.ignore_chain,
+            self.generate_struct_layouts,
+            self.chain_id.as_deref().unwrap_or_default(),
+        )
+    }
 }
 
 impl Default for CompileOptions {
@@ -33,6 +79,7 @@ impl Default for CompileOptions {
             ignore_chain: true,
             generate_struct_layouts: false,
             chain_id: None,
+            framework: None,
         }
     }
 }
@@ -46,6 +93,13 @@ pub struct CompileResult {
     pub bytecode_base64: Vec<String>,
     /// 错误信息（如果编译失败）
     pub error_message: Option<String>,
+    /// 结构化的编译器诊断（错误和警告）
+    pub diagnostics: Vec<Diagnostic>,
+    /// 实际使用的Sui框架版本
+    pub framework: Option<ResolvedFramework>,
+    /// 结果是否来自编译缓存
+    #[serde(default)]
+    pub cache_hit: bool,
 }
 
 /// 编译Move源代码到字节码
@@ -54,64 +108,102 @@ pub async fn compile_move_source(
     file_name: &str,
     options: &CompileOptions,
 ) -> Result<CompileResult> {
-    // 创建临时目录和源文件
-    let package_path = create_temp_package(source_code, file_name).await?;
+    let mut files = BTreeMap::new();
+    files.insert(package::source_file_path(file_name), source_code.to_string());
+    compile_move_files(&files, options).await
+}
+
+/// 将多个源文件作为同一个包编译，键为包内相对路径（如`sources/a.move`）
+pub async fn compile_move_files(
+    files: &BTreeMap<String, String>,
+    options: &CompileOptions,
+) -> Result<CompileResult> {
+    // 解析框架版本，创建临时目录和源文件
+    let framework = FrameworkConfig::from_env().resolve_or_default(options.framework.as_ref())?;
+    let package_path = create_temp_package(files, &framework).await?;
     
-    // 编译并返回结果
-    match compile_move_package(&package_path, options).await {
-        Ok(bytecode) => Ok(CompileResult {
+    // 编译并返回结果，结果不引用包目录，编译后即可删除
+    let result = compile_package_cached(&package_path, &framework, options).await;
+    let _ = WorkspaceConfig::from_env().remove(&package_path);
+    result
+}
+
+// 先查编译缓存，未命中时编译并写入缓存
+async fn compile_package_cached(
+    package_path: &Path,
+    framework: &ResolvedFramework,
+    options: &CompileOptions,
+) -> Result<CompileResult> {
+    let cache = CompileCache::from_env();
+    let cache_key = cache.key(package_path, framework, &options.cache_fingerprint())?;
+    if let Some(cached) = cache_key.as_deref().and_then(|key| cache.get::<CompileResult>(key)) {
+        return Ok(CompileResult {
+            framework: Some(framework.clone()),
+            cache_hit: true,
+            ..cached
+        });
+    }
+
+    let result = match compile_move_package(package_path, options).await {
+        Ok((bytecode, diagnostics)) => CompileResult {
             success: true,
             bytecode_base64: bytecode,
             error_message: None,
-        }),
-        Err(e) => Ok(CompileResult {
+            diagnostics,
+            framework: Some(framework.clone()),
+            cache_hit: false,
+        },
+        Err(e) => CompileResult {
             success: false,
             bytecode_base64: vec![],
             error_message: Some(format!("编译错误: {}", e)),
-        }),
+            diagnostics: e.diagnostics,
+            framework: Some(framework.clone()),
+            cache_hit: false,
+        },
+    };
+
+    // 只缓存确定性的结果：编译成功或带诊断的编译错误，写入失败不影响本次结果
+    if let Some(key) = &cache_key {
+        if result.success || !result.diagnostics.is_empty() {
+            let _ = cache.put(key, &result);
+        }
     }
+    Ok(result)
 }
 
-/// 创建临时包目录和文件
-pub async fn create_temp_package(source_code: &str, file_name: &str) -> Result<PathBuf> {
-    let temp_dir = std::env::temp_dir().join("move-web-compiler");
-    if !temp_dir.exists() {
-        std::fs::create_dir_all(&temp_dir)?;
-    }
-
-    // 写入源文件
-    let source_path = temp_dir.join(file_name);
-    std::fs::write(&source_path, source_code)?;
+/// 在独立的工作区中创建临时包目录和文件
+pub async fn create_temp_package(files: &BTreeMap<String, String>, framework: &ResolvedFramework) -> Result<PathBuf> {
+    let temp_dir = WorkspaceConfig::from_env().create()?;
+    package::write_package_files(&temp_dir, files)?;
 
-    // 创建Move.toml配置文件
-    let move_toml_content = r#"[package]
+    // 创建Move.toml配置文件，框架依赖优先指向本地缓存
+    let move_toml_content = format!(r#"[package]
 name = "MoveWebCompile"
 version = "0.0.1"
 
-[dependencies]
-Sui = { git = "https://github.com/MystenLabs/sui.git", subdir = "crates/sui-framework/packages/sui-framework", rev = "main" }
-
+{}
 [addresses]
 std = "0x1"
 sui = "0x2"
-"#;
+"#, framework.dependencies_toml(framework::references_deepbook(files.values()))?);
     let move_toml_path = temp_dir.join("Move.toml");
     std::fs::write(&move_toml_path, move_toml_content)?;
 
     Ok(temp_dir)
 }
 
-/// 编译Move包
+/// 编译Move包，返回字节码和编译器诊断
 pub async fn compile_move_package(
     package_path: &Path,
     options: &CompileOptions,
-) -> Result<Vec<String>> {
+) -> std::result::Result<(Vec<String>, Vec<Diagnostic>), BuildFailure> {
     // 创建Sui编译配置 - 直接使用测试配置
     let sui_build_config = sui_move_build::BuildConfig::new_for_testing();
 
     // 编译包
-    let compiled_package = sui_build_config.build(package_path)
-        .map_err(|e| anyhow!("编译失败: {:?}", e))?;
+    let compiler::BuildOutput { package: compiled_package, diagnostics } =
+        compiler::build_package(sui_build_config, package_path)?;
 
     // 获取字节码（根据选项决定是否包含未发布的依赖）
     let bytecode_base64 = compiled_package
@@ -120,7 +212,7 @@ pub async fn compile_move_package(
         .map(|b| b.encoded().to_string())
         .collect();
 
-    Ok(bytecode_base64)
+    Ok((bytecode_base64, diagnostics))
 }
 
 /// 检查源文件是否存在
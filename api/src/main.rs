@@ -1,48 +1,151 @@
 // Copyright (c) Move Web Compiler
 // SPDX-License-Identifier: Apache-2.0
 
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use actix_web::{web, App, HttpResponse, HttpServer, Responder};
 use actix_cors::Cors;
 use actix_files as fs;
+use clap::Parser;
 use serde::{Deserialize, Serialize};
 use anyhow::{Result, anyhow};
 use chrono::Local;
 use std::fs as std_fs;
 use tokio::fs::File;
 use tokio::io::AsyncReadExt;
-use std::process::Command;
+use std::process::{Command, Stdio};
+
+mod cli;
+use cli::{CliOptions, Commands, DisassembleArgs, FormatArgs, ServeArgs, SimulateArgs, TestArgs};
 
 // 导入Sui相关的编译依赖
 use sui_move_build;
-// 添加base64依赖
-use base64;
+use move_web_compiler::CompileOptions;
+use move_web_compiler::abi::{self, ModuleAbi};
+use move_web_compiler::cache::CompileCache;
+use move_web_compiler::call::{self, CallTransactionRequest};
+use move_web_compiler::codegen::{self, CodegenOptions, CodegenOutput, GeneratedFile};
+use move_web_compiler::compiler::{self, BuildFailure, CompiledModuleInfo};
+use move_web_compiler::config::ServerConfig;
+use move_web_compiler::coverage::{self, CoverageReport};
+use move_web_compiler::disassemble::{self, DisassembleOptions, DisassembledModule};
+use move_web_compiler::diagnostics::{self, Diagnostic, Severity};
+use move_web_compiler::format::{self, FormatOptions};
+use move_web_compiler::framework::{self, Framework, FrameworkConfig, ResolvedFramework};
+use move_web_compiler::gas_profile::{self, GasProfile, GasProfileOptions};
+use move_web_compiler::operations::{self, ErrorCode, Operation, OperationError};
+use move_web_compiler::package;
+use move_web_compiler::rpc::{ExecuteRequest, ObjectSummary, RpcConfig, SuiRpc, TransactionSummary};
+use move_web_compiler::scheduler::{self, BuildScheduler, ScheduleError, SchedulerConfig};
+use move_web_compiler::session::{self, SessionCallRequest, SessionConfig, SessionStore, SimulationSession};
+use move_web_compiler::simulate::{self, LocalSimulator, ObjectDetail, SimulatedPackage, SimulateRequest, SimulationOutcome, SimulationReport};
+use move_web_compiler::transaction::{self, PublishTransactionRequest, UnsignedTransaction};
+use move_web_compiler::upgrade::{self, CompatibilityViolation, UpgradePolicy, UpgradeTransactionRequest};
+use move_web_compiler::unit_test::{self, TestCaseResult, UnitTestOptions, UnitTestReport};
+use move_web_compiler::workspace::WorkspaceConfig;
 
-// 编译请求的数据结构
+// 包内容的请求字段，编译和测试请求共用
 #[derive(Debug, Deserialize)]
-struct CompileRequest {
-    source_code: String,
+struct PackageRequest {
+    source_code: Option<String>,
     file_name: Option<String>,
+    files: Option<BTreeMap<String, String>>, // 多文件包：包内相对路径（sources/、tests/、examples/）到文件内容的映射
     addresses_toml_content: Option<String>, // 新增字段，用于接收前端的地址配置
+    #[serde(alias = "network")]
+    framework: Option<String>, // 编译使用的框架：testnet/mainnet/devnet或显式的git版本
 }
 
-// 部署请求的数据结构
+impl PackageRequest {
+    // 合并单文件字段与多文件字段，得到最终写入包目录的文件集合
+    fn package_files(&self) -> Result<BTreeMap<String, String>> {
+        let mut files = self.files.clone().unwrap_or_default();
+        if let Some(source_code) = &self.source_code {
+            let file_name = self.file_name.clone().unwrap_or_else(|| "main.move".to_string());
+            files.insert(package::source_file_path(&file_name), source_code.clone());
+        }
+        if files.is_empty() {
+            return Err(anyhow!("请求中没有源代码，请提供source_code或files"));
+        }
+        Ok(files)
+    }
+
+    // 解析请求指定的框架版本
+    fn framework(&self) -> Result<Option<Framework>> {
+        self.framework.as_deref()
+            .filter(|f| !f.trim().is_empty())
+            .map(str::parse)
+            .transpose()
+    }
+}
+
+// 编译请求的数据结构
 #[derive(Debug, Deserialize)]
-struct DeployRequest {
-    command: String,
+struct CompileRequest {
+    #[serde(flatten)]
+    package: PackageRequest,
+}
+
+// 准备包目录时的错误
+enum PrepareError {
+    BadRequest(String),
+    Internal(String),
+}
+
+// 根据请求创建临时包目录，返回包路径和实际使用的框架
+fn prepare_package(req: &PackageRequest) -> Result<(PathBuf, ResolvedFramework), PrepareError> {
+    let files = req.package_files().map_err(|e| PrepareError::BadRequest(e.to_string()))?;
+
+    // 解析请求指定的框架版本，优先使用本地缓存
+    let requested_framework = req.framework().map_err(|e| PrepareError::BadRequest(e.to_string()))?;
+    let framework = FrameworkConfig::from_env()
+        .resolve_or_default(requested_framework.as_ref())
+        .map_err(|e| PrepareError::Internal(format!("解析框架依赖失败: {}", e)))?;
+
+    // 创建临时包目录，传递地址配置
+    let package_path = create_temp_package_dir(&files, req.addresses_toml_content.as_deref(), &framework)
+        .map_err(|e| PrepareError::Internal(format!("创建临时文件失败: {}", e)))?;
+
+    Ok((package_path, framework))
 }
 
 // 部署响应的数据结构
-#[derive(Debug, Serialize)]
+#[derive(Debug, Default, Serialize)]
 struct DeployResponse {
     success: bool,
-    package_id: Option<String>,
+    #[serde(flatten)]
+    summary: Option<TransactionSummary>, // 从Sui CLI的JSON输出解析的交易结果：包ID、UpgradeCap、对象变更、gas等
     output: Option<String>,
     error: Option<String>,
+    error_code: Option<ErrorCode>, // 操作被拒绝时的错误码
 }
 
-// 编译结果的数据结构
-#[derive(Debug, Serialize)]
+// 构建调度失败时的响应：队列已满返回429，超时返回503，均附带Retry-After
+fn schedule_error_response<T: Serialize>(error: &ScheduleError, body: T) -> HttpResponse {
+    let mut response = match error {
+        ScheduleError::Busy { .. } => HttpResponse::TooManyRequests(),
+        ScheduleError::TimedOut { .. } => HttpResponse::ServiceUnavailable(),
+        ScheduleError::Failed(_) => HttpResponse::InternalServerError(),
+    };
+    if let Some(retry_after) = error.retry_after() {
+        response.insert_header((actix_web::http::header::RETRY_AFTER, retry_after.as_secs().to_string()));
+    }
+    response.json(body)
+}
+
+// 操作被拒绝时的HTTP状态
+fn operation_error_status(error: &OperationError) -> actix_web::http::StatusCode {
+    match error.code {
+        ErrorCode::OperationNotAllowed => actix_web::http::StatusCode::FORBIDDEN,
+        ErrorCode::RpcError => actix_web::http::StatusCode::BAD_GATEWAY,
+        ErrorCode::IncompatibleUpgrade => actix_web::http::StatusCode::CONFLICT,
+        ErrorCode::SimulationError => actix_web::http::StatusCode::INTERNAL_SERVER_ERROR,
+        ErrorCode::SessionNotFound => actix_web::http::StatusCode::NOT_FOUND,
+        _ => actix_web::http::StatusCode::BAD_REQUEST,
+    }
+}
+
+// 编译结果的数据结构，同时作为编译缓存的条目
+#[derive(Debug, Serialize, Deserialize)]
 struct CompileResponse {
     success: bool,
     bytecode_base64: Vec<String>,
@@ -52,20 +155,85 @@ struct CompileResponse {
     error_message: Option<String>,
     warnings: Vec<String>,
     bytecode_path: Option<String>, // 新增字段：字节码保存路径
+    modules: Vec<CompiledModuleInfo>, // 根包中每个模块的字节码信息，按发布顺序排列
+    #[serde(default)]
+    dependencies: Vec<String>, // 依赖的已发布包ID，构建发布交易时使用
+    #[serde(default)]
+    abi: Vec<ModuleAbi>, // 根包中每个模块的接口描述
+    diagnostics: Vec<Diagnostic>,  // 结构化的编译器诊断（错误和警告）
+    framework: Option<ResolvedFramework>, // 实际使用的Sui框架版本
+    #[serde(default)]
+    cache_hit: bool, // 结果是否来自编译缓存
+}
+
+// 编译缓存中服务端编译结果的命名空间，与库接口的缓存条目区分开
+const COMPILE_CACHE_NAMESPACE: &str = "api-compile-response-v4";
+
+impl CompileResponse {
+    // 构造编译失败的响应
+    fn failure(error_message: String) -> Self {
+        CompileResponse {
+            success: false,
+            bytecode_base64: vec![],
+            module_names: vec![],
+            bytecode_size: vec![],
+            compile_time_ms: 0,
+            error_message: Some(error_message),
+            warnings: vec![],
+            bytecode_path: None,
+            modules: vec![],
+            dependencies: vec![],
+            abi: vec![],
+            diagnostics: vec![],
+            framework: None,
+            cache_hit: false,
+        }
+    }
+}
+
+// compile_move_code的输出
+struct CompileOutput {
+    bytecode_base64: Vec<String>,
+    module_names: Vec<String>,
+    bytecode_size: Vec<usize>,
+    compile_time_ms: u64,
+    warnings: Vec<String>,
+    package_path: String,
+    modules: Vec<CompiledModuleInfo>,
+    dependencies: Vec<String>,
+    abi: Vec<ModuleAbi>,
+    diagnostics: Vec<Diagnostic>,
 }
 
 // 测试请求的数据结构
 #[derive(Debug, Deserialize)]
 struct TestRequest {
-    command: String,
+    command: Option<String>, // 旧接口：sui move test命令，只接受测试命令
+    package_path: Option<String>, // 测试编译接口返回的包目录，而不是上传源码
+    #[serde(flatten)]
+    package: PackageRequest,
+    filter: Option<String>,  // 只运行名称包含该字符串的测试
+    gas_limit: Option<u64>,  // 单个测试允许执行的最大指令数
+    #[serde(flatten)]
+    profile: GasProfileOptions, // 按函数分析每个测试的gas，可选输出折叠调用栈
+    #[serde(default)]
+    coverage: bool, // 统计根包源文件的行覆盖率
 }
 
 // 测试响应的数据结构
-#[derive(Debug, Serialize)]
+#[derive(Debug, Default, Serialize)]
 struct TestResponse {
     success: bool,
     output: Option<String>,
     error: Option<String>,
+    tests: Vec<TestCaseResult>,    // 每个测试的结果
+    passed: usize,
+    failed: usize,
+    debug_output: Vec<String>,     // 测试中debug::print的输出
+    diagnostics: Vec<Diagnostic>,  // 以测试模式编译失败时的诊断
+    framework: Option<ResolvedFramework>,
+    error_code: Option<ErrorCode>, // 操作被拒绝时的错误码
+    coverage: Option<CoverageReport>, // 行覆盖率和lcov报告，请求coverage时才有
 }
 
 // 日志记录函数
@@ -74,38 +242,27 @@ fn log(message: &str) {
     println!("[{}] {}", now, message);
 }
 
-// 创建临时源文件
-async fn create_temp_source_file(source_code: &str, file_name: &str, addresses_toml_content: Option<&str>) -> Result<PathBuf> {
-    // 创建唯一的临时目录，添加时间戳避免冲突
-    let timestamp = Local::now().format("%Y%m%d%H%M%S").to_string();
-    let temp_dir = std::env::temp_dir().join(format!("move-web-compiler-{}", timestamp));
-    
-    if !temp_dir.exists() {
-        std_fs::create_dir_all(&temp_dir)?;
-    }
+// 创建临时包目录，写入全部源文件和Move.toml
+fn create_temp_package_dir(files: &BTreeMap<String, String>, addresses_toml_content: Option<&str>, framework: &ResolvedFramework) -> Result<PathBuf> {
+    // 每个请求使用随机命名的独立工作区，过期后由后台任务清理
+    let temp_dir = WorkspaceConfig::from_env().create()?;
 
     println!("创建临时编译目录: {:?}", temp_dir);
     
-    // 创建源文件目录结构
-    let sources_dir = temp_dir.join("sources");
-    if !sources_dir.exists() {
-        std_fs::create_dir_all(&sources_dir)?;
+    // 按相对路径保存全部源文件（sources/、tests/、examples/）
+    for source_path in package::write_package_files(&temp_dir, files)? {
+        println!("创建源文件: {:?}", temp_dir.join(source_path));
     }
-    
-    // 保存源文件到sources目录
-    let source_path = sources_dir.join(file_name);
-    std_fs::write(&source_path, source_code)?;
-    println!("创建源文件: {:?}", source_path);
 
-    // 创建Move.toml文件
+    // 创建Move.toml文件，框架依赖优先指向本地缓存
     let mut move_toml_content = String::from(r#"[package]
 name = "MoveWebCompile"
 version = "0.0.1"
 edition = "2024.beta"
 
-[dependencies]
-Sui = { git = "https://github.com/MystenLabs/sui.git", subdir = "crates/sui-framework/packages/sui-framework", rev = "framework/testnet" }
 "#);
+    move_toml_content.push_str(&framework.dependencies_toml(framework::references_deepbook(files.values()))?);
+    println!("框架版本: {} ({})", framework.revision, if framework.is_local() { "本地缓存" } else { "git" });
 
     // 使用前端传递的地址内容，或者使用默认内容
     if let Some(addresses_content) = addresses_toml_content {
@@ -136,8 +293,25 @@ hello_world = "0x0"
     Ok(temp_dir)
 }
 
+// 将字节码保存在包目录下独立的bytecode目录，原始源码保持不变
+fn save_bytecode(package_path: &Path, modules: &[CompiledModuleInfo]) -> Result<PathBuf> {
+    let bytecode_dir = package_path.join("bytecode");
+    if !bytecode_dir.exists() {
+        std_fs::create_dir_all(&bytecode_dir)?;
+    }
+
+    for module in modules {
+        let bytecode_path = bytecode_dir.join(format!("{}.mv", module.name));
+        match std_fs::write(&bytecode_path, module.bytes()?) {
+            Ok(_) => println!("成功保存字节码到: {:?}", bytecode_path),
+            Err(e) => println!("保存字节码文件失败: {:?}", e)
+        }
+    }
+    Ok(bytecode_dir)
+}
+
 // 编译Move源代码
-async fn compile_move_code(package_path: &PathBuf) -> Result<(Vec<String>, Vec<String>, Vec<usize>, u64, Vec<String>, String)> {
+fn compile_move_code(package_path: &Path) -> Result<CompileOutput, BuildFailure> {
     let start_time = std::time::Instant::now();
     
     println!("开始编译包: {:?}", package_path);
@@ -145,97 +319,44 @@ async fn compile_move_code(package_path: &PathBuf) -> Result<(Vec<String>, Vec<S
     // 创建Sui编译配置
     let sui_build_config = sui_move_build::BuildConfig::new_for_testing();
 
-    // 编译包
-    let compiled_package = sui_build_config.build(package_path)
-        .map_err(|e| anyhow!("编译失败: {:?}", e))?;
+    // 编译包，同时收集结构化诊断
+    let compiler::BuildOutput { package: compiled_package, diagnostics } =
+        compiler::build_package(sui_build_config, package_path)?;
         
     println!("编译成功，保存字节码...");
 
-    // 收集编译结果信息
-    let mut module_names = Vec::new();
-    let mut bytecode_size = Vec::new();
-    let mut warnings = Vec::new();
-    let mut bytecode_base64 = Vec::new();
-    
-    // 获取完整的模块列表
-    let mut all_modules = Vec::new();
-
-    // 确保编译后的字节码保存在一个独立目录，同时原始源码保持不变
-    let bytecode_dir = package_path.join("bytecode");
-    if !bytecode_dir.exists() {
-        std_fs::create_dir_all(&bytecode_dir)?;
-    }
-    
-    // 获取编译后的模块信息
-    for unit in compiled_package.package.all_compiled_units() {
-        // 获取模块名称
-        let module_name = unit.name.to_string();
-        all_modules.push(module_name.clone());
-        
-        // 获取字节码大小 - 由于无法直接调用serialize，使用估算方法
-        let module = &unit.module;
-        // 简单估算字节码大小 - 这不是精确的，但足够展示
-        // 一个简单估计是根据模块结构的复杂度
-        let estimated_size = 
-            module.function_defs().len() * 8 + 
-            module.struct_defs().len() * 16 +
-            module.signatures().len() * 4 +
-            module.identifiers().len() * 12;
-        
-        // 筛选用户自定义模块 - 检查源码中特殊标识，如用户提供的hello模块
-        if module_name.contains("hello") || module_name.starts_with("examples::") {
-            println!("找到用户模块: {}", module_name);
-            module_names.push(module_name.clone());
-            bytecode_size.push(estimated_size);
-        }
-    }
+    let warnings: Vec<String> = diagnostics.iter()
+        .filter(|d| d.severity == Severity::Warning)
+        .map(Diagnostic::summary)
+        .collect();
 
-    // 打印所有模块名称以便调试
+    // 打印所有模块名称以便调试，其中包含依赖包的模块
+    let all_modules: Vec<String> = compiled_package.package.all_compiled_units()
+        .map(|unit| unit.name.to_string())
+        .collect();
     println!("所有模块: {:?}", all_modules);
-    
-    // 尝试获取用户模块字节码
-    let all_bytecode = compiled_package.get_package_base64(true);
-    println!("初始字节码数量: {}", all_bytecode.len());
-    
-    // 将Base64字节码保存到磁盘
-    for (idx, bytecode) in all_bytecode.iter().enumerate() {
-        // 收集Base64编码的字节码
-        bytecode_base64.push(bytecode.encoded().to_string());
-        
-        // 模块名称，如果在module_names中有对应的，则使用它，否则用索引
-        let module_name = if idx < module_names.len() {
-            module_names[idx].clone()
-        } else {
-            let default_name = format!("Module_{}", idx + 1);
-            // 如果模块名未添加过，添加到列表中
-            if !module_names.contains(&default_name) {
-                module_names.push(default_name.clone());
-                bytecode_size.push(1024); // 默认大小
-            }
-            default_name
-        };
-        
-        // 从Base64解码字节码
-        match base64::decode(bytecode.encoded()) {
-            Ok(decoded) => {
-                let bytecode_path = bytecode_dir.join(format!("{}.mv", module_name));
-                match std_fs::write(&bytecode_path, &decoded) {
-                    Ok(_) => println!("成功保存字节码到: {:?}", bytecode_path),
-                    Err(e) => println!("保存字节码文件失败: {:?}", e)
-                }
-            },
-            Err(e) => println!("无法解码字节码 {}: {:?}", module_name, e)
-        }
-    }
+
+    // 序列化根包中的每个模块，得到真实的字节码大小和摘要
+    let modules = compiler::root_modules(&compiled_package, package_path)?;
+    let dependencies = compiler::dependency_ids(&compiled_package);
+    let abi = abi::package_abi(&compiled_package);
+
+    // 将字节码保存到磁盘
+    let bytecode_dir = save_bytecode(package_path, &modules)?;
+
+    // 兼容旧字段：与modules一一对应
+    let bytecode_base64: Vec<String> = modules.iter().map(|m| m.base64.clone()).collect();
+    let module_names: Vec<String> = modules.iter().map(|m| m.name.clone()).collect();
+    let bytecode_size: Vec<usize> = modules.iter().map(|m| m.size).collect();
 
     let compile_time_ms = start_time.elapsed().as_millis() as u64;
 
     // 打印调试信息
     println!("生成了 {} 个字节码模块", bytecode_base64.len());
     if !module_names.is_empty() {
-        println!("用户模块名称: {:?}", module_names);
+        println!("根包模块名称: {:?}", module_names);
     } else {
-        println!("警告：没有找到任何用户模块");
+        println!("警告：根包中没有任何模块");
     }
     
     // 字节码存储位置
@@ -247,123 +368,219 @@ async fn compile_move_code(package_path: &PathBuf) -> Result<(Vec<String>, Vec<S
     println!("发布到测试网: sui client publish --path {} --gas-budget 100000000 --testnet", package_path.display());
     println!("===================");
 
-    Ok((bytecode_base64, module_names, bytecode_size, compile_time_ms, warnings, package_path.display().to_string()))
+    Ok(CompileOutput {
+        bytecode_base64,
+        module_names,
+        bytecode_size,
+        compile_time_ms,
+        warnings,
+        package_path: package_path.display().to_string(),
+        modules,
+        dependencies,
+        abi,
+        diagnostics,
+    })
 }
 
 // 处理编译请求的API端点
-async fn compile_handler(req: web::Json<CompileRequest>) -> impl Responder {
-    log(&format!("收到编译请求: {:?}, 地址配置: {:?}", req.file_name, req.addresses_toml_content.is_some()));
-    
-    let file_name = req.file_name.clone().unwrap_or_else(|| "main.move".to_string());
-    
-    // 创建临时文件，传递地址配置
-    let package_path = match create_temp_source_file(&req.source_code, &file_name, req.addresses_toml_content.as_deref()).await {
-        Ok(path) => path,
+async fn compile_handler(req: web::Json<CompileRequest>, scheduler: web::Data<BuildScheduler>) -> impl Responder {
+    log(&format!("收到编译请求: {:?}, 文件数: {}, 地址配置: {:?}, 框架: {:?}",
+        req.package.file_name,
+        req.package.files.as_ref().map_or(0, |files| files.len()),
+        req.package.addresses_toml_content.is_some(),
+        req.package.framework));
+
+    // 解析框架、写入文件、计算缓存键和编译都在阻塞线程池中排队执行
+    let package = req.into_inner().package;
+    let result = scheduler.run(move || -> Result<CompileResponse, PrepareError> {
+        let (package_path, framework) = prepare_package(&package)?;
+        Ok(compile_or_load_cached(&package_path, framework))
+    }).await;
+
+    match result {
         Err(e) => {
-            return HttpResponse::InternalServerError().json(CompileResponse {
-                success: false,
-                bytecode_base64: vec![],
-                module_names: vec![],
-                bytecode_size: vec![],
-                compile_time_ms: 0,
-                error_message: Some(format!("创建临时文件失败: {}", e)),
-                warnings: vec![],
-                bytecode_path: None,
-            });
+            log(&format!("编译调度失败: {}", e));
+            schedule_error_response(&e, CompileResponse::failure(e.to_string()))
+        }
+        Ok(Ok(response)) => HttpResponse::Ok().json(response),
+        Ok(Err(PrepareError::BadRequest(message))) => {
+            HttpResponse::BadRequest().json(CompileResponse::failure(message))
+        }
+        Ok(Err(PrepareError::Internal(message))) => {
+            HttpResponse::InternalServerError().json(CompileResponse::failure(message))
+        }
+    }
+}
+
+// 编译准备好的包目录，相同的源码、Move.toml、框架版本和编译选项直接返回缓存的结果
+fn compile_or_load_cached(package_path: &Path, framework: ResolvedFramework) -> CompileResponse {
+    let start_time = std::time::Instant::now();
+    let cache = CompileCache::from_env();
+    let cache_key = match cache.key(package_path, &framework, &format!("{};{}", COMPILE_CACHE_NAMESPACE, CompileOptions::default().cache_fingerprint())) {
+        Ok(key) => key,
+        Err(e) => {
+            log(&format!("计算编译缓存键失败: {}", e));
+            None
         }
     };
-    
-    // 编译代码
-    match compile_move_code(&package_path).await {
-        Ok((bytecode, module_names, bytecode_size, compile_time_ms, warnings, package_path_str)) => {
+    if let Some(cached) = cache_key.as_deref().and_then(|key| cache.get::<CompileResponse>(key)) {
+        if let Err(e) = save_bytecode(package_path, &cached.modules) {
+            log(&format!("保存缓存的字节码失败: {}", e));
+        }
+        log(&format!("命中编译缓存: {}", cache_key.as_deref().unwrap_or_default()));
+        return CompileResponse {
+            compile_time_ms: start_time.elapsed().as_millis() as u64,
+            bytecode_path: cached.success.then(|| package_path.display().to_string()),
+            framework: Some(framework),
+            cache_hit: true,
+            ..cached
+        };
+    }
+
+    let response = match compile_move_code(package_path) {
+        Ok(output) => {
             log("编译成功");
-            HttpResponse::Ok().json(CompileResponse {
+            CompileResponse {
                 success: true,
-                bytecode_base64: bytecode,
-                module_names,
-                bytecode_size,
-                compile_time_ms,
+                bytecode_base64: output.bytecode_base64,
+                module_names: output.module_names,
+                bytecode_size: output.bytecode_size,
+                compile_time_ms: output.compile_time_ms,
                 error_message: None,
-                warnings,
-                bytecode_path: Some(package_path_str),
-            })
+                warnings: output.warnings,
+                bytecode_path: Some(output.package_path),
+                modules: output.modules,
+                dependencies: output.dependencies,
+                abi: output.abi,
+                diagnostics: output.diagnostics,
+                framework: Some(framework),
+                cache_hit: false,
+            }
         },
         Err(e) => {
             log(&format!("编译失败: {}", e));
-            HttpResponse::Ok().json(CompileResponse {
-                success: false,
-                bytecode_base64: vec![],
-                module_names: vec![],
-                bytecode_size: vec![],
-                compile_time_ms: 0,
-                error_message: Some(format!("编译错误: {}", e)),
-                warnings: vec![],
-                bytecode_path: None,
-            })
+            // 错误信息附带codespan风格的诊断报告，结构化诊断供编辑器标注使用
+            let report = diagnostics::render(&e.diagnostics, package_path);
+            let error_message = if report.is_empty() {
+                format!("编译错误: {}", e)
+            } else {
+                format!("编译错误: {}\n{}", e, report)
+            };
+            CompileResponse {
+                diagnostics: e.diagnostics,
+                framework: Some(framework),
+                ..CompileResponse::failure(error_message)
+            }
+        }
+    };
+
+    // 缓存成功的结果和带诊断的编译错误，依赖解析等偶发错误不缓存
+    if let Some(key) = &cache_key {
+        if response.success || !response.diagnostics.is_empty() {
+            if let Err(e) = cache.put(key, &response) {
+                log(&format!("写入编译缓存失败: {}", e));
+            }
         }
     }
+    response
 }
 
-// 处理部署请求的API端点
-async fn deploy_handler(req: web::Json<DeployRequest>) -> impl Responder {
-    log(&format!("收到部署请求: {}", req.command));
-    
-    // 解析命令
-    let parts: Vec<&str> = req.command.split_whitespace().collect();
-    if parts.len() < 2 || parts[0] != "sui" {
-        return HttpResponse::BadRequest().json(DeployResponse {
-            success: false,
-            package_id: None,
-            output: None,
-            error: Some("无效的命令格式，必须是sui开头的命令".to_string()),
-        });
-    }
-    
-    // 执行命令
-    let output = Command::new("sui")
-        .args(&parts[1..])
-        .output();
+// 处理部署请求的API端点，只执行允许列表中的发布和调用操作
+async fn deploy_handler(req: web::Json<serde_json::Value>, scheduler: web::Data<BuildScheduler>) -> impl Responder {
+    let operation = match Operation::from_request(req.into_inner()) {
+        Ok(Operation::Test(_)) => Err(OperationError::new(
+            ErrorCode::OperationNotAllowed,
+            "部署接口不执行测试，请使用 /api/test",
+        )),
+        Ok(operation) => Ok(operation),
+        Err(e) => Err(e),
+    };
+    let sui_args = match operation.and_then(|operation| {
+        log(&format!("收到部署请求: {:?}", operation));
+        operation.sui_args()
+    }) {
+        Ok(sui_args) => sui_args,
+        Err(e) => {
+            log(&format!("拒绝部署请求: {}", e));
+            return HttpResponse::build(operation_error_status(&e)).json(DeployResponse {
+                error: Some(e.message),
+                error_code: Some(e.code),
+                ..DeployResponse::default()
+            });
+        }
+    };
     
-    match output {
-        Ok(output) => {
+    // 发布会重新编译包并等待网络确认，与构建一样在调度器中执行，超过截止时间时终止命令
+    let deadline = std::time::Instant::now() + scheduler.config().build_timeout;
+    let result = scheduler
+        .run(move || -> Result<std::process::Output> {
+            // 参数逐个传递，不经过shell
+            let child = Command::new("sui")
+                .args(&sui_args)
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()?;
+            scheduler::wait_with_deadline(child, Some(deadline))
+        })
+        .await;
+
+    match result {
+        Err(e) => {
+            log(&format!("部署命令调度失败: {}", e));
+            schedule_error_response(&e, DeployResponse {
+                error: Some(e.to_string()),
+                ..DeployResponse::default()
+            })
+        },
+        Ok(Ok(output)) => {
             let stdout = String::from_utf8_lossy(&output.stdout).to_string();
             let stderr = String::from_utf8_lossy(&output.stderr).to_string();
             
             log(&format!("部署命令执行结果: {}", if output.status.success() { "成功" } else { "失败" }));
             
-            // 尝试从输出中解析包ID
-            let mut package_id = None;
-            for line in stdout.lines() {
-                if line.contains("包ID") || line.contains("Package ID") {
-                    // 从行中提取16进制字符串
-                    if let Some(id) = line.split_whitespace().find(|s| s.starts_with("0x")) {
-                        package_id = Some(id.to_string());
-                        break;
+            // 解析--json输出的交易响应，命令失败时可能没有JSON输出
+            let summary = match TransactionSummary::from_cli_output(&stdout) {
+                Ok(summary) => Some(summary),
+                Err(e) => {
+                    if output.status.success() {
+                        log(&format!("无法解析部署命令的JSON输出: {}", e));
                     }
+                    None
                 }
-            }
-            
+            };
+
+            let executed = output.status.success() && summary.as_ref().is_some_and(TransactionSummary::is_success);
+            let error = if executed {
+                None
+            } else if let Some(execution_error) = summary.as_ref().and_then(|s| s.execution_error.clone()) {
+                Some(execution_error)
+            } else if summary.is_none() && output.status.success() {
+                Some("无法解析Sui CLI的JSON输出".to_string())
+            } else {
+                Some(stderr.clone())
+            };
+
             HttpResponse::Ok().json(DeployResponse {
-                success: output.status.success(),
-                package_id,
+                success: executed,
+                summary,
                 output: Some(format!("stdout: {}\nstderr: {}", stdout, stderr)),
-                error: if output.status.success() { None } else { Some(stderr) },
+                error,
+                error_code: None,
             })
         },
-        Err(e) => {
+        Ok(Err(e)) => {
             log(&format!("执行部署命令失败: {}", e));
             HttpResponse::InternalServerError().json(DeployResponse {
-                success: false,
-                package_id: None,
-                output: None,
                 error: Some(format!("执行部署命令失败: {}", e)),
+                ..DeployResponse::default()
             })
         }
     }
 }
 
-// 处理单文件编译的命令行功能
-async fn compile_single_file(source_path: PathBuf, verbose: bool) -> Result<()> {
+// 处理单文件编译的命令行功能，source_path也可以是包含sources/等目录的包目录
+async fn compile_single_file(source_path: PathBuf, framework: Option<Framework>, verbose: bool) -> Result<()> {
     if !source_path.exists() {
         return Err(anyhow!("源文件不存在"));
     }
@@ -372,35 +589,51 @@ async fn compile_single_file(source_path: PathBuf, verbose: bool) -> Result<()>
         println!("读取源文件: {:?}", source_path);
     }
 
-    // 读取源文件内容
-    let mut file = File::open(&source_path).await?;
-    let mut source_code = String::new();
-    file.read_to_string(&mut source_code).await?;
+    let files = if source_path.is_dir() {
+        // 读取包目录下的全部源文件
+        package::collect_package_files(&source_path)?
+    } else {
+        // 读取源文件内容
+        let mut file = File::open(&source_path).await?;
+        let mut source_code = String::new();
+        file.read_to_string(&mut source_code).await?;
 
-    let file_name = source_path.file_name()
-        .ok_or_else(|| anyhow!("无效的文件路径"))?
-        .to_string_lossy()
-        .to_string();
+        let file_name = source_path.file_name()
+            .ok_or_else(|| anyhow!("无效的文件路径"))?
+            .to_string_lossy()
+            .to_string();
+
+        let mut files = BTreeMap::new();
+        files.insert(package::source_file_path(&file_name), source_code);
+        files
+    };
 
     // 创建临时目录并写入源文件
-    let package_path = create_temp_source_file(&source_code, &file_name, None).await?;
+    let framework = FrameworkConfig::from_env().resolve_or_default(framework.as_ref())?;
+    let package_path = create_temp_package_dir(&files, None, &framework)?;
 
     if verbose {
         println!("创建临时包目录: {:?}", package_path);
     }
 
-    // 编译代码
-    let (bytecode, module_names, bytecode_size, compile_time_ms, warnings, _) = compile_move_code(&package_path).await?;
+    // 编译代码，失败时打印codespan风格的诊断报告
+    let CompileOutput { bytecode_base64: bytecode, module_names, bytecode_size, compile_time_ms, warnings, modules, diagnostics: compile_diagnostics, .. } =
+        match compile_move_code(&package_path) {
+            Ok(output) => output,
+            Err(e) => {
+                eprintln!("{}", diagnostics::render(&e.diagnostics, &package_path));
+                return Err(anyhow!(e));
+            }
+        };
 
     if verbose {
         println!("编译成功，生成 {} 个模块", bytecode.len());
         println!("编译耗时: {}ms", compile_time_ms);
+        println!("框架版本: {}{}", framework.revision, framework.commit.as_ref().map(|c| format!(" ({})", c)).unwrap_or_default());
         
         if !warnings.is_empty() {
             println!("\n警告:");
-            for warning in &warnings {
-                println!("- {}", warning);
-            }
+            println!("{}", diagnostics::render(&compile_diagnostics, &package_path));
         }
     }
 
@@ -411,6 +644,13 @@ async fn compile_single_file(source_path: PathBuf, verbose: bool) -> Result<()>
             println!("{}", code);
         }
     }
+
+    if verbose {
+        println!("\n根包模块:");
+        for module in &modules {
+            println!("- {}::{} ({}, {}字节, digest {})", module.address, module.name, module.source_file, module.size, module.digest);
+        }
+    }
     
     // 打印源码与编译后的代码包的存储路径
     let sources_dir = package_path.join("sources");
@@ -433,121 +673,1245 @@ async fn compile_single_file(source_path: PathBuf, verbose: bool) -> Result<()>
     Ok(())
 }
 
+// 运行包的单元测试，超过`deadline`时终止测试进程
+//
+// 包由测试运行器编译，只有统计覆盖率时才需要先以测试模式编译一遍，用来换算源码行。
+fn run_package_tests(package_path: &Path, options: &UnitTestOptions, deadline: Option<std::time::Instant>) -> Result<UnitTestReport, BuildFailure> {
+    let package = match options.coverage {
+        true => Some(build_test_package(package_path)?),
+        false => None,
+    };
+    match unit_test::run_unit_tests(package.as_ref(), package_path, options, deadline) {
+        Ok(report) => Ok(report),
+        // 测试运行器遇到编译错误时直接退出，重新编译一次返回结构化诊断
+        Err(e) if package.is_none() && e.is::<unit_test::WorkerExited>() => {
+            build_test_package(package_path)?;
+            Err(e.into())
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+// 以测试模式编译包，编译失败时返回结构化诊断
+fn build_test_package(package_path: &Path) -> Result<sui_move_build::CompiledPackage, BuildFailure> {
+    let mut build_config = sui_move_build::BuildConfig::new_for_testing();
+    build_config.config.test_mode = true;
+    Ok(compiler::build_package(build_config, package_path)?.package)
+}
+
 // 处理测试请求的API端点
-async fn test_handler(req: web::Json<TestRequest>) -> impl Responder {
-    log(&format!("收到测试请求: {}", req.command));
-    
-    // 解析命令
-    let parts: Vec<&str> = req.command.split_whitespace().collect();
-    if parts.len() < 2 || parts[0] != "sui" {
-        return HttpResponse::BadRequest().json(TestResponse {
-            success: false,
-            output: None,
-            error: Some("无效的命令格式，必须是sui开头的命令".to_string()),
+async fn test_handler(req: web::Json<TestRequest>, scheduler: web::Data<BuildScheduler>) -> impl Responder {
+    let mut options = UnitTestOptions {
+        filter: req.filter.clone().filter(|f| !f.trim().is_empty()),
+        gas_limit: req.gas_limit.unwrap_or(unit_test::DEFAULT_TEST_GAS_LIMIT),
+        profile: req.profile,
+        coverage: req.coverage,
+    };
+    if let Err(e) = options.validate() {
+        log(&format!("拒绝测试请求: {}", e));
+        return HttpResponse::build(operation_error_status(&e)).json(TestResponse {
+            error: Some(e.message),
+            error_code: Some(e.code),
+            ..TestResponse::default()
         });
     }
-    
-    // 执行命令
-    let output = Command::new("sui")
-        .args(&parts[1..])
-        .output();
-    
-    match output {
-        Ok(output) => {
-            let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-            
-            log(&format!("测试命令执行结果: {}", if output.status.success() { "成功" } else { "失败" }));
-            
+
+    // 旧接口的命令只能解析为测试操作，测试已有的包目录
+    let existing_package = match (&req.command, &req.package_path) {
+        (Some(command), _) => match Operation::parse_command(command) {
+            Ok(Operation::Test(test)) => Ok(Some(test)),
+            Ok(operation) => Err(OperationError::new(
+                ErrorCode::OperationNotAllowed,
+                format!("测试接口不执行{}操作", operation.name()),
+            )),
+            Err(e) => Err(e),
+        },
+        (None, Some(package_path)) => Ok(Some(operations::TestOperation {
+            package_path: package_path.clone(),
+            filter: options.filter.clone(),
+        })),
+        (None, None) => Ok(None),
+    };
+    let existing_package = existing_package.and_then(|test| match test {
+        Some(test) => {
+            Operation::Test(test.clone()).validate()?;
+            Ok(Some((operations::validate_package_path(&test.package_path)?, test.filter)))
+        }
+        None => Ok(None),
+    });
+
+    let (package_path, framework) = match existing_package {
+        Ok(Some((package_path, filter))) => {
+            log(&format!("收到单元测试请求: 包目录: {:?}, 过滤条件: {:?}", package_path, filter));
+            options.filter = filter.or(options.filter);
+            (package_path, None)
+        }
+        Ok(None) => {
+            log(&format!("收到单元测试请求: 文件数: {}, 过滤条件: {:?}, 框架: {:?}",
+                req.package.files.as_ref().map_or(0, |files| files.len()) + req.package.source_code.is_some() as usize,
+                req.filter,
+                req.package.framework));
+
+            match prepare_package(&req.package) {
+                Ok((package_path, framework)) => (package_path, Some(framework)),
+                Err(PrepareError::BadRequest(message)) => {
+                    return HttpResponse::BadRequest().json(TestResponse { error: Some(message), ..TestResponse::default() });
+                }
+                Err(PrepareError::Internal(message)) => {
+                    return HttpResponse::InternalServerError().json(TestResponse { error: Some(message), ..TestResponse::default() });
+                }
+            }
+        }
+        Err(e) => {
+            log(&format!("拒绝测试请求: {}", e));
+            return HttpResponse::build(operation_error_status(&e)).json(TestResponse {
+                error: Some(e.message),
+                error_code: Some(e.code),
+                ..TestResponse::default()
+            });
+        }
+    };
+
+    // 与调度器相同的截止时间，超时后终止测试进程，释放构建名额
+    let deadline = std::time::Instant::now() + scheduler.config().build_timeout;
+    let test_package_path = package_path.clone();
+    let result = scheduler.run(move || run_package_tests(&test_package_path, &options, Some(deadline))).await;
+
+    match result {
+        Err(e) => {
+            log(&format!("单元测试调度失败: {}", e));
+            schedule_error_response(&e, TestResponse {
+                error: Some(e.to_string()),
+                ..TestResponse::default()
+            })
+        },
+        Ok(Ok(report)) => {
+            log(&format!("单元测试完成: 通过 {}, 未通过 {}", report.passed, report.failed));
+            HttpResponse::Ok().json(TestResponse {
+                success: report.success,
+                output: Some(report.raw_output),
+                error: None,
+                tests: report.tests,
+                passed: report.passed,
+                failed: report.failed,
+                debug_output: report.debug_output,
+                diagnostics: vec![],
+                framework,
+                error_code: None,
+                coverage: report.coverage,
+            })
+        },
+        Ok(Err(e)) => {
+            log(&format!("单元测试失败: {}", e));
             HttpResponse::Ok().json(TestResponse {
-                success: output.status.success(),
-                output: Some(format!("# 输出结果：\n{}\n\n# 错误/警告：\n{}", stdout, stderr)),
-                error: if output.status.success() { None } else { Some(stderr) },
+                output: Some(diagnostics::render(&e.diagnostics, &package_path)),
+                error: Some(format!("测试失败: {}", e)),
+                diagnostics: e.diagnostics,
+                framework,
+                ..TestResponse::default()
+            })
+        }
+    }
+}
+
+// 处理反汇编的命令行功能，输入可以是.mv文件、Base64编码的字节码或包目录
+fn disassemble_command(args: &DisassembleArgs) -> Result<()> {
+    let options = args.options();
+    let modules = if args.source_map.is_some() || args.source.is_some() {
+        vec![disassemble::disassemble_file(Path::new(&args.input), args.source_map.as_deref(), args.source.as_deref(), &options)?]
+    } else {
+        disassemble::disassemble_input(&args.input, &options)?
+    };
+
+    for module in modules {
+        println!("{}", module.disassembly);
+    }
+    Ok(())
+}
+
+// 处理format命令：格式化单个源文件，有语法错误时打印诊断且不修改文件
+fn format_command(args: &FormatArgs) -> Result<()> {
+    let source = std_fs::read_to_string(&args.path)?;
+    let file_name = move_web_compiler::get_file_name(&args.path)?;
+    let formatted = match format::format_source(&source, &file_name, &args.options()) {
+        Ok(formatted) => formatted,
+        Err(mut e) => {
+            // 诊断中的路径指向临时目录，换成命令行给出的文件
+            for diagnostic in &mut e.diagnostics {
+                for label in std::iter::once(&mut diagnostic.primary).chain(diagnostic.secondary.iter_mut()) {
+                    label.file = args.path.display().to_string();
+                }
+            }
+            eprintln!("{}", diagnostics::render(&e.diagnostics, Path::new("")));
+            return Err(anyhow!(e));
+        }
+    };
+
+    if !args.write {
+        print!("{}", formatted);
+    } else if formatted != source {
+        std_fs::write(&args.path, &formatted)?;
+        println!("已格式化: {}", args.path.display());
+    } else {
+        println!("无需修改: {}", args.path.display());
+    }
+    Ok(())
+}
+
+// 处理build命令：不使用Move.toml，按依赖目录和命名地址直接编译源文件
+fn build_command(targets: Vec<PathBuf>, dependency_dirs: Option<String>, address_maps: Option<String>, out_dir: PathBuf, verbose: bool) -> Result<()> {
+    let dependencies = dependency_dirs.map(CliOptions::parse_deps).unwrap_or_default();
+    let addresses = address_maps.map(CliOptions::parse_addresses).transpose()?.unwrap_or_default();
+    let root = std::env::current_dir()?;
+
+    if verbose {
+        println!("编译目标: {:?}", targets);
+        println!("依赖目录: {:?}", dependencies);
+        println!("命名地址: {:?}", addresses.iter().map(|(name, addr)| format!("{}={}", name, addr)).collect::<Vec<_>>());
+    }
+
+    let (modules, build_diagnostics) = match compiler::build_sources(&targets, &dependencies, &addresses, &root) {
+        Ok(output) => output,
+        Err(e) => {
+            eprintln!("{}", diagnostics::render(&e.diagnostics, &root));
+            return Err(anyhow!(e));
+        }
+    };
+    if !build_diagnostics.is_empty() {
+        println!("{}", diagnostics::render(&build_diagnostics, &root));
+    }
+
+    std_fs::create_dir_all(&out_dir)?;
+    for module in &modules {
+        let path = out_dir.join(format!("{}.mv", module.name));
+        std_fs::write(&path, module.bytes()?)?;
+        println!("{}::{} ({}字节) -> {}", module.address, module.name, module.size, path.display());
+    }
+    println!("编译成功，生成 {} 个模块", modules.len());
+    Ok(())
+}
+
+// 处理test命令：运行包的单元测试并打印每个测试的结果
+fn test_command(args: &TestArgs, verbose: bool) -> Result<()> {
+    let report = match run_package_tests(&args.path, &args.options(), None) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("{}", diagnostics::render(&e.diagnostics, &args.path));
+            return Err(anyhow!(e));
+        }
+    };
+
+    if verbose {
+        println!("{}", report.raw_output);
+    }
+    for line in &report.debug_output {
+        println!("[debug] {}", line);
+    }
+    for test in &report.tests {
+        print!("[ {:<7} ] {}", test.status.as_str().to_uppercase(), test.name);
+        if let Some(gas) = test.gas_used {
+            print!(" (gas {})", gas);
+        }
+        println!();
+        if let Some(message) = &test.message {
+            println!("          {}", message);
+        }
+        if let Some(profile) = &test.gas_profile {
+            print_gas_profile(profile, args.folded_out.as_deref(), &test.name)?;
+        }
+    }
+    println!("通过 {}, 未通过 {}", report.passed, report.failed);
+
+    if let Some(coverage) = &report.coverage {
+        print!("{}", coverage::render_summary(coverage));
+        if let Some(lcov) = &args.lcov {
+            std_fs::write(lcov, &coverage.lcov)?;
+            println!("lcov报告: {}", lcov.display());
+        }
+    }
+
+    if !report.success {
+        return Err(anyhow!("单元测试未全部通过"));
+    }
+    Ok(())
+}
+
+// 处理codegen命令：编译包并生成TypeScript绑定
+fn codegen_command(path: PathBuf, out_dir: PathBuf, package_id: Option<String>, verbose: bool) -> Result<()> {
+    let output = match compiler::build_package(sui_move_build::BuildConfig::new_for_testing(), &path) {
+        Ok(output) => output,
+        Err(e) => {
+            eprintln!("{}", diagnostics::render(&e.diagnostics, &path));
+            return Err(anyhow!(e));
+        }
+    };
+    if verbose && !output.diagnostics.is_empty() {
+        println!("{}", diagnostics::render(&output.diagnostics, &path));
+    }
+
+    let modules = abi::package_abi(&output.package);
+    let CodegenOutput { files, warnings } = codegen::generate_typescript(&modules, &CodegenOptions { package_id })?;
+    for warning in &warnings {
+        println!("[warning] {}", warning);
+    }
+
+    std_fs::create_dir_all(&out_dir)?;
+    for file in &files {
+        let file_path = out_dir.join(&file.path);
+        std_fs::write(&file_path, &file.content)?;
+        println!("{}", file_path.display());
+    }
+    println!("生成成功，共 {} 个模块", modules.len());
+    Ok(())
+}
+
+// 处理simulate命令：编译包，发布到本地模拟链后执行指定的函数
+fn simulate_command(args: &SimulateArgs, verbose: bool) -> Result<()> {
+    let move_call = args.call()?;
+    let output = match compiler::build_package(sui_move_build::BuildConfig::new_for_testing(), &args.path) {
+        Ok(output) => output,
+        Err(e) => {
+            eprintln!("{}", diagnostics::render(&e.diagnostics, &args.path));
+            return Err(anyhow!(e));
+        }
+    };
+    if verbose && !output.diagnostics.is_empty() {
+        println!("{}", diagnostics::render(&output.diagnostics, &args.path));
+    }
+    let dependencies = compiler::dependency_ids(&output.package)
+        .iter()
+        .map(|id| transaction::parse_object_id(id))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut simulator = LocalSimulator::new(transaction::parse_address(&args.sender)?, args.profile())?;
+    let publish = simulator.publish(output.package.get_package_bytes(false), dependencies, args.gas_budget)?;
+    print_simulation_report("发布", &publish);
+    if let Some(profile) = &publish.gas_profile {
+        print_gas_profile(profile, args.folded_out.as_deref(), "publish")?;
+    }
+    let package_id = match (publish.is_success(), publish.package_id.as_deref()) {
+        (true, Some(package_id)) => transaction::parse_object_id(package_id)?,
+        _ => return Err(anyhow!("发布失败")),
+    };
+
+    let report = simulator.call(package_id, &move_call, args.gas_budget)?;
+    print_simulation_report(&format!("调用 {}::{}", move_call.module, move_call.function), &report);
+    if let Some(profile) = &report.gas_profile {
+        print_gas_profile(profile, args.folded_out.as_deref(), &format!("{}::{}", move_call.module, move_call.function))?;
+    }
+    if !report.is_success() {
+        return Err(anyhow!("执行失败"));
+    }
+    Ok(())
+}
+
+// 打印按函数统计的gas，指定目录时把折叠调用栈写到`<name>.folded`
+fn print_gas_profile(profile: &GasProfile, folded_out: Option<&Path>, name: &str) -> Result<()> {
+    println!("gas分析: 合计 {}, 指令 {}, 原生调用 {}{}",
+        profile.total_gas,
+        profile.instructions,
+        profile.native_calls,
+        profile.storage_bytes.map(|bytes| format!(", 写入对象 {} 字节", bytes)).unwrap_or_default());
+    print!("{}", gas_profile::render_table(profile, 20));
+
+    if let (Some(dir), Some(stacks)) = (folded_out, &profile.folded_stacks) {
+        std_fs::create_dir_all(dir)?;
+        let file_path = dir.join(format!("{}.folded", name.replace("::", "-")));
+        std_fs::write(&file_path, stacks)?;
+        println!("折叠调用栈: {}", file_path.display());
+    }
+    Ok(())
+}
+
+// 打印本地执行结果
+fn print_simulation_report(title: &str, report: &SimulationReport) {
+    println!("== {} [{}] ==", title, report.status);
+    if let Some(error) = &report.execution_error {
+        println!("错误: {}", error);
+    }
+    if let Some(package_id) = &report.package_id {
+        println!("包ID: {}", package_id);
+    }
+    for value in &report.return_values {
+        let json = value.json.as_ref().map_or_else(|| value.bcs.clone(), ToString::to_string);
+        println!("返回值: {} = {}", value.type_, json);
+    }
+    for object in &report.created {
+        println!("创建: {} {}", object.object_id, object.object_type.as_deref().unwrap_or_default());
+    }
+    for object in &report.mutated {
+        println!("修改: {} {}", object.object_id, object.object_type.as_deref().unwrap_or_default());
+    }
+    for object_id in &report.deleted {
+        println!("删除: {}", object_id);
+    }
+    for event in &report.events {
+        println!("事件: {} {}", event.event_type, event.parsed_json);
+    }
+    println!("gas: 计算 {}, 存储 {}, 退款 {}, 合计 {}",
+        report.gas.computation_cost,
+        report.gas.storage_cost,
+        report.gas.storage_rebate,
+        report.gas.total);
+}
+
+// 反汇编请求的数据结构
+#[derive(Debug, Deserialize)]
+struct DisassembleRequest {
+    modules: Option<Vec<String>>, // Base64编码的模块字节码，例如编译接口返回的bytecode_base64
+    package_path: Option<String>, // 编译接口返回的包目录，重新编译以获得源码映射
+    #[serde(flatten)]
+    options: DisassembleOptions,
+}
+
+// 反汇编响应的数据结构
+#[derive(Debug, Default, Serialize)]
+struct DisassembleResponse {
+    success: bool,
+    modules: Vec<DisassembledModule>,
+    error: Option<String>,
+    error_code: Option<ErrorCode>, // 包路径无效时的错误码
+}
+
+// 处理反汇编请求的API端点
+async fn disassemble_handler(req: web::Json<DisassembleRequest>, scheduler: web::Data<BuildScheduler>) -> impl Responder {
+    let req = req.into_inner();
+    log(&format!("收到反汇编请求: 模块数: {}, 包目录: {:?}",
+        req.modules.as_ref().map_or(0, |modules| modules.len()),
+        req.package_path));
+
+    let job: Box<dyn FnOnce() -> Result<Vec<DisassembledModule>> + Send> = match (req.modules, req.package_path) {
+        (Some(modules), None) if !modules.is_empty() => {
+            let options = req.options;
+            Box::new(move || modules.iter().map(|module| disassemble::disassemble_base64(module, &options)).collect())
+        },
+        (None, Some(package_path)) => {
+            let package_path = match operations::validate_package_path(&package_path) {
+                Ok(path) => path,
+                Err(e) => {
+                    return HttpResponse::BadRequest().json(DisassembleResponse {
+                        error: Some(e.message),
+                        error_code: Some(e.code),
+                        ..DisassembleResponse::default()
+                    });
+                }
+            };
+            let options = req.options;
+            Box::new(move || {
+                disassemble::disassemble_package(&package_path, &options).map_err(|e| {
+                    anyhow!("{}\n{}", e, diagnostics::render(&e.diagnostics, &package_path))
+                })
+            })
+        },
+        _ => {
+            return HttpResponse::BadRequest().json(DisassembleResponse {
+                error: Some("请提供modules或package_path中的一个".to_string()),
+                ..DisassembleResponse::default()
+            });
+        }
+    };
+
+    match scheduler.run(job).await {
+        Ok(Ok(modules)) => HttpResponse::Ok().json(DisassembleResponse {
+            success: true,
+            modules,
+            ..DisassembleResponse::default()
+        }),
+        Ok(Err(e)) => {
+            log(&format!("反汇编失败: {}", e));
+            HttpResponse::Ok().json(DisassembleResponse {
+                error: Some(format!("反汇编失败: {}", e)),
+                ..DisassembleResponse::default()
+            })
+        },
+        Err(e) => schedule_error_response(&e, DisassembleResponse {
+            error: Some(e.to_string()),
+            ..DisassembleResponse::default()
+        }),
+    }
+}
+
+// 格式化请求的数据结构
+#[derive(Debug, Deserialize)]
+struct FormatRequest {
+    source_code: String,
+    file_name: Option<String>, // 源文件名，用于诊断中的路径，默认为source.move
+    #[serde(flatten)]
+    options: FormatOptions, // 缩进宽度和行宽
+}
+
+// 格式化响应的数据结构
+#[derive(Debug, Default, Serialize)]
+struct FormatResponse {
+    success: bool,
+    formatted: Option<String>, // 格式化后的源码
+    changed: bool, // 格式化结果与原源码是否不同
+    diagnostics: Vec<Diagnostic>, // 语法错误的诊断，格式同编译接口
+    error: Option<String>,
+}
+
+// 处理格式化请求的API端点
+async fn format_handler(req: web::Json<FormatRequest>, scheduler: web::Data<BuildScheduler>) -> impl Responder {
+    let req = req.into_inner();
+    let file_name = req.file_name.filter(|name| !name.trim().is_empty()).unwrap_or_else(|| "source.move".to_string());
+    log(&format!("收到格式化请求: 文件名: {}, 长度: {}, 缩进: {}, 行宽: {}",
+        file_name, req.source_code.len(), req.options.indent_width, req.options.line_length));
+
+    if let Err(e) = req.options.validate() {
+        return HttpResponse::BadRequest().json(FormatResponse {
+            error: Some(e.to_string()),
+            ..FormatResponse::default()
+        });
+    }
+
+    let source = req.source_code;
+    let options = req.options;
+    let job = move || {
+        let formatted = format::format_source(&source, &file_name, &options)?;
+        Ok::<_, BuildFailure>((formatted != source, formatted))
+    };
+    match scheduler.run(job).await {
+        Ok(Ok((changed, formatted))) => HttpResponse::Ok().json(FormatResponse {
+            success: true,
+            formatted: Some(formatted),
+            changed,
+            ..FormatResponse::default()
+        }),
+        Ok(Err(e)) => {
+            log(&format!("格式化失败: {}", e));
+            HttpResponse::Ok().json(FormatResponse {
+                error: Some(format!("格式化失败: {}", e)),
+                diagnostics: e.diagnostics,
+                ..FormatResponse::default()
+            })
+        },
+        Err(e) => schedule_error_response(&e, FormatResponse {
+            error: Some(e.to_string()),
+            ..FormatResponse::default()
+        }),
+    }
+}
+
+// 接口描述请求的数据结构
+#[derive(Debug, Deserialize)]
+struct AbiRequest {
+    modules: Option<Vec<String>>, // Base64编码的模块字节码，例如编译接口返回的bytecode_base64
+    package_path: Option<String>, // 编译接口返回的包目录，重新编译以获得常量名
+}
+
+// 接口描述响应的数据结构
+#[derive(Debug, Default, Serialize)]
+struct AbiResponse {
+    success: bool,
+    modules: Vec<ModuleAbi>,
+    error: Option<String>,
+    error_code: Option<ErrorCode>, // 包路径无效时的错误码
+}
+
+// 处理接口描述请求的API端点
+async fn abi_handler(req: web::Json<AbiRequest>, scheduler: web::Data<BuildScheduler>) -> impl Responder {
+    let req = req.into_inner();
+    log(&format!("收到接口描述请求: 模块数: {}, 包目录: {:?}",
+        req.modules.as_ref().map_or(0, |modules| modules.len()),
+        req.package_path));
+
+    let job: Box<dyn FnOnce() -> Result<Vec<ModuleAbi>> + Send> = match (req.modules, req.package_path) {
+        (Some(modules), None) if !modules.is_empty() => {
+            Box::new(move || modules.iter().map(|module| abi::base64_abi(module)).collect())
+        },
+        (None, Some(package_path)) => {
+            let package_path = match operations::validate_package_path(&package_path) {
+                Ok(path) => path,
+                Err(e) => {
+                    return HttpResponse::BadRequest().json(AbiResponse {
+                        error: Some(e.message),
+                        error_code: Some(e.code),
+                        ..AbiResponse::default()
+                    });
+                }
+            };
+            Box::new(move || {
+                let output = compiler::build_package(sui_move_build::BuildConfig::new_for_testing(), &package_path)
+                    .map_err(|e| anyhow!("{}\n{}", e, diagnostics::render(&e.diagnostics, &package_path)))?;
+                Ok(abi::package_abi(&output.package))
+            })
+        },
+        _ => {
+            return HttpResponse::BadRequest().json(AbiResponse {
+                error: Some("请提供modules或package_path中的一个".to_string()),
+                ..AbiResponse::default()
+            });
+        }
+    };
+
+    match scheduler.run(job).await {
+        Ok(Ok(modules)) => HttpResponse::Ok().json(AbiResponse {
+            success: true,
+            modules,
+            ..AbiResponse::default()
+        }),
+        Ok(Err(e)) => {
+            log(&format!("提取接口描述失败: {}", e));
+            HttpResponse::Ok().json(AbiResponse {
+                error: Some(format!("提取接口描述失败: {}", e)),
+                ..AbiResponse::default()
+            })
+        },
+        Err(e) => schedule_error_response(&e, AbiResponse {
+            error: Some(e.to_string()),
+            ..AbiResponse::default()
+        }),
+    }
+}
+
+// TypeScript绑定生成请求的数据结构
+#[derive(Debug, Deserialize)]
+struct CodegenRequest {
+    modules: Option<Vec<String>>, // Base64编码的模块字节码，没有参数名，按位置命名参数
+    package_path: Option<String>, // 编译接口返回的包目录，重新编译以获得参数名
+    package_id: Option<String>, // 已发布的包ID，为空时使用模块地址
+}
+
+// TypeScript绑定生成响应的数据结构
+#[derive(Debug, Default, Serialize)]
+struct CodegenResponse {
+    success: bool,
+    files: Vec<GeneratedFile>,
+    warnings: Vec<String>,
+    error: Option<String>,
+    error_code: Option<ErrorCode>, // 包路径无效时的错误码
+}
+
+// 处理TypeScript绑定生成请求的API端点
+async fn codegen_handler(req: web::Json<CodegenRequest>, scheduler: web::Data<BuildScheduler>) -> impl Responder {
+    let req = req.into_inner();
+    log(&format!("收到TypeScript绑定生成请求: 模块数: {}, 包目录: {:?}, 包ID: {:?}",
+        req.modules.as_ref().map_or(0, |modules| modules.len()),
+        req.package_path,
+        req.package_id));
+
+    let options = CodegenOptions { package_id: req.package_id };
+    let job: Box<dyn FnOnce() -> Result<CodegenOutput> + Send> = match (req.modules, req.package_path) {
+        (Some(modules), None) if !modules.is_empty() => {
+            Box::new(move || {
+                let modules = modules.iter().map(|module| abi::base64_abi(module)).collect::<Result<Vec<_>>>()?;
+                codegen::generate_typescript(&modules, &options)
+            })
+        },
+        (None, Some(package_path)) => {
+            let package_path = match operations::validate_package_path(&package_path) {
+                Ok(path) => path,
+                Err(e) => {
+                    return HttpResponse::BadRequest().json(CodegenResponse {
+                        error: Some(e.message),
+                        error_code: Some(e.code),
+                        ..CodegenResponse::default()
+                    });
+                }
+            };
+            Box::new(move || {
+                let output = compiler::build_package(sui_move_build::BuildConfig::new_for_testing(), &package_path)
+                    .map_err(|e| anyhow!("{}\n{}", e, diagnostics::render(&e.diagnostics, &package_path)))?;
+                codegen::generate_typescript(&abi::package_abi(&output.package), &options)
+            })
+        },
+        _ => {
+            return HttpResponse::BadRequest().json(CodegenResponse {
+                error: Some("请提供modules或package_path中的一个".to_string()),
+                ..CodegenResponse::default()
+            });
+        }
+    };
+
+    match scheduler.run(job).await {
+        Ok(Ok(output)) => HttpResponse::Ok().json(CodegenResponse {
+            success: true,
+            files: output.files,
+            warnings: output.warnings,
+            ..CodegenResponse::default()
+        }),
+        Ok(Err(e)) => {
+            log(&format!("生成TypeScript绑定失败: {}", e));
+            HttpResponse::Ok().json(CodegenResponse {
+                error: Some(format!("生成TypeScript绑定失败: {}", e)),
+                ..CodegenResponse::default()
+            })
+        },
+        Err(e) => schedule_error_response(&e, CodegenResponse {
+            error: Some(e.to_string()),
+            ..CodegenResponse::default()
+        }),
+    }
+}
+
+// 发布、调用交易构建接口的响应
+#[derive(Debug, Default, Serialize)]
+struct TransactionBuildResponse {
+    success: bool,
+    #[serde(flatten)]
+    transaction: Option<UnsignedTransaction>, // 未签名的交易，由前端钱包签名后提交
+    error: Option<String>,
+    error_code: Option<ErrorCode>,
+}
+
+// 处理发布交易构建请求的API端点：根据编译结果构建未签名的发布交易，不使用服务器钱包
+async fn publish_build_handler(req: web::Json<PublishTransactionRequest>) -> impl Responder {
+    log(&format!("收到发布交易构建请求: 发送方 {}, 模块数 {}, 依赖 {:?}",
+        req.gas.sender,
+        req.modules.len(),
+        req.dependencies));
+
+    match transaction::build_publish_transaction(&req) {
+        Ok(transaction) => {
+            log(&format!("发布交易构建成功: {}", transaction.digest));
+            HttpResponse::Ok().json(TransactionBuildResponse {
+                success: true,
+                transaction: Some(transaction),
+                ..TransactionBuildResponse::default()
+            })
+        },
+        Err(e) => {
+            log(&format!("发布交易构建失败: {}", e));
+            HttpResponse::build(operation_error_status(&e)).json(TransactionBuildResponse {
+                error: Some(e.message),
+                error_code: Some(e.code),
+                ..TransactionBuildResponse::default()
+            })
+        }
+    }
+}
+
+// 处理调用交易构建请求的API端点：按字节码中的函数签名校验参数，构建未签名的调用交易
+async fn call_build_handler(req: web::Json<CallTransactionRequest>, rpc: web::Data<SuiRpc>) -> impl Responder {
+    log(&format!("收到调用交易构建请求: {}::{}::{}, 参数数 {}, 本地模块数 {}",
+        req.package,
+        req.module,
+        req.function,
+        req.args.len(),
+        req.modules.len()));
+
+    match call::build_call_transaction(&rpc, &req).await {
+        Ok(transaction) => {
+            log(&format!("调用交易构建成功: {}", transaction.digest));
+            HttpResponse::Ok().json(TransactionBuildResponse {
+                success: true,
+                transaction: Some(transaction),
+                ..TransactionBuildResponse::default()
+            })
+        },
+        Err(e) => {
+            log(&format!("调用交易构建失败: {}", e));
+            HttpResponse::build(operation_error_status(&e)).json(TransactionBuildResponse {
+                error: Some(e.message),
+                error_code: Some(e.code),
+                ..TransactionBuildResponse::default()
+            })
+        }
+    }
+}
+
+// 提交交易接口的响应
+#[derive(Debug, Default, Serialize)]
+struct ExecuteResponse {
+    success: bool, // 交易已执行且执行成功
+    #[serde(flatten)]
+    summary: Option<TransactionSummary>, // 节点返回的执行结果，执行失败时同样返回
+    error: Option<String>,
+    error_code: Option<ErrorCode>,
+}
+
+// 处理提交交易请求的API端点：将钱包签名后的交易转发到配置的Sui节点
+async fn execute_handler(req: web::Json<ExecuteRequest>, rpc: web::Data<SuiRpc>) -> impl Responder {
+    log(&format!("收到交易提交请求: 签名数 {}, 节点 {}", req.signatures.len(), rpc.config().url));
+
+    match rpc.execute(&req).await {
+        Ok(summary) => {
+            log(&format!("交易已执行: {} ({})", summary.digest, summary.status));
+            HttpResponse::Ok().json(ExecuteResponse {
+                success: summary.is_success(),
+                error: summary.execution_error.clone(),
+                summary: Some(summary),
+                ..ExecuteResponse::default()
             })
         },
         Err(e) => {
-            log(&format!("执行测试命令失败: {}", e));
-            HttpResponse::InternalServerError().json(TestResponse {
-                success: false,
-                output: None,
-                error: Some(format!("执行测试命令失败: {}", e)),
+            log(&format!("交易提交失败: {}", e));
+            HttpResponse::build(operation_error_status(&e)).json(ExecuteResponse {
+                error: Some(e.message),
+                error_code: Some(e.code),
+                ..ExecuteResponse::default()
             })
         }
     }
 }
 
+// 升级交易构建接口的响应
+#[derive(Debug, Default, Serialize)]
+struct UpgradeBuildResponse {
+    success: bool,
+    #[serde(flatten)]
+    transaction: Option<UnsignedTransaction>, // 兼容性检查通过时的未签名升级交易
+    current_package_id: Option<String>, // 升级前最新版本的包ID
+    current_version: Option<u64>,
+    cap_policy: Option<UpgradePolicy>, // UpgradeCap当前的策略
+    violations: Vec<CompatibilityViolation>, // 违反的兼容性规则
+    error: Option<String>,
+    error_code: Option<ErrorCode>,
+}
+
+// 处理升级交易构建请求的API端点：先检查新字节码与链上版本的兼容性，再构建未签名的升级交易
+async fn upgrade_build_handler(req: web::Json<UpgradeTransactionRequest>, rpc: web::Data<SuiRpc>) -> impl Responder {
+    log(&format!("收到升级交易构建请求: 包 {}, UpgradeCap {}, 策略 {}, 模块数 {}",
+        req.package_id,
+        req.upgrade_cap,
+        req.policy,
+        req.modules.len()));
+
+    match upgrade::build_upgrade_transaction(&rpc, &req).await {
+        Ok(outcome) if outcome.violations.is_empty() => {
+            log(&format!("升级交易构建成功: {}", outcome.current_package_id));
+            HttpResponse::Ok().json(UpgradeBuildResponse {
+                success: true,
+                transaction: outcome.transaction,
+                current_package_id: Some(outcome.current_package_id),
+                current_version: Some(outcome.current_version),
+                cap_policy: outcome.cap_policy,
+                ..UpgradeBuildResponse::default()
+            })
+        },
+        Ok(outcome) => {
+            log(&format!("升级不兼容: {} 处问题", outcome.violations.len()));
+            let error = OperationError::new(
+                ErrorCode::IncompatibleUpgrade,
+                format!("新版本与链上版本不兼容，共 {} 处问题", outcome.violations.len()),
+            );
+            HttpResponse::build(operation_error_status(&error)).json(UpgradeBuildResponse {
+                current_package_id: Some(outcome.current_package_id),
+                current_version: Some(outcome.current_version),
+                cap_policy: outcome.cap_policy,
+                violations: outcome.violations,
+                error: Some(error.message),
+                error_code: Some(error.code),
+                ..UpgradeBuildResponse::default()
+            })
+        },
+        Err(e) => {
+            log(&format!("升级交易构建失败: {}", e));
+            HttpResponse::build(operation_error_status(&e)).json(UpgradeBuildResponse {
+                error: Some(e.message),
+                error_code: Some(e.code),
+                ..UpgradeBuildResponse::default()
+            })
+        }
+    }
+}
+
+// 本地执行接口的响应
+#[derive(Debug, Default, Serialize)]
+struct SimulateResponse {
+    success: bool, // 发布和函数调用都执行成功
+    #[serde(flatten)]
+    outcome: Option<SimulationOutcome>, // 发布和调用的执行结果，执行失败时同样返回
+    error: Option<String>,
+    error_code: Option<ErrorCode>,
+}
+
+// 处理本地执行请求的API端点：在内存中的模拟链上发布包并执行函数，不访问网络
+async fn simulate_handler(req: web::Json<SimulateRequest>, scheduler: web::Data<BuildScheduler>) -> impl Responder {
+    let req = req.into_inner();
+    log(&format!("收到本地执行请求: {}::{}, 参数数 {}, 模块数 {}",
+        req.call.module,
+        req.call.function,
+        req.call.args.len(),
+        req.package.modules.len()));
+
+    match scheduler.run(move || simulate::simulate(&req)).await {
+        Ok(Ok(outcome)) => {
+            let failure = match &outcome.call {
+                Some(call) => call.execution_error.clone(),
+                None => Some(format!("发布失败: {}", outcome.publish.execution_error.as_deref().unwrap_or_default())),
+            };
+            log(&format!("本地执行完成: {}", failure.as_deref().unwrap_or("成功")));
+            HttpResponse::Ok().json(SimulateResponse {
+                success: failure.is_none(),
+                outcome: Some(outcome),
+                error: failure,
+                ..SimulateResponse::default()
+            })
+        },
+        Ok(Err(e)) => {
+            log(&format!("本地执行失败: {}", e));
+            HttpResponse::build(operation_error_status(&e)).json(SimulateResponse {
+                error: Some(e.message),
+                error_code: Some(e.code),
+                ..SimulateResponse::default()
+            })
+        },
+        Err(e) => schedule_error_response(&e, SimulateResponse {
+            error: Some(e.to_string()),
+            ..SimulateResponse::default()
+        }),
+    }
+}
+
+// 模拟会话接口的响应，各端点只填写相关的字段
+#[derive(Debug, Default, Serialize)]
+struct SessionResponse {
+    success: bool, // 创建会话时为发布成功，执行函数时为函数执行成功
+    session_id: Option<String>,
+    package_id: Option<String>, // 会话中发布的包ID
+    sender: Option<String>,
+    publish: Option<SimulationReport>, // 创建会话时发布交易的执行结果
+    call: Option<SimulationReport>, // 函数的执行结果
+    objects: Option<Vec<ObjectSummary>>, // 会话中的对象，不包括框架包
+    object: Option<ObjectDetail>, // 对象内容和解码后的字段
+    snapshot: Option<String>, // 保存的快照名称
+    snapshots: Option<Vec<String>>, // 会话中所有快照的名称
+    error: Option<String>,
+    error_code: Option<ErrorCode>,
+}
+
+// 保存快照的请求，未指定名称时自动命名
+#[derive(Debug, Default, Deserialize)]
+struct SnapshotRequest {
+    name: Option<String>,
+}
+
+// 会话操作被拒绝时的响应
+fn session_error_response(e: OperationError) -> HttpResponse {
+    log(&format!("模拟会话操作失败: {}", e));
+    HttpResponse::build(operation_error_status(&e)).json(SessionResponse {
+        error: Some(e.message),
+        error_code: Some(e.code),
+        ..SessionResponse::default()
+    })
+}
+
+// 在构建调度器中对会话执行操作，同一会话的操作依次执行
+async fn run_in_session<F>(
+    scheduler: &BuildScheduler,
+    sessions: &SessionStore,
+    session_id: &str,
+    operation: F,
+) -> HttpResponse
+where
+    F: FnOnce(&mut SimulationSession) -> Result<SessionResponse, OperationError> + Send + 'static,
+{
+    let entry = match sessions.get(session_id) {
+        Ok(entry) => entry,
+        Err(e) => return session_error_response(e),
+    };
+    match scheduler.run(move || operation(&mut session::lock_session(&entry))).await {
+        Ok(Ok(response)) => HttpResponse::Ok().json(response),
+        Ok(Err(e)) => session_error_response(e),
+        Err(e) => schedule_error_response(&e, SessionResponse {
+            error: Some(e.to_string()),
+            ..SessionResponse::default()
+        }),
+    }
+}
+
+// 创建模拟会话：在新的本地模拟链上发布包，之后的调用共用这条链上的对象
+async fn session_create_handler(
+    req: web::Json<SimulatedPackage>,
+    scheduler: web::Data<BuildScheduler>,
+    sessions: web::Data<SessionStore>,
+) -> impl Responder {
+    let req = req.into_inner();
+    log(&format!("收到创建模拟会话请求: 模块数 {}", req.modules.len()));
+
+    let max_snapshots = sessions.config().max_snapshots;
+    match scheduler.run(move || SimulationSession::create(&req, max_snapshots)).await {
+        Ok(Ok((Some(session), publish))) => {
+            let package_id = session.package_id().to_string();
+            let sender = session.sender();
+            let session_id = sessions.insert(session);
+            log(&format!("模拟会话已创建: {}, 包 {}", session_id, package_id));
+            HttpResponse::Ok().json(SessionResponse {
+                success: true,
+                session_id: Some(session_id),
+                package_id: Some(package_id),
+                sender: Some(sender),
+                publish: Some(publish),
+                ..SessionResponse::default()
+            })
+        },
+        Ok(Ok((None, publish))) => {
+            let error = format!("发布失败: {}", publish.execution_error.as_deref().unwrap_or_default());
+            log(&format!("模拟会话创建失败: {}", error));
+            HttpResponse::Ok().json(SessionResponse {
+                publish: Some(publish),
+                error: Some(error),
+                ..SessionResponse::default()
+            })
+        },
+        Ok(Err(e)) => session_error_response(e),
+        Err(e) => schedule_error_response(&e, SessionResponse {
+            error: Some(e.to_string()),
+            ..SessionResponse::default()
+        }),
+    }
+}
+
+// 在会话中执行函数，对象参数可以使用之前的调用创建的对象
+async fn session_call_handler(
+    path: web::Path<String>,
+    req: web::Json<SessionCallRequest>,
+    scheduler: web::Data<BuildScheduler>,
+    sessions: web::Data<SessionStore>,
+) -> impl Responder {
+    let session_id = path.into_inner();
+    let req = req.into_inner();
+    log(&format!("收到会话执行请求: {}, {}::{}, 参数数 {}",
+        session_id,
+        req.call.module,
+        req.call.function,
+        req.call.args.len()));
+
+    run_in_session(&scheduler, &sessions, &session_id, move |session| {
+        let report = session.call(&req)?;
+        log(&format!("会话执行完成: {}", report.execution_error.as_deref().unwrap_or("成功")));
+        Ok(SessionResponse {
+            success: report.is_success(),
+            error: report.execution_error.clone(),
+            call: Some(report),
+            ..SessionResponse::default()
+        })
+    }).await
+}
+
+// 列出会话中的对象
+async fn session_objects_handler(
+    path: web::Path<String>,
+    scheduler: web::Data<BuildScheduler>,
+    sessions: web::Data<SessionStore>,
+) -> impl Responder {
+    run_in_session(&scheduler, &sessions, &path.into_inner(), |session| {
+        Ok(SessionResponse {
+            success: true,
+            objects: Some(session.objects()),
+            ..SessionResponse::default()
+        })
+    }).await
+}
+
+// 读取会话中的对象，字段解码为JSON
+async fn session_object_handler(
+    path: web::Path<(String, String)>,
+    scheduler: web::Data<BuildScheduler>,
+    sessions: web::Data<SessionStore>,
+) -> impl Responder {
+    let (session_id, object_id) = path.into_inner();
+    run_in_session(&scheduler, &sessions, &session_id, move |session| {
+        Ok(SessionResponse {
+            success: true,
+            object: Some(session.object(&object_id)?),
+            ..SessionResponse::default()
+        })
+    }).await
+}
+
+// 将会话恢复到刚发布包后的状态
+async fn session_reset_handler(
+    path: web::Path<String>,
+    scheduler: web::Data<BuildScheduler>,
+    sessions: web::Data<SessionStore>,
+) -> impl Responder {
+    let session_id = path.into_inner();
+    log(&format!("重置模拟会话: {}", session_id));
+    run_in_session(&scheduler, &sessions, &session_id, |session| {
+        session.reset();
+        Ok(SessionResponse {
+            success: true,
+            objects: Some(session.objects()),
+            ..SessionResponse::default()
+        })
+    }).await
+}
+
+// 保存会话当前的对象存储
+async fn session_snapshot_handler(
+    path: web::Path<String>,
+    req: Option<web::Json<SnapshotRequest>>,
+    scheduler: web::Data<BuildScheduler>,
+    sessions: web::Data<SessionStore>,
+) -> impl Responder {
+    let session_id = path.into_inner();
+    let req = req.map(web::Json::into_inner).unwrap_or_default();
+    run_in_session(&scheduler, &sessions, &session_id, move |session| {
+        let name = session.save_snapshot(req.name.as_deref())?;
+        Ok(SessionResponse {
+            success: true,
+            snapshot: Some(name),
+            snapshots: Some(session.snapshot_names()),
+            ..SessionResponse::default()
+        })
+    }).await
+}
+
+// 将会话恢复到已保存的快照
+async fn session_restore_handler(
+    path: web::Path<(String, String)>,
+    scheduler: web::Data<BuildScheduler>,
+    sessions: web::Data<SessionStore>,
+) -> impl Responder {
+    let (session_id, name) = path.into_inner();
+    log(&format!("恢复模拟会话 {} 到快照 {}", session_id, name));
+    run_in_session(&scheduler, &sessions, &session_id, move |session| {
+        session.restore_snapshot(&name)?;
+        Ok(SessionResponse {
+            success: true,
+            snapshot: Some(name),
+            objects: Some(session.objects()),
+            ..SessionResponse::default()
+        })
+    }).await
+}
+
+// 删除模拟会话
+async fn session_delete_handler(path: web::Path<String>, sessions: web::Data<SessionStore>) -> impl Responder {
+    let session_id = path.into_inner();
+    if !sessions.remove(&session_id) {
+        return session_error_response(OperationError::new(
+            ErrorCode::SessionNotFound,
+            format!("会话不存在或已过期: {}", session_id),
+        ));
+    }
+    log(&format!("模拟会话已删除: {}", session_id));
+    HttpResponse::Ok().json(SessionResponse {
+        success: true,
+        session_id: Some(session_id),
+        ..SessionResponse::default()
+    })
+}
+
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    let args: Vec<String> = std::env::args().collect();
-    
-    if args.len() > 1 {
-        // 命令行模式
-        match args[1].as_str() {
-            "compile" => {
-                let mut source_path = None;
-                let mut verbose = false;
-                
-                let mut i = 2;
-                while i < args.len() {
-                    match args[i].as_str() {
-                        "-s" | "--source" => {
-                            if i + 1 < args.len() {
-                                source_path = Some(PathBuf::from(&args[i + 1]));
-                                i += 2;
-                            } else {
-                                eprintln!("错误: -s/--source 参数需要指定源文件路径");
-                                std::process::exit(1);
-                            }
-                        },
-                        "-v" | "--verbose" => {
-                            verbose = true;
-                            i += 1;
-                        },
-                        _ => {
-                            eprintln!("未知参数: {}", args[i]);
-                            i += 1;
-                        }
-                    }
-                }
-                
-                if let Some(path) = source_path {
-                    match compile_single_file(path, verbose).await {
-                        Ok(_) => std::process::exit(0),
-                        Err(e) => {
-                            eprintln!("Error: {}", e);
-                            std::process::exit(1);
-                        }
-                    }
-                } else {
-                    eprintln!("错误: 缺少源文件路径。使用 -s/--source 指定源文件");
-                    std::process::exit(1);
-                }
-            },
-            _ => {
-                println!("未知命令: {}", args[1]);
-                println!("可用命令: compile");
-                std::process::exit(1);
+    let options = CliOptions::parse();
+    let verbose = options.verbose;
+
+    // 没有子命令时启动Web服务器
+    let result = match options.commands.unwrap_or_else(|| Commands::Serve(ServeArgs::default())) {
+        Commands::Serve(args) => match args.server_config() {
+            Ok(config) => return serve(config).await,
+            Err(e) => Err(e),
+        },
+        Commands::Compile { source, framework } => {
+            match framework.as_deref().map(str::parse::<Framework>).transpose() {
+                Ok(framework) => compile_single_file(source, framework, verbose).await,
+                Err(e) => Err(e),
             }
+        },
+        Commands::Build { targets, dependency_dirs, address_maps, out_dir } => {
+            build_command(targets, dependency_dirs, address_maps, out_dir, verbose)
+        },
+        Commands::Disassemble(args) => disassemble_command(&args),
+        Commands::Test(args) => test_command(&args, verbose),
+        Commands::Codegen { path, out_dir, package_id } => codegen_command(path, out_dir, package_id, verbose),
+        Commands::Simulate(args) => simulate_command(&args, verbose),
+        Commands::Format(args) => format_command(&args),
+        Commands::UnitTestWorker { package_path, report_path, gas_limit, filter, coverage } => {
+            let options = UnitTestOptions { filter, gas_limit, coverage: coverage.is_some(), ..UnitTestOptions::default() };
+            unit_test::run_worker(&package_path, &report_path, coverage.as_deref(), &options)
+        },
+    };
+
+    match result {
+        Ok(_) => std::process::exit(0),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
         }
     }
-    
+}
+
+// 启动Web服务器
+async fn serve(config: ServerConfig) -> std::io::Result<()> {
     // Web服务器模式
-    println!("启动Move Web编译器服务器，监听端口8081...");
+    println!("启动Move Web编译器服务器，监听 {}:{}...", config.host, config.port);
+    println!("静态文件目录: {:?}", config.static_dir);
+    if config.allows_any_origin() {
+        println!("跨域来源: 任意");
+    } else {
+        println!("跨域来源: {}", config.allowed_origins.join(", "));
+    }
+
+    // 后台定期清理过期或超出配额的构建工作区
+    let workspace_config = WorkspaceConfig::from_env();
+    println!("构建工作区: {:?} (保留 {} 秒, 配额 {} MB)",
+        workspace_config.root,
+        workspace_config.ttl.as_secs(),
+        workspace_config.max_total_bytes / 1024 / 1024);
+    workspace_config.spawn_reaper();
     
-    HttpServer::new(|| {
-        let cors = Cors::default()
-            .allow_any_origin()
+    // 编译和测试共用的构建调度器
+    let scheduler_config = SchedulerConfig::from_env();
+    println!("构建调度: 最大并发 {}, 最大排队 {}, 超时 {} 秒",
+        scheduler_config.max_concurrent,
+        scheduler_config.max_queue,
+        scheduler_config.build_timeout.as_secs());
+    let scheduler = web::Data::new(BuildScheduler::new(scheduler_config));
+
+    // 提交交易使用的Sui节点，首次请求时连接
+    let rpc_config = RpcConfig::from_env();
+    println!("Sui节点RPC: {}", rpc_config.url);
+    let rpc = web::Data::new(SuiRpc::new(rpc_config));
+
+    // 本地模拟会话保存在内存中，服务重启后丢失
+    let session_config = SessionConfig::from_env();
+    println!("模拟会话: 最多 {} 个, 保留 {} 秒, 每个会话最多 {} 个快照",
+        session_config.max_sessions,
+        session_config.ttl.as_secs(),
+        session_config.max_snapshots);
+    let sessions = web::Data::new(SessionStore::new(session_config));
+
+    let bind_address = config.bind_address();
+    let workers = config.workers;
+    let server = HttpServer::new(move || {
+        let mut cors = Cors::default()
             .allow_any_method()
             .allow_any_header();
-            
+        if config.allows_any_origin() {
+            cors = cors.allow_any_origin();
+        } else {
+            for origin in &config.allowed_origins {
+                cors = cors.allowed_origin(origin);
+            }
+        }
+
         App::new()
             .wrap(cors)
+            .app_data(scheduler.clone())
+            .app_data(rpc.clone())
+            .app_data(sessions.clone())
+            .app_data(web::JsonConfig::default().limit(config.max_body_bytes))
+            .app_data(web::PayloadConfig::new(config.max_body_bytes))
             .route("/api/compile", web::post().to(compile_handler))
             .route("/api/deploy", web::post().to(deploy_handler))
             .route("/api/test", web::post().to(test_handler))
-            .service(fs::Files::new("/", "./static").index_file("index.html"))
-    })
-    .bind("0.0.0.0:8081")?
-    .run()
-    .await
+            .route("/api/disassemble", web::post().to(disassemble_handler))
+            .route("/api/format", web::post().to(format_handler))
+            .route("/api/abi", web::post().to(abi_handler))
+            .route("/api/codegen", web::post().to(codegen_handler))
+            .route("/api/publish/build", web::post().to(publish_build_handler))
+            .route("/api/execute", web::post().to(execute_handler))
+            .route("/api/upgrade/build", web::post().to(upgrade_build_handler))
+            .route("/api/call/build", web::post().to(call_build_handler))
+            .route("/api/simulate", web::post().to(simulate_handler))
+            .route("/api/simulate/sessions", web::post().to(session_create_handler))
+            .route("/api/simulate/sessions/{id}", web::delete().to(session_delete_handler))
+            .route("/api/simulate/sessions/{id}/call", web::post().to(session_call_handler))
+            .route("/api/simulate/sessions/{id}/objects", web::get().to(session_objects_handler))
+            .route("/api/simulate/sessions/{id}/objects/{object_id}", web::get().to(session_object_handler))
+            .route("/api/simulate/sessions/{id}/reset", web::post().to(session_reset_handler))
+            .route("/api/simulate/sessions/{id}/snapshots", web::post().to(session_snapshot_handler))
+            .route("/api/simulate/sessions/{id}/snapshots/{name}/restore", web::post().to(session_restore_handler))
+            .service(fs::Files::new("/", &config.static_dir).index_file("index.html"))
+    });
+    let server = match workers {
+        Some(workers) => server.workers(workers),
+        None => server,
+    };
+
+    server.bind(bind_address)?.run().await
 }
\ No newline at end of file
@@ -0,0 +1,618 @@
+// Copyright (c) Move Web Compiler
+// SPDX-License-Identifier: Apache-2.0
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use move_core_types::account_address::AccountAddress;
+use move_core_types::identifier;
+use move_core_types::parser::parse_type_tag;
+use serde::{Deserialize, Serialize};
+
+use crate::workspace::WorkspaceConfig;
+
+/// 默认gas预算（MIST）
+pub const DEFAULT_GAS_BUDGET: u64 = 200_000_000;
+
+/// 允许的最大gas预算（MIST），避免一次请求耗尽服务器钱包
+pub const MAX_GAS_BUDGET: u64 = 10_000_000_000;
+
+/// 单次调用允许的最大参数个数
+pub const MAX_CALL_ARGS: usize = 32;
+
+/// 单个参数的最大长度
+pub const MAX_ARG_LENGTH: usize = 1024;
+
+/// 操作错误码
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    /// 请求体不是合法的操作
+    InvalidRequest,
+    /// 旧接口的命令无法解析
+    InvalidCommand,
+    /// 命令或操作不在允许列表中
+    OperationNotAllowed,
+    /// 参数不合法
+    InvalidArgument,
+    /// 包路径不是服务器创建的工作区
+    InvalidPackagePath,
+    /// 无法访问Sui节点或节点拒绝了请求
+    RpcError,
+    /// 新版本的包与链上版本不兼容
+    IncompatibleUpgrade,
+    /// 无法创建本地执行环境
+    SimulationError,
+    /// 模拟会话不存在或已过期
+    SessionNotFound,
+}
+
+impl ErrorCode {
+    /// 错误码字符串
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::InvalidRequest => "INVALID_REQUEST",
+            ErrorCode::InvalidCommand => "INVALID_COMMAND",
+            ErrorCode::OperationNotAllowed => "OPERATION_NOT_ALLOWED",
+            ErrorCode::InvalidArgument => "INVALID_ARGUMENT",
+            ErrorCode::InvalidPackagePath => "INVALID_PACKAGE_PATH",
+            ErrorCode::RpcError => "RPC_ERROR",
+            ErrorCode::IncompatibleUpgrade => "INCOMPATIBLE_UPGRADE",
+            ErrorCode::SimulationError => "SIMULATION_ERROR",
+            ErrorCode::SessionNotFound => "SESSION_NOT_FOUND",
+        }
+    }
+}
+
+/// 操作校验失败
+#[derive(Clone, Debug)]
+pub struct OperationError {
+    /// 错误码
+    pub code: ErrorCode,
+    /// 错误信息
+    pub message: String,
+}
+
+impl OperationError {
+    /// 构造操作错误
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for OperationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.code.as_str(), self.message)
+    }
+}
+
+impl std::error::Error for OperationError {}
+
+/// 发布包
+#[derive(Clone, Debug, Deserialize)]
+pub struct PublishOperation {
+    /// 编译接口返回的包目录（`bytecode_path`）
+    pub package_path: String,
+    /// gas预算（MIST）
+    #[serde(default = "default_gas_budget")]
+    pub gas_budget: u64,
+}
+
+/// 调用已发布包中的函数
+#[derive(Clone, Debug, Deserialize)]
+pub struct CallOperation {
+    /// 包ID
+    pub package: String,
+    /// 模块名
+    pub module: String,
+    /// 函数名
+    pub function: String,
+    /// 类型参数，例如`0x2::sui::SUI`
+    #[serde(default)]
+    pub type_args: Vec<String>,
+    /// 调用参数
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// gas预算（MIST）
+    #[serde(default = "default_gas_budget")]
+    pub gas_budget: u64,
+}
+
+/// 运行包的单元测试
+#[derive(Clone, Debug, Deserialize)]
+pub struct TestOperation {
+    /// 编译接口返回的包目录（`bytecode_path`）
+    pub package_path: String,
+    /// 只运行名称包含该字符串的测试
+    #[serde(default)]
+    pub filter: Option<String>,
+}
+
+/// 允许执行的操作
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "operation", rename_all = "snake_case")]
+pub enum Operation {
+    Publish(PublishOperation),
+    Call(CallOperation),
+    Test(TestOperation),
+}
+
+impl Operation {
+    /// 操作名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            Operation::Publish(_) => "publish",
+            Operation::Call(_) => "call",
+            Operation::Test(_) => "test",
+        }
+    }
+
+    /// 从请求体解析操作：包含`command`字段时按旧接口解析命令，否则按操作类型解析
+    pub fn from_request(body: serde_json::Value) -> Result<Self, OperationError> {
+        if let Some(command) = body.get("command") {
+            let command = command
+                .as_str()
+                .ok_or_else(|| OperationError::new(ErrorCode::InvalidRequest, "command必须是字符串"))?;
+            return Self::parse_command(command);
+        }
+        serde_json::from_value(body)
+            .map_err(|e| OperationError::new(ErrorCode::InvalidRequest, format!("无效的操作请求: {}", e)))
+    }
+
+    /// 将旧接口的sui命令解析为操作，只接受`sui client publish`、`sui client call`和`sui move test`
+    pub fn parse_command(command: &str) -> Result<Self, OperationError> {
+        let parts: Vec<&str> = command.split_whitespace().collect();
+        if parts.len() < 3 || parts[0] != "sui" {
+            return Err(OperationError::new(
+                ErrorCode::InvalidCommand,
+                "无效的命令格式，必须是sui开头的命令",
+            ));
+        }
+
+        let operation = match (parts[1], parts[2]) {
+            ("client", "publish") => parse_publish_command(&parts[3..])?,
+            ("client", "call") => parse_call_command(&parts[3..])?,
+            ("move", "test") => parse_test_command(&parts[3..])?,
+            (group, subcommand) => {
+                return Err(OperationError::new(
+                    ErrorCode::OperationNotAllowed,
+                    format!("不允许执行命令: sui {} {}", group, subcommand),
+                ))
+            }
+        };
+        operation.validate()?;
+        Ok(operation)
+    }
+
+    /// 校验操作参数
+    pub fn validate(&self) -> Result<(), OperationError> {
+        match self {
+            Operation::Publish(publish) => {
+                validate_package_path(&publish.package_path)?;
+                validate_gas_budget(publish.gas_budget)
+            }
+            Operation::Call(call) => {
+                AccountAddress::from_hex_literal(&call.package).map_err(|_| {
+                    OperationError::new(ErrorCode::InvalidArgument, format!("无效的包ID: {}", call.package))
+                })?;
+                for name in [&call.module, &call.function] {
+                    if !identifier::is_valid(name) {
+                        return Err(OperationError::new(
+                            ErrorCode::InvalidArgument,
+                            format!("无效的Move标识符: {}", name),
+                        ));
+                    }
+                }
+                for type_arg in &call.type_args {
+                    parse_type_tag(type_arg).map_err(|_| {
+                        OperationError::new(ErrorCode::InvalidArgument, format!("无效的类型参数: {}", type_arg))
+                    })?;
+                }
+                if call.args.len() > MAX_CALL_ARGS {
+                    return Err(OperationError::new(
+                        ErrorCode::InvalidArgument,
+                        format!("参数过多，最多允许 {} 个", MAX_CALL_ARGS),
+                    ));
+                }
+                for arg in &call.args {
+                    validate_call_arg(arg)?;
+                }
+                validate_gas_budget(call.gas_budget)
+            }
+            Operation::Test(test) => {
+                validate_package_path(&test.package_path)?;
+                match &test.filter {
+                    Some(filter) => validate_test_filter(filter),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+
+    /// 生成传给`sui`的参数，只有发布和调用需要通过Sui CLI执行
+    ///
+    /// 始终带上`--json`，输出为节点返回的交易响应，由`rpc::TransactionSummary::from_cli_output`解析。
+    pub fn sui_args(&self) -> Result<Vec<String>, OperationError> {
+        self.validate()?;
+        match self {
+            Operation::Publish(publish) => {
+                let package_path = validate_package_path(&publish.package_path)?;
+                Ok(vec![
+                    "client".to_string(),
+                    "publish".to_string(),
+                    "--json".to_string(),
+                    package_path.display().to_string(),
+                    "--gas-budget".to_string(),
+                    publish.gas_budget.to_string(),
+                ])
+            }
+            Operation::Call(call) => {
+                let mut args = vec![
+                    "client".to_string(),
+                    "call".to_string(),
+                    "--json".to_string(),
+                    "--package".to_string(),
+                    call.package.clone(),
+                    "--module".to_string(),
+                    call.module.clone(),
+                    "--function".to_string(),
+                    call.function.clone(),
+                ];
+                if !call.type_args.is_empty() {
+                    args.push("--type-args".to_string());
+                    args.extend(call.type_args.iter().cloned());
+                }
+                if !call.args.is_empty() {
+                    args.push("--args".to_string());
+                    args.extend(call.args.iter().cloned());
+                }
+                args.push("--gas-budget".to_string());
+                args.push(call.gas_budget.to_string());
+                Ok(args)
+            }
+            Operation::Test(_) => Err(OperationError::new(
+                ErrorCode::OperationNotAllowed,
+                "测试操作不通过Sui CLI执行",
+            )),
+        }
+    }
+}
+
+/// 校验包路径必须是服务器创建的工作区，返回规范化后的路径
+pub fn validate_package_path(path: &str) -> Result<PathBuf, OperationError> {
+    let invalid = || OperationError::new(ErrorCode::InvalidPackagePath, format!("无效的包路径: {}", path));
+
+    let path = Path::new(path);
+    if !WorkspaceConfig::from_env().contains(path) || !path.join("Move.toml").is_file() {
+        return Err(invalid());
+    }
+    path.canonicalize().map_err(|_| invalid())
+}
+
+/// 校验测试过滤条件，过滤条件作为进程参数传给测试工作进程，不能被解析为选项
+pub fn validate_test_filter(filter: &str) -> Result<(), OperationError> {
+    if filter.starts_with('-') || filter.chars().any(|c| c.is_control()) {
+        return Err(OperationError::new(
+            ErrorCode::InvalidArgument,
+            format!("无效的测试过滤条件: {}", filter),
+        ));
+    }
+    Ok(())
+}
+
+fn default_gas_budget() -> u64 {
+    DEFAULT_GAS_BUDGET
+}
+
+fn validate_gas_budget(gas_budget: u64) -> Result<(), OperationError> {
+    if gas_budget == 0 || gas_budget > MAX_GAS_BUDGET {
+        return Err(OperationError::new(
+            ErrorCode::InvalidArgument,
+            format!("gas预算必须在 1 到 {} 之间", MAX_GAS_BUDGET),
+        ));
+    }
+    Ok(())
+}
+
+// 参数作为独立的进程参数传递，不经过shell，这里只需防止被解析为命令行选项
+fn validate_call_arg(arg: &str) -> Result<(), OperationError> {
+    if arg.is_empty() || arg.len() > MAX_ARG_LENGTH || arg.starts_with('-') || arg.chars().any(|c| c.is_control()) {
+        return Err(OperationError::new(ErrorCode::InvalidArgument, format!("无效的调用参数: {}", arg)));
+    }
+    Ok(())
+}
+
+// 取出选项后面的值
+fn option_value<'a>(args: &[&'a str], index: usize) -> Result<&'a str, OperationError> {
+    args.get(index + 1)
+        .copied()
+        .filter(|value| !value.starts_with("--"))
+        .ok_or_else(|| OperationError::new(ErrorCode::InvalidCommand, format!("选项 {} 缺少参数值", args[index])))
+}
+
+fn parse_gas_budget(value: &str) -> Result<u64, OperationError> {
+    value
+        .parse()
+        .map_err(|_| OperationError::new(ErrorCode::InvalidArgument, format!("无效的gas预算: {}", value)))
+}
+
+fn unsupported_option(option: &str) -> OperationError {
+    OperationError::new(ErrorCode::OperationNotAllowed, format!("不支持的选项: {}", option))
+}
+
+// sui client publish [--path] <包路径> --gas-budget <预算>
+fn parse_publish_command(args: &[&str]) -> Result<Operation, OperationError> {
+    let mut package_path = None;
+    let mut gas_budget = DEFAULT_GAS_BUDGET;
+    let mut i = 0;
+    while i < args.len() {
+        match args[i] {
+            "--gas-budget" => {
+                gas_budget = parse_gas_budget(option_value(args, i)?)?;
+                i += 2;
+            }
+            "--path" => {
+                package_path = Some(option_value(args, i)?.to_string());
+                i += 2;
+            }
+            option if option.starts_with('-') => return Err(unsupported_option(option)),
+            path if package_path.is_none() => {
+                package_path = Some(path.to_string());
+                i += 1;
+            }
+            extra => {
+                return Err(OperationError::new(ErrorCode::InvalidCommand, format!("多余的参数: {}", extra)))
+            }
+        }
+    }
+
+    let package_path =
+        package_path.ok_or_else(|| OperationError::new(ErrorCode::InvalidCommand, "发布命令缺少包路径"))?;
+    Ok(Operation::Publish(PublishOperation { package_path, gas_budget }))
+}
+
+// sui client call --package <ID> --module <模块> --function <函数> [--type-args ...] [--args ...] --gas-budget <预算>
+fn parse_call_command(args: &[&str]) -> Result<Operation, OperationError> {
+    let mut package = None;
+    let mut module = None;
+    let mut function = None;
+    let mut type_args = Vec::new();
+    let mut call_args = Vec::new();
+    let mut gas_budget = DEFAULT_GAS_BUDGET;
+    let mut i = 0;
+    while i < args.len() {
+        match args[i] {
+            "--package" => {
+                package = Some(option_value(args, i)?.to_string());
+                i += 2;
+            }
+            "--module" => {
+                module = Some(option_value(args, i)?.to_string());
+                i += 2;
+            }
+            "--function" => {
+                function = Some(option_value(args, i)?.to_string());
+                i += 2;
+            }
+            "--gas-budget" => {
+                gas_budget = parse_gas_budget(option_value(args, i)?)?;
+                i += 2;
+            }
+            list @ ("--type-args" | "--args") => {
+                // 多值选项，取到下一个选项为止
+                i += 1;
+                while i < args.len() && !args[i].starts_with("--") {
+                    let value = args[i].to_string();
+                    if list == "--type-args" {
+                        type_args.push(value);
+                    } else {
+                        call_args.push(value);
+                    }
+                    i += 1;
+                }
+            }
+            option => return Err(unsupported_option(option)),
+        }
+    }
+
+    let missing = |name: &str| OperationError::new(ErrorCode::InvalidCommand, format!("调用命令缺少 --{}", name));
+    Ok(Operation::Call(CallOperation {
+        package: package.ok_or_else(|| missing("package"))?,
+        module: module.ok_or_else(|| missing("module"))?,
+        function: function.ok_or_else(|| missing("function"))?,
+        type_args,
+        args: call_args,
+        gas_budget,
+    }))
+}
+
+// sui move test [过滤条件] --path <包路径>
+fn parse_test_command(args: &[&str]) -> Result<Operation, OperationError> {
+    let mut package_path = None;
+    let mut filter = None;
+    let mut i = 0;
+    while i < args.len() {
+        match args[i] {
+            "--path" | "-p" => {
+                package_path = Some(option_value(args, i)?.to_string());
+                i += 2;
+            }
+            option if option.starts_with('-') => return Err(unsupported_option(option)),
+            value if filter.is_none() => {
+                filter = Some(value.to_string());
+                i += 1;
+            }
+            extra => {
+                return Err(OperationError::new(ErrorCode::InvalidCommand, format!("多余的参数: {}", extra)))
+            }
+        }
+    }
+
+    let package_path =
+        package_path.ok_or_else(|| OperationError::new(ErrorCode::InvalidCommand, "测试命令缺少 --path"))?;
+    Ok(Operation::Test(TestOperation { package_path, filter }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 服务器创建的工作区，`manifest`为真时写入Move.toml，离开作用域时删除
+    struct Workspace(PathBuf);
+
+    impl Workspace {
+        fn new(manifest: bool) -> Self {
+            let path = WorkspaceConfig::from_env().create().unwrap();
+            if manifest {
+                std::fs::write(path.join("Move.toml"), "[package]\nname = \"hello\"\n").unwrap();
+            }
+            Self(path)
+        }
+
+        fn path(&self) -> String {
+            self.0.display().to_string()
+        }
+    }
+
+    impl Drop for Workspace {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn parse_error(command: &str) -> ErrorCode {
+        Operation::parse_command(command).unwrap_err().code
+    }
+
+    fn strings(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    const CALL: &str = "sui client call --package 0x2 --module coin --function join";
+
+    #[test]
+    fn rejects_commands_outside_allowlist() {
+        assert_eq!(parse_error("sui keytool export --key-identity main"), ErrorCode::OperationNotAllowed);
+        assert_eq!(parse_error("sui client transfer --to 0x1 --object-id 0x2"), ErrorCode::OperationNotAllowed);
+        assert_eq!(parse_error("ls -la /"), ErrorCode::InvalidCommand);
+        assert_eq!(parse_error("sui client"), ErrorCode::InvalidCommand);
+    }
+
+    #[test]
+    fn rejects_unsupported_options() {
+        let workspace = Workspace::new(true);
+        let command = format!("sui client publish {} --skip-dependency-verification", workspace.path());
+        assert_eq!(parse_error(&command), ErrorCode::OperationNotAllowed);
+        assert_eq!(parse_error(&format!("{} --sender 0x1", CALL)), ErrorCode::OperationNotAllowed);
+    }
+
+    #[test]
+    fn rejects_package_paths_outside_workspaces() {
+        assert_eq!(parse_error("sui client publish --path ../../etc"), ErrorCode::InvalidPackagePath);
+
+        let outside = std::env::temp_dir().join(format!("move-operations-test-{:016x}", rand::random::<u64>()));
+        std::fs::create_dir_all(&outside).unwrap();
+        std::fs::write(outside.join("Move.toml"), "[package]\nname = \"hello\"\n").unwrap();
+        let result = validate_package_path(&outside.display().to_string());
+        let _ = std::fs::remove_dir_all(&outside);
+        assert_eq!(result.unwrap_err().code, ErrorCode::InvalidPackagePath);
+
+        let workspace = Workspace::new(true);
+        let nested = format!("{}/sources", workspace.path());
+        std::fs::create_dir_all(&nested).unwrap();
+        assert_eq!(validate_package_path(&nested).unwrap_err().code, ErrorCode::InvalidPackagePath);
+    }
+
+    #[test]
+    fn rejects_workspace_without_manifest() {
+        let workspace = Workspace::new(false);
+        assert_eq!(validate_package_path(&workspace.path()).unwrap_err().code, ErrorCode::InvalidPackagePath);
+        let command = format!("sui client publish {}", workspace.path());
+        assert_eq!(parse_error(&command), ErrorCode::InvalidPackagePath);
+    }
+
+    #[test]
+    fn rejects_invalid_call_arguments() {
+        assert_eq!(parse_error(&format!("{} --args -1", CALL)), ErrorCode::InvalidArgument);
+        assert_eq!(parse_error(&format!("{} --args 0x1\u{7}", CALL)), ErrorCode::InvalidArgument);
+        let long = "1".repeat(MAX_ARG_LENGTH + 1);
+        assert_eq!(parse_error(&format!("{} --args {}", CALL, long)), ErrorCode::InvalidArgument);
+        let many = vec!["1"; MAX_CALL_ARGS + 1].join(" ");
+        assert_eq!(parse_error(&format!("{} --args {}", CALL, many)), ErrorCode::InvalidArgument);
+        assert_eq!(parse_error(&format!("{} --type-args not::a-type", CALL)), ErrorCode::InvalidArgument);
+        assert_eq!(parse_error("sui client call --package 0x2 --module coin --function 1join"), ErrorCode::InvalidArgument);
+    }
+
+    #[test]
+    fn rejects_invalid_gas_budgets() {
+        assert_eq!(parse_error(&format!("{} --gas-budget 0", CALL)), ErrorCode::InvalidArgument);
+        assert_eq!(
+            parse_error(&format!("{} --gas-budget {}", CALL, MAX_GAS_BUDGET + 1)),
+            ErrorCode::InvalidArgument
+        );
+        assert_eq!(parse_error(&format!("{} --gas-budget lots", CALL)), ErrorCode::InvalidArgument);
+        assert_eq!(parse_error(&format!("{} --gas-budget", CALL)), ErrorCode::InvalidCommand);
+    }
+
+    #[test]
+    fn rejects_invalid_requests() {
+        let body = serde_json::json!({ "operation": "call", "package": "0x2", "module": "coin", "function": "join", "args": ["\u{1b}[2J"] });
+        assert_eq!(Operation::from_request(body).unwrap().validate().unwrap_err().code, ErrorCode::InvalidArgument);
+        let body = serde_json::json!({ "operation": "transfer" });
+        assert_eq!(Operation::from_request(body).unwrap_err().code, ErrorCode::InvalidRequest);
+        let body = serde_json::json!({ "command": 1 });
+        assert_eq!(Operation::from_request(body).unwrap_err().code, ErrorCode::InvalidRequest);
+    }
+
+    #[test]
+    fn maps_publish_to_sui_args() {
+        let workspace = Workspace::new(true);
+        let canonical = workspace.0.canonicalize().unwrap().display().to_string();
+        let expected = strings(&["client", "publish", "--json", &canonical, "--gas-budget", "100000000"]);
+
+        for command in [
+            format!("sui client publish {} --gas-budget 100000000", workspace.path()),
+            format!("sui client publish --gas-budget 100000000 --path {}", workspace.path()),
+        ] {
+            assert_eq!(Operation::parse_command(&command).unwrap().sui_args().unwrap(), expected);
+        }
+
+        let body = serde_json::json!({ "operation": "publish", "package_path": workspace.path() });
+        let args = Operation::from_request(body).unwrap().sui_args().unwrap();
+        assert_eq!(args.last().unwrap(), &DEFAULT_GAS_BUDGET.to_string());
+    }
+
+    #[test]
+    fn maps_call_to_sui_args() {
+        let command = format!("{} --type-args 0x2::sui::SUI --args 0xa 0xb --gas-budget 5000", CALL);
+        let operation = Operation::parse_command(&command).unwrap();
+        assert_eq!(operation.sui_args().unwrap(), strings(&[
+            "client", "call", "--json", "--package", "0x2", "--module", "coin", "--function", "join",
+            "--type-args", "0x2::sui::SUI", "--args", "0xa", "0xb", "--gas-budget", "5000",
+        ]));
+
+        let operation = Operation::parse_command(CALL).unwrap();
+        assert_eq!(operation.sui_args().unwrap(), strings(&[
+            "client", "call", "--json", "--package", "0x2", "--module", "coin", "--function", "join",
+            "--gas-budget", &DEFAULT_GAS_BUDGET.to_string(),
+        ]));
+    }
+
+    #[test]
+    fn parses_test_without_sui_args() {
+        let workspace = Workspace::new(true);
+        let command = format!("sui move test test_mint --path {}", workspace.path());
+        let Operation::Test(test) = Operation::parse_command(&command).unwrap() else {
+            panic!("应解析为测试操作");
+        };
+        assert_eq!(test.filter.as_deref(), Some("test_mint"));
+        assert_eq!(test.package_path, workspace.path());
+
+        let operation = Operation::Test(test);
+        assert_eq!(operation.sui_args().unwrap_err().code, ErrorCode::OperationNotAllowed);
+        let command = format!("sui move test --path {} --coverage", workspace.path());
+        assert_eq!(parse_error(&command), ErrorCode::OperationNotAllowed);
+    }
+}
@@ -0,0 +1,208 @@
+// Copyright (c) Move Web Compiler
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use anyhow::{Result, anyhow};
+use walkdir::WalkDir;
+
+/// 允许提交源文件的包内顶层目录
+pub const PACKAGE_SOURCE_DIRS: &[&str] = &["sources", "tests", "examples"];
+
+/// 单个包允许提交的最大文件数量
+pub const MAX_PACKAGE_FILES: usize = 64;
+
+/// 校验包内相对路径，返回可以安全拼接到包目录下的路径
+///
+/// 路径必须位于`sources/`、`tests/`或`examples/`之下，以`.move`结尾，
+/// 且不能包含`..`、根目录或盘符等会逃逸出包目录的部分。
+pub fn validate_relative_path(path: &str) -> Result<PathBuf> {
+    let normalized = path.trim().replace('\\', "/");
+    if normalized.is_empty() {
+        return Err(anyhow!("文件路径不能为空"));
+    }
+
+    let mut relative = PathBuf::new();
+    for component in Path::new(&normalized).components() {
+        match component {
+            Component::Normal(part) => relative.push(part),
+            Component::CurDir => {}
+            _ => return Err(anyhow!("非法的文件路径: {}", path)),
+        }
+    }
+
+    let top_dir = relative
+        .components()
+        .next()
+        .and_then(|c| c.as_os_str().to_str())
+        .unwrap_or_default()
+        .to_string();
+    if !PACKAGE_SOURCE_DIRS.contains(&top_dir.as_str()) || relative.components().count() < 2 {
+        return Err(anyhow!(
+            "文件 {} 必须位于 {} 目录之下",
+            path,
+            PACKAGE_SOURCE_DIRS.join("/、")
+        ));
+    }
+
+    if relative.extension().and_then(|e| e.to_str()) != Some("move") {
+        return Err(anyhow!("文件 {} 不是.move源文件", path));
+    }
+
+    Ok(relative)
+}
+
+/// 将单文件请求的文件名转换为包内相对路径，未指定目录时放入`sources/`
+pub fn source_file_path(file_name: &str) -> String {
+    let normalized = file_name.trim().replace('\\', "/");
+    let has_package_dir = PACKAGE_SOURCE_DIRS
+        .iter()
+        .any(|dir| normalized.starts_with(&format!("{}/", dir)));
+    if has_package_dir {
+        normalized
+    } else {
+        format!("sources/{}", normalized)
+    }
+}
+
+/// 将源文件集合写入包目录，返回写入的相对路径
+pub fn write_package_files(package_path: &Path, files: &BTreeMap<String, String>) -> Result<Vec<PathBuf>> {
+    if files.is_empty() {
+        return Err(anyhow!("没有提供任何源文件"));
+    }
+    if files.len() > MAX_PACKAGE_FILES {
+        return Err(anyhow!("源文件数量超过上限 {}", MAX_PACKAGE_FILES));
+    }
+
+    // 先整体校验，避免写入一半后才发现非法路径
+    let mut validated = Vec::with_capacity(files.len());
+    for (path, content) in files {
+        validated.push((validate_relative_path(path)?, content));
+    }
+
+    let mut written = Vec::with_capacity(validated.len());
+    for (relative, content) in validated {
+        let target = package_path.join(&relative);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&target, content)?;
+        written.push(relative);
+    }
+
+    Ok(written)
+}
+
+/// 从本地包目录中收集`sources/`、`tests/`、`examples/`下的所有Move源文件
+pub fn collect_package_files(package_path: &Path) -> Result<BTreeMap<String, String>> {
+    let mut files = BTreeMap::new();
+    for dir in PACKAGE_SOURCE_DIRS {
+        let root = package_path.join(dir);
+        if !root.is_dir() {
+            continue;
+        }
+        for entry in WalkDir::new(&root).into_iter().filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if !entry.file_type().is_file() || path.extension().and_then(|e| e.to_str()) != Some("move") {
+                continue;
+            }
+            let relative = path
+                .strip_prefix(package_path)?
+                .to_string_lossy()
+                .replace('\\', "/");
+            files.insert(relative, std::fs::read_to_string(path)?);
+        }
+    }
+
+    if files.is_empty() {
+        return Err(anyhow!("目录 {:?} 中没有找到Move源文件", package_path));
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rejects(path: &str) -> bool {
+        validate_relative_path(path).is_err()
+    }
+
+    #[test]
+    fn accepts_package_source_paths() {
+        assert_eq!(validate_relative_path("sources/hello.move").unwrap(), PathBuf::from("sources/hello.move"));
+        assert_eq!(validate_relative_path(" ./tests/a/b.move ").unwrap(), PathBuf::from("tests/a/b.move"));
+        assert_eq!(validate_relative_path("examples\\demo.move").unwrap(), PathBuf::from("examples/demo.move"));
+    }
+
+    #[test]
+    fn rejects_parent_directories() {
+        assert!(rejects("../x.move"));
+        assert!(rejects("sources/../../x.move"));
+        assert!(rejects("sources/../x.move"));
+        assert!(rejects("sources\\..\\..\\x.move"));
+    }
+
+    #[test]
+    fn rejects_absolute_paths() {
+        assert!(rejects("/abs.move"));
+        assert!(rejects("/sources/abs.move"));
+        assert!(rejects("C:\\sources\\x.move"));
+        assert!(rejects("C:sources/x.move"));
+        assert!(rejects("\\\\server\\share\\sources\\x.move"));
+    }
+
+    #[test]
+    fn rejects_paths_outside_source_dirs() {
+        assert!(rejects("Move.toml.move"));
+        assert!(rejects("build/hello.move"));
+        assert!(rejects("src/hello.move"));
+        assert!(rejects("sources"));
+    }
+
+    #[test]
+    fn rejects_other_extensions() {
+        assert!(rejects("sources/Move.toml"));
+        assert!(rejects("sources/hello.move.txt"));
+        assert!(rejects("sources/hello"));
+    }
+
+    #[test]
+    fn rejects_empty_paths() {
+        assert!(rejects(""));
+        assert!(rejects("   "));
+        assert!(rejects("./"));
+    }
+
+    #[test]
+    fn limits_file_count() {
+        let package_path = std::env::temp_dir().join(format!("move-package-test-{:016x}", rand::random::<u64>()));
+        let files: BTreeMap<String, String> = (0..=MAX_PACKAGE_FILES)
+            .map(|index| (format!("sources/m{}.move", index), String::new()))
+            .collect();
+        assert!(write_package_files(&package_path, &files).is_err());
+        assert!(!package_path.exists());
+    }
+
+    #[test]
+    fn writes_nothing_when_any_path_is_invalid() {
+        let package_path = std::env::temp_dir().join(format!("move-package-test-{:016x}", rand::random::<u64>()));
+        let mut files = BTreeMap::new();
+        files.insert("sources/a.move".to_string(), "module 0x1::a {}".to_string());
+        files.insert("sources/../../b.move".to_string(), String::new());
+        assert!(write_package_files(&package_path, &files).is_err());
+        assert!(!package_path.exists());
+
+        files.remove("sources/../../b.move");
+        let written = write_package_files(&package_path, &files).unwrap();
+        assert_eq!(written, [PathBuf::from("sources/a.move")]);
+        assert_eq!(std::fs::read_to_string(package_path.join("sources/a.move")).unwrap(), "module 0x1::a {}");
+        std::fs::remove_dir_all(&package_path).unwrap();
+    }
+
+    #[test]
+    fn places_single_files_in_sources() {
+        assert_eq!(source_file_path("hello.move"), "sources/hello.move");
+        assert_eq!(source_file_path("tests\\t.move"), "tests/t.move");
+    }
+}
@@ -0,0 +1,703 @@
+// Copyright (c) Move Web Compiler
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use fastcrypto::traits::ToFromBytes;
+use move_core_types::language_storage::StructTag;
+use serde::{Deserialize, Serialize};
+use sui_sdk::rpc_types::{
+    ObjectChange, OwnedObjectRef, SuiExecutionStatus, SuiObjectData, SuiObjectDataOptions, SuiRawData,
+    SuiTransactionBlockEffects, SuiTransactionBlockEffectsAPI, SuiTransactionBlockResponse,
+    SuiTransactionBlockResponseOptions,
+};
+use sui_sdk::{SuiClient, SuiClientBuilder};
+use sui_types::SUI_FRAMEWORK_ADDRESS;
+use sui_types::base_types::{ObjectID, ObjectRef};
+use sui_types::move_package::UpgradeCap;
+use sui_types::gas::GasCostSummary;
+use sui_types::quorum_driver_types::ExecuteTransactionRequestType;
+use sui_types::signature::GenericSignature;
+use sui_types::transaction::{Transaction, TransactionData};
+use tokio::sync::OnceCell;
+
+use crate::operations::{ErrorCode, OperationError};
+
+/// 默认的Sui全节点RPC地址
+pub const DEFAULT_RPC_URL: &str = "https://fullnode.testnet.sui.io:443";
+
+/// 默认的RPC请求超时（秒）
+pub const DEFAULT_RPC_TIMEOUT_SECS: u64 = 60;
+
+/// 单笔交易最多的签名数：发送方和gas赞助方各一个
+pub const MAX_TX_SIGNATURES: usize = 2;
+
+/// Sui全节点RPC配置
+#[derive(Clone, Debug)]
+pub struct RpcConfig {
+    /// 全节点JSON-RPC地址
+    pub url: String,
+    /// 单次请求的超时时间
+    pub request_timeout: Duration,
+}
+
+impl Default for RpcConfig {
+    fn default() -> Self {
+        Self {
+            url: DEFAULT_RPC_URL.to_string(),
+            request_timeout: Duration::from_secs(DEFAULT_RPC_TIMEOUT_SECS),
+        }
+    }
+}
+
+impl RpcConfig {
+    /// 从环境变量读取配置：`MOVE_SUI_RPC_URL`、`MOVE_SUI_RPC_TIMEOUT_SECS`
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Ok(url) = std::env::var("MOVE_SUI_RPC_URL") {
+            if !url.trim().is_empty() {
+                config.url = url.trim().to_string();
+            }
+        }
+        if let Some(timeout) = std::env::var("MOVE_SUI_RPC_TIMEOUT_SECS").ok().and_then(|v| v.trim().parse::<u64>().ok()) {
+            config.request_timeout = Duration::from_secs(timeout.max(1));
+        }
+        config
+    }
+}
+
+/// 已签名的交易
+#[derive(Clone, Debug, Deserialize)]
+pub struct ExecuteRequest {
+    /// BCS序列化的`TransactionData`（Base64），即构建接口返回的`tx_bytes`
+    pub tx_bytes: String,
+    /// 钱包返回的签名（Base64，`flag || signature || public key`），赞助交易时包括赞助方的签名
+    pub signatures: Vec<String>,
+}
+
+impl ExecuteRequest {
+    /// 解析交易数据和签名
+    pub fn transaction(&self) -> Result<Transaction, OperationError> {
+        let bytes = BASE64_STANDARD
+            .decode(self.tx_bytes.trim())
+            .map_err(|e| invalid_argument(format!("无效的Base64交易数据: {}", e)))?;
+        let data: TransactionData =
+            bcs::from_bytes(&bytes).map_err(|e| invalid_argument(format!("无法解析交易数据: {}", e)))?;
+
+        if self.signatures.is_empty() || self.signatures.len() > MAX_TX_SIGNATURES {
+            return Err(invalid_argument(format!("签名数量必须在1到{}之间", MAX_TX_SIGNATURES)));
+        }
+        let signatures = self
+            .signatures
+            .iter()
+            .map(|signature| {
+                let bytes = BASE64_STANDARD
+                    .decode(signature.trim())
+                    .map_err(|e| invalid_argument(format!("无效的Base64签名: {}", e)))?;
+                GenericSignature::from_bytes(&bytes).map_err(|e| invalid_argument(format!("无法解析签名: {}", e)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Transaction::from_generic_sig_data(data, signatures))
+    }
+}
+
+/// 交易中创建或修改的对象
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ObjectSummary {
+    /// 对象ID
+    pub object_id: String,
+    /// 对象类型，例如`0x2::coin::Coin<0x2::sui::SUI>`，只有效果信息时为空
+    pub object_type: Option<String>,
+    /// 所有者，例如`{"AddressOwner": "0x..."}`或`"Immutable"`
+    pub owner: Option<serde_json::Value>,
+    /// 交易后的版本
+    pub version: u64,
+}
+
+/// 交易产生的事件
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EventSummary {
+    /// 事件类型
+    pub event_type: String,
+    /// 发出事件的包ID
+    pub package_id: String,
+    /// 发出事件的模块
+    pub module: String,
+    /// 交易发送方
+    pub sender: String,
+    /// 事件内容
+    pub parsed_json: serde_json::Value,
+}
+
+/// gas费用明细（MIST）
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct GasSummary {
+    /// 计算费用
+    pub computation_cost: u64,
+    /// 存储费用
+    pub storage_cost: u64,
+    /// 存储退款
+    pub storage_rebate: u64,
+    /// 不退还的存储费用
+    pub non_refundable_storage_fee: u64,
+    /// 实际花费：计算费用 + 存储费用 - 存储退款
+    pub total: i64,
+}
+
+impl From<&GasCostSummary> for GasSummary {
+    fn from(gas: &GasCostSummary) -> Self {
+        Self {
+            computation_cost: gas.computation_cost,
+            storage_cost: gas.storage_cost,
+            storage_rebate: gas.storage_rebate,
+            non_refundable_storage_fee: gas.non_refundable_storage_fee,
+            total: gas.net_gas_usage(),
+        }
+    }
+}
+
+/// 交易执行结果
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TransactionSummary {
+    /// 交易摘要
+    pub digest: String,
+    /// 执行状态：`success`或`failure`
+    pub status: String,
+    /// 执行失败的原因
+    pub execution_error: Option<String>,
+    /// 发布交易中新包的ID
+    pub package_id: Option<String>,
+    /// 发布交易中创建的`UpgradeCap`的ID
+    pub upgrade_cap_id: Option<String>,
+    /// 创建的对象
+    pub created: Vec<ObjectSummary>,
+    /// 修改的对象（包括gas币）
+    pub mutated: Vec<ObjectSummary>,
+    /// 删除的对象ID
+    pub deleted: Vec<String>,
+    /// 被包装进其他对象的对象ID，对象仍然存在，只是不能再直接访问
+    #[serde(default)]
+    pub wrapped: Vec<String>,
+    /// 交易产生的事件
+    pub events: Vec<EventSummary>,
+    /// gas费用
+    pub gas: GasSummary,
+    /// 节点返回的完整效果
+    pub effects: Option<serde_json::Value>,
+}
+
+impl TransactionSummary {
+    /// 执行是否成功
+    pub fn is_success(&self) -> bool {
+        self.status == "success"
+    }
+
+    /// 从节点的交易响应中提取结果
+    ///
+    /// 有对象变更信息时从中读取对象类型和所有者，否则退回到效果中的对象引用。
+    pub fn from_response(response: &SuiTransactionBlockResponse) -> Self {
+        let mut summary = Self {
+            digest: response.digest.to_string(),
+            status: "success".to_string(),
+            execution_error: None,
+            package_id: None,
+            upgrade_cap_id: None,
+            created: vec![],
+            mutated: vec![],
+            deleted: vec![],
+            wrapped: vec![],
+            events: vec![],
+            gas: GasSummary::default(),
+            effects: None,
+        };
+
+        if let Some(effects) = &response.effects {
+            if let SuiExecutionStatus::Failure { error } = effects.status() {
+                summary.status = "failure".to_string();
+                summary.execution_error = Some(error.clone());
+            }
+            summary.gas = GasSummary::from(effects.gas_cost_summary());
+            summary.effects = serde_json::to_value(effects).ok();
+        }
+
+        match &response.object_changes {
+            Some(changes) => summary.add_object_changes(changes),
+            None => {
+                if let Some(effects) = &response.effects {
+                    summary.add_effect_objects(effects);
+                }
+            }
+        }
+
+        if let Some(events) = &response.events {
+            summary.events = events
+                .data
+                .iter()
+                .map(|event| EventSummary {
+                    event_type: event.type_.to_string(),
+                    package_id: event.package_id.to_string(),
+                    module: event.transaction_module.to_string(),
+                    sender: event.sender.to_string(),
+                    parsed_json: event.parsed_json.clone(),
+                })
+                .collect();
+        }
+        summary
+    }
+
+    /// 从`sui client publish/call --json`的输出解析
+    ///
+    /// 输出前可能有依赖更新、构建进度、编译警告等非JSON内容，其中也可能出现`{`。
+    /// CLI输出的JSON从行首开始，依次尝试从每个以`{`开头的行解析交易响应，忽略JSON之后的内容。
+    pub fn from_cli_output(stdout: &str) -> Result<Self, serde_json::Error> {
+        let line_starts = stdout
+            .match_indices('{')
+            .map(|(start, _)| start)
+            .filter(|&start| start == 0 || stdout[..start].ends_with('\n'));
+
+        let mut error = None;
+        for start in line_starts {
+            let mut responses = serde_json::Deserializer::from_str(&stdout[start..]).into_iter::<SuiTransactionBlockResponse>();
+            match responses.next() {
+                Some(Ok(response)) => return Ok(Self::from_response(&response)),
+                Some(Err(e)) => error = Some(e),
+                None => {}
+            }
+        }
+        match error {
+            Some(e) => Err(e),
+            None => serde_json::from_str::<SuiTransactionBlockResponse>(stdout.trim()).map(|response| Self::from_response(&response)),
+        }
+    }
+
+    fn add_object_changes(&mut self, changes: &[ObjectChange]) {
+        for change in changes {
+            match change {
+                ObjectChange::Published { package_id, .. } => {
+                    self.package_id = Some(package_id.to_string());
+                }
+                ObjectChange::Created { owner, object_type, object_id, version, .. } => {
+                    if is_upgrade_cap(object_type) {
+                        self.upgrade_cap_id = Some(object_id.to_string());
+                    }
+                    self.created.push(ObjectSummary {
+                        object_id: object_id.to_string(),
+                        object_type: Some(object_type.to_string()),
+                        owner: serde_json::to_value(owner).ok(),
+                        version: version.value(),
+                    });
+                }
+                ObjectChange::Mutated { owner, object_type, object_id, version, .. } => {
+                    self.mutated.push(ObjectSummary {
+                        object_id: object_id.to_string(),
+                        object_type: Some(object_type.to_string()),
+                        owner: serde_json::to_value(owner).ok(),
+                        version: version.value(),
+                    });
+                }
+                ObjectChange::Transferred { recipient, object_type, object_id, version, .. } => {
+                    self.mutated.push(ObjectSummary {
+                        object_id: object_id.to_string(),
+                        object_type: Some(object_type.to_string()),
+                        owner: serde_json::to_value(recipient).ok(),
+                        version: version.value(),
+                    });
+                }
+                ObjectChange::Deleted { object_id, .. } => {
+                    self.deleted.push(object_id.to_string());
+                }
+                ObjectChange::Wrapped { object_id, .. } => {
+                    self.wrapped.push(object_id.to_string());
+                }
+            }
+        }
+    }
+
+    fn add_effect_objects(&mut self, effects: &SuiTransactionBlockEffects) {
+        let summarize = |object: &OwnedObjectRef| ObjectSummary {
+            object_id: object.object_id().to_string(),
+            object_type: None,
+            owner: serde_json::to_value(&object.owner).ok(),
+            version: object.reference.version.value(),
+        };
+        self.created = effects.created().iter().map(summarize).collect();
+        self.mutated = effects.mutated().iter().map(summarize).collect();
+        self.deleted = effects.deleted().iter().map(|object| object.object_id.to_string()).collect();
+        self.wrapped = effects.wrapped().iter().map(|object| object.object_id.to_string()).collect();
+    }
+}
+
+/// Sui全节点客户端，首次使用时连接
+pub struct SuiRpc {
+    config: RpcConfig,
+    client: OnceCell<SuiClient>,
+}
+
+impl SuiRpc {
+    /// 按配置创建，不立即连接节点
+    pub fn new(config: RpcConfig) -> Self {
+        Self {
+            config,
+            client: OnceCell::new(),
+        }
+    }
+
+    /// RPC配置
+    pub fn config(&self) -> &RpcConfig {
+        &self.config
+    }
+
+    /// 连接节点，连接失败时下次请求重试
+    pub async fn client(&self) -> Result<&SuiClient, OperationError> {
+        self.client
+            .get_or_try_init(|| async {
+                SuiClientBuilder::default()
+                    .request_timeout(self.config.request_timeout)
+                    .build(&self.config.url)
+                    .await
+                    .map_err(|e| rpc_error(format!("无法连接Sui节点 {}: {}", self.config.url, e)))
+            })
+            .await
+    }
+
+    /// 提交已签名的交易并等待执行完成
+    pub async fn execute(&self, request: &ExecuteRequest) -> Result<TransactionSummary, OperationError> {
+        let transaction = request.transaction()?;
+        let client = self.client().await?;
+        let options = SuiTransactionBlockResponseOptions::new()
+            .with_effects()
+            .with_events()
+            .with_object_changes();
+        let response = client
+            .quorum_driver_api()
+            .execute_transaction_block(transaction, options, Some(ExecuteTransactionRequestType::WaitForLocalExecution))
+            .await
+            .map_err(|e| rpc_error(format!("提交交易失败: {}", e)))?;
+        Ok(TransactionSummary::from_response(&response))
+    }
+
+    /// 读取对象的BCS内容
+    pub async fn get_object(&self, id: ObjectID) -> Result<SuiObjectData, OperationError> {
+        let client = self.client().await?;
+        let response = client
+            .read_api()
+            .get_object_with_options(id, SuiObjectDataOptions::bcs_lossless())
+            .await
+            .map_err(|e| rpc_error(format!("读取对象 {} 失败: {}", id, e)))?;
+        response
+            .data
+            .ok_or_else(|| invalid_argument(format!("对象不存在: {}", id)))
+    }
+
+    /// 读取链上包的全部模块字节码，按模块名排序
+    pub async fn get_package_modules(&self, package_id: ObjectID) -> Result<Vec<Vec<u8>>, OperationError> {
+        match self.get_object(package_id).await?.bcs {
+            Some(SuiRawData::Package(package)) => Ok(package.module_map.into_values().collect()),
+            _ => Err(invalid_argument(format!("{} 不是Move包", package_id))),
+        }
+    }
+
+    /// 读取`UpgradeCap`的对象引用和内容
+    pub async fn get_upgrade_cap(&self, cap_id: ObjectID) -> Result<(ObjectRef, UpgradeCap), OperationError> {
+        let object = self.get_object(cap_id).await?;
+        let not_upgrade_cap = || invalid_argument(format!("{} 不是UpgradeCap", cap_id));
+        match &object.bcs {
+            Some(SuiRawData::MoveObject(raw)) if is_upgrade_cap(&raw.type_) => {
+                let cap: UpgradeCap = bcs::from_bytes(&raw.bcs_bytes).map_err(|_| not_upgrade_cap())?;
+                Ok((object.object_ref(), cap))
+            }
+            _ => Err(not_upgrade_cap()),
+        }
+    }
+}
+
+// 是否为发布时创建的`0x2::package::UpgradeCap`
+fn is_upgrade_cap(object_type: &StructTag) -> bool {
+    object_type.address == SUI_FRAMEWORK_ADDRESS
+        && object_type.module.as_str() == "package"
+        && object_type.name.as_str() == "UpgradeCap"
+}
+
+fn invalid_argument(message: impl Into<String>) -> OperationError {
+    OperationError::new(ErrorCode::InvalidArgument, message)
+}
+
+fn rpc_error(message: impl Into<String>) -> OperationError {
+    OperationError::new(ErrorCode::RpcError, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use serde_json::{Value, json};
+    use sui_types::base_types::{SuiAddress, random_object_ref};
+    use sui_types::crypto::{AccountKeyPair, get_key_pair};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    // 已签名的转账交易及其摘要
+    fn signed_transfer() -> (ExecuteRequest, String, SuiAddress) {
+        let (sender, keypair): (SuiAddress, AccountKeyPair) = get_key_pair();
+        let data = TransactionData::new_transfer_sui(sender, sender, Some(1), random_object_ref(), 10_000_000, 1_000);
+        let transaction = Transaction::from_data_and_signer(data.clone(), vec![&keypair]);
+        let request = ExecuteRequest {
+            tx_bytes: BASE64_STANDARD.encode(bcs::to_bytes(&data).unwrap()),
+            signatures: transaction
+                .tx_signatures()
+                .iter()
+                .map(|signature| BASE64_STANDARD.encode(signature.as_ref()))
+                .collect(),
+        };
+        (request, transaction.digest().to_string(), sender)
+    }
+
+    // 节点对sui_executeTransactionBlock的响应
+    fn execute_response(digest: &str, sender: SuiAddress, status: Value) -> Value {
+        let gas_object = random_object_ref();
+        let package = random_object_ref();
+        let upgrade_cap = random_object_ref();
+        json!({
+            "digest": digest,
+            "effects": {
+                "messageVersion": "v1",
+                "status": status,
+                "executedEpoch": "0",
+                "gasUsed": {
+                    "computationCost": "1000000",
+                    "storageCost": "5000000",
+                    "storageRebate": "978120",
+                    "nonRefundableStorageFee": "9880"
+                },
+                "transactionDigest": digest,
+                "gasObject": {
+                    "owner": { "AddressOwner": sender.to_string() },
+                    "reference": {
+                        "objectId": gas_object.0.to_string(),
+                        "version": 2,
+                        "digest": gas_object.2.to_string()
+                    }
+                }
+            },
+            "objectChanges": [
+                {
+                    "type": "published",
+                    "packageId": package.0.to_string(),
+                    "version": "1",
+                    "digest": package.2.to_string(),
+                    "modules": ["hello"]
+                },
+                {
+                    "type": "created",
+                    "sender": sender.to_string(),
+                    "owner": { "AddressOwner": sender.to_string() },
+                    "objectType": "0x2::package::UpgradeCap",
+                    "objectId": upgrade_cap.0.to_string(),
+                    "version": "2",
+                    "digest": upgrade_cap.2.to_string()
+                }
+            ],
+            "events": [],
+            "confirmedLocalExecution": true
+        })
+    }
+
+    // 本地JSON-RPC桩：`rpc.discover`返回支持的方法，交易相关的方法返回`result`，并记录收到的方法名
+    async fn start_mock_rpc(result: Value) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let methods = Arc::new(Mutex::new(Vec::new()));
+        let received = methods.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve_connection(stream, result.clone(), received.clone()));
+            }
+        });
+        (url, methods)
+    }
+
+    async fn serve_connection(mut stream: TcpStream, result: Value, methods: Arc<Mutex<Vec<String>>>) {
+        let mut buffer = Vec::new();
+        loop {
+            let header_end = loop {
+                if let Some(position) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+                    break position + 4;
+                }
+                if !read_more(&mut stream, &mut buffer).await {
+                    return;
+                }
+            };
+            let headers = String::from_utf8_lossy(&buffer[..header_end]).to_ascii_lowercase();
+            let length = headers
+                .lines()
+                .find_map(|line| line.strip_prefix("content-length:"))
+                .and_then(|value| value.trim().parse::<usize>().ok())
+                .unwrap_or(0);
+            while buffer.len() < header_end + length {
+                if !read_more(&mut stream, &mut buffer).await {
+                    return;
+                }
+            }
+            let request: Value = serde_json::from_slice(&buffer[header_end..header_end + length]).unwrap();
+            buffer.drain(..header_end + length);
+
+            let method = request["method"].as_str().unwrap_or_default().to_string();
+            methods.lock().unwrap().push(method.clone());
+            let reply = match method.as_str() {
+                "rpc.discover" => json!({
+                    "jsonrpc": "2.0",
+                    "id": request["id"],
+                    "result": {
+                        "info": { "version": "1.30.0" },
+                        "methods": [{ "name": "sui_executeTransactionBlock" }, { "name": "sui_getTransactionBlock" }]
+                    }
+                }),
+                "sui_executeTransactionBlock" | "sui_getTransactionBlock" => {
+                    json!({ "jsonrpc": "2.0", "id": request["id"], "result": result })
+                }
+                _ => json!({
+                    "jsonrpc": "2.0",
+                    "id": request["id"],
+                    "error": { "code": -32601, "message": "Method not found" }
+                }),
+            };
+            let body = reply.to_string();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            if stream.write_all(response.as_bytes()).await.is_err() {
+                return;
+            }
+        }
+    }
+
+    async fn read_more(stream: &mut TcpStream, buffer: &mut Vec<u8>) -> bool {
+        let mut chunk = [0u8; 4096];
+        match stream.read(&mut chunk).await {
+            Ok(0) | Err(_) => false,
+            Ok(n) => {
+                buffer.extend_from_slice(&chunk[..n]);
+                true
+            }
+        }
+    }
+
+    fn mock_client(url: String) -> SuiRpc {
+        SuiRpc::new(RpcConfig {
+            url,
+            request_timeout: Duration::from_secs(10),
+        })
+    }
+
+    #[tokio::test]
+    async fn execute_reports_successful_effects() {
+        let (request, digest, sender) = signed_transfer();
+        let response = execute_response(&digest, sender, json!({ "status": "success" }));
+        let (url, methods) = start_mock_rpc(response.clone()).await;
+
+        let summary = mock_client(url).execute(&request).await.unwrap();
+
+        assert!(methods.lock().unwrap().contains(&"sui_executeTransactionBlock".to_string()));
+        assert!(summary.is_success());
+        assert_eq!(summary.digest, digest);
+        assert_eq!(summary.execution_error, None);
+        assert_eq!(summary.package_id.as_deref(), response["objectChanges"][0]["packageId"].as_str());
+        assert_eq!(summary.upgrade_cap_id.as_deref(), response["objectChanges"][1]["objectId"].as_str());
+        assert_eq!(summary.created.len(), 1);
+        assert_eq!(summary.gas.computation_cost, 1_000_000);
+        assert_eq!(summary.gas.total, 1_000_000 + 5_000_000 - 978_120);
+    }
+
+    #[tokio::test]
+    async fn execute_reports_failed_effects() {
+        let (request, digest, sender) = signed_transfer();
+        let error = "MoveAbort(MoveLocation { module: 0x2::coin, function: 1, instruction: 5 }, 2) in command 0";
+        let mut response = execute_response(&digest, sender, json!({ "status": "failure", "error": error }));
+        response["objectChanges"] = json!([]);
+        let (url, _) = start_mock_rpc(response).await;
+
+        let summary = mock_client(url).execute(&request).await.unwrap();
+
+        assert!(!summary.is_success());
+        assert_eq!(summary.status, "failure");
+        assert_eq!(summary.execution_error.as_deref(), Some(error));
+        assert_eq!(summary.package_id, None);
+        assert!(summary.created.is_empty());
+        assert_eq!(summary.gas.storage_rebate, 978_120);
+    }
+
+    #[test]
+    fn cli_output_skips_build_progress_and_warnings() {
+        let (_, digest, sender) = signed_transfer();
+        let response = execute_response(&digest, sender, json!({ "status": "success" }));
+        let stdout = format!(
+            "UPDATING GIT DEPENDENCY https://github.com/MystenLabs/sui.git\n\
+             INCLUDING DEPENDENCY Sui\n\
+             BUILDING hello\n\
+             warning[W09002]: unused variable\n\
+             {{ let x = 1; }}\n\
+             {}\n\
+             Transaction Digest: {}\n",
+            serde_json::to_string_pretty(&response).unwrap(),
+            digest
+        );
+
+        let summary = TransactionSummary::from_cli_output(&stdout).unwrap();
+
+        assert!(summary.is_success());
+        assert_eq!(summary.digest, digest);
+        assert_eq!(summary.package_id.as_deref(), response["objectChanges"][0]["packageId"].as_str());
+        assert_eq!(summary.upgrade_cap_id.as_deref(), response["objectChanges"][1]["objectId"].as_str());
+    }
+
+    #[test]
+    fn cli_output_reports_wrapped_objects_separately() {
+        let (_, digest, sender) = signed_transfer();
+        let wrapped = random_object_ref();
+        let deleted = random_object_ref();
+        let mut response = execute_response(&digest, sender, json!({ "status": "success" }));
+        response["objectChanges"] = json!([
+            {
+                "type": "wrapped",
+                "sender": sender.to_string(),
+                "objectType": "0x0::hello::Ticket",
+                "objectId": wrapped.0.to_string(),
+                "version": "3"
+            },
+            {
+                "type": "deleted",
+                "sender": sender.to_string(),
+                "objectType": "0x0::hello::Receipt",
+                "objectId": deleted.0.to_string(),
+                "version": "3"
+            }
+        ]);
+
+        let summary = TransactionSummary::from_cli_output(&serde_json::to_string_pretty(&response).unwrap()).unwrap();
+
+        assert_eq!(summary.wrapped, vec![wrapped.0.to_string()]);
+        assert_eq!(summary.deleted, vec![deleted.0.to_string()]);
+    }
+
+    #[test]
+    fn cli_output_without_json_is_an_error() {
+        assert!(TransactionSummary::from_cli_output("Error executing transaction: InsufficientGas\n").is_err());
+        assert!(TransactionSummary::from_cli_output("BUILDING hello\n{ not json }\n").is_err());
+    }
+
+    #[tokio::test]
+    async fn execute_rejects_invalid_signatures_before_contacting_the_node() {
+        let (mut request, _, _) = signed_transfer();
+        request.signatures = vec!["not base64!".to_string()];
+        let (url, methods) = start_mock_rpc(Value::Null).await;
+
+        let error = mock_client(url).execute(&request).await.unwrap_err();
+
+        assert_eq!(error.code, ErrorCode::InvalidArgument);
+        assert!(methods.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,230 @@
+// Copyright (c) Move Web Compiler
+// SPDX-License-Identifier: Apache-2.0
+
+use std::fmt;
+use std::io::Read;
+use std::process::{Child, Output};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use anyhow::{Result, anyhow};
+use tokio::sync::Semaphore;
+
+/// 默认的构建超时（秒）
+pub const DEFAULT_BUILD_TIMEOUT_SECS: u64 = 120;
+
+/// 默认的等待队列长度
+pub const DEFAULT_BUILD_QUEUE: usize = 16;
+
+/// 默认建议客户端重试的等待时间（秒）
+pub const DEFAULT_RETRY_AFTER_SECS: u64 = 5;
+
+// 等待子进程退出时的轮询间隔
+const CHILD_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// 构建调度配置
+#[derive(Clone, Debug)]
+pub struct SchedulerConfig {
+    /// 同时进行的最大构建数
+    pub max_concurrent: usize,
+    /// 等待执行的最大构建数，超出后直接拒绝
+    pub max_queue: usize,
+    /// 单次构建的超时时间，包括排队时间
+    pub build_timeout: Duration,
+    /// 拒绝请求时建议客户端等待的时间（`Retry-After`）
+    pub retry_after: Duration,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent: std::thread::available_parallelism().map_or(2, |n| n.get()),
+            max_queue: DEFAULT_BUILD_QUEUE,
+            build_timeout: Duration::from_secs(DEFAULT_BUILD_TIMEOUT_SECS),
+            retry_after: Duration::from_secs(DEFAULT_RETRY_AFTER_SECS),
+        }
+    }
+}
+
+impl SchedulerConfig {
+    /// 从环境变量读取配置：
+    /// `MOVE_BUILD_CONCURRENCY`、`MOVE_BUILD_QUEUE`、`MOVE_BUILD_TIMEOUT_SECS`、`MOVE_BUILD_RETRY_AFTER_SECS`
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Some(concurrency) = env_parse::<usize>("MOVE_BUILD_CONCURRENCY") {
+            config.max_concurrent = concurrency.max(1);
+        }
+        if let Some(queue) = env_parse("MOVE_BUILD_QUEUE") {
+            config.max_queue = queue;
+        }
+        if let Some(timeout) = env_parse::<u64>("MOVE_BUILD_TIMEOUT_SECS") {
+            config.build_timeout = Duration::from_secs(timeout.max(1));
+        }
+        if let Some(retry_after) = env_parse("MOVE_BUILD_RETRY_AFTER_SECS") {
+            config.retry_after = Duration::from_secs(retry_after);
+        }
+        config
+    }
+}
+
+/// 调度失败的原因
+#[derive(Clone, Debug)]
+pub enum ScheduleError {
+    /// 正在执行和排队的构建都已满
+    Busy { retry_after: Duration },
+    /// 构建超时
+    TimedOut { timeout: Duration, retry_after: Duration },
+    /// 构建线程异常退出
+    Failed(String),
+}
+
+impl ScheduleError {
+    /// 建议客户端重试的等待时间
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            ScheduleError::Busy { retry_after } | ScheduleError::TimedOut { retry_after, .. } => Some(*retry_after),
+            ScheduleError::Failed(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for ScheduleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScheduleError::Busy { .. } => write!(f, "服务器繁忙，构建队列已满，请稍后重试"),
+            ScheduleError::TimedOut { timeout, .. } => write!(f, "构建超时（{} 秒）", timeout.as_secs()),
+            ScheduleError::Failed(e) => write!(f, "构建线程异常: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ScheduleError {}
+
+/// 在阻塞线程池中执行构建，限制并发数和排队数
+///
+/// 编译和测试都是同步的CPU密集任务，直接在异步处理函数中执行会占用Web服务器的工作线程。
+#[derive(Clone, Debug)]
+pub struct BuildScheduler {
+    config: SchedulerConfig,
+    permits: Arc<Semaphore>,
+    // 已接受的构建数（执行中和排队中）
+    admitted: Arc<AtomicUsize>,
+}
+
+// 构建结束或请求被取消时释放名额
+struct Admission(Arc<AtomicUsize>);
+
+impl Drop for Admission {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl BuildScheduler {
+    /// 按配置创建调度器
+    pub fn new(config: SchedulerConfig) -> Self {
+        Self {
+            permits: Arc::new(Semaphore::new(config.max_concurrent)),
+            admitted: Arc::new(AtomicUsize::new(0)),
+            config,
+        }
+    }
+
+    /// 调度配置
+    pub fn config(&self) -> &SchedulerConfig {
+        &self.config
+    }
+
+    /// 执行中的构建数
+    pub fn running(&self) -> usize {
+        self.config.max_concurrent - self.permits.available_permits()
+    }
+
+    /// 排队中的构建数
+    pub fn queued(&self) -> usize {
+        self.admitted.load(Ordering::SeqCst).saturating_sub(self.running())
+    }
+
+    /// 排队等待空闲名额后在阻塞线程中执行`build`
+    ///
+    /// 超时后立即返回，构建线程无法被中断，会继续运行到结束并一直占用名额，
+    /// 因此超时的构建仍计入并发数，不会导致同时运行的构建超过上限。
+    pub async fn run<F, T>(&self, build: F) -> Result<T, ScheduleError>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let limit = self.config.max_concurrent + self.config.max_queue;
+        if self.admitted.fetch_add(1, Ordering::SeqCst) >= limit {
+            self.admitted.fetch_sub(1, Ordering::SeqCst);
+            return Err(ScheduleError::Busy {
+                retry_after: self.config.retry_after,
+            });
+        }
+        let admission = Admission(self.admitted.clone());
+
+        let timed_out = || ScheduleError::TimedOut {
+            timeout: self.config.build_timeout,
+            retry_after: self.config.retry_after,
+        };
+        let deadline = tokio::time::Instant::now() + self.config.build_timeout;
+
+        let permit = tokio::time::timeout_at(deadline, self.permits.clone().acquire_owned())
+            .await
+            .map_err(|_| timed_out())?
+            .map_err(|e| ScheduleError::Failed(e.to_string()))?;
+
+        let handle = tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            let _admission = admission;
+            build()
+        });
+
+        match tokio::time::timeout_at(deadline, handle).await {
+            Ok(Ok(output)) => Ok(output),
+            Ok(Err(e)) => Err(ScheduleError::Failed(e.to_string())),
+            Err(_) => Err(timed_out()),
+        }
+    }
+}
+
+/// 等待子进程退出并收集输出，超过`deadline`时终止并回收进程
+///
+/// 在构建线程中调用，截止时间与调度器的超时一致，超时的子进程不会继续占用构建名额。
+pub fn wait_with_deadline(mut child: Child, deadline: Option<Instant>) -> Result<Output> {
+    // 在单独的线程中读取输出，避免管道写满后子进程阻塞
+    let stdout = read_pipe(child.stdout.take());
+    let stderr = read_pipe(child.stderr.take());
+
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(anyhow!("运行超时，已终止进程"));
+        }
+        std::thread::sleep(CHILD_POLL_INTERVAL);
+    };
+
+    Ok(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
+
+fn read_pipe(pipe: Option<impl Read + Send + 'static>) -> std::thread::JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut buffer = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buffer);
+        }
+        buffer
+    })
+}
+
+fn env_parse<T: std::str::FromStr>(name: &str) -> Option<T> {
+    std::env::var(name).ok().and_then(|value| value.trim().parse().ok())
+}
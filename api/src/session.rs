@@ -0,0 +1,395 @@
+// Copyright (c) Move Web Compiler
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use serde::Deserialize;
+use sui_types::base_types::ObjectID;
+
+use crate::operations::{ErrorCode, OperationError};
+use crate::rpc::ObjectSummary;
+use crate::simulate::{LocalSimulator, ObjectDetail, SimulatedCall, SimulatedPackage, SimulationReport, SimulatorSnapshot};
+use crate::transaction;
+
+/// 默认的会话过期时间（秒），超过这段时间未使用的会话会被删除
+pub const DEFAULT_SESSION_TTL_SECS: u64 = 30 * 60;
+
+/// 默认的最大会话数
+pub const DEFAULT_MAX_SESSIONS: usize = 32;
+
+/// 默认每个会话最多保存的快照数
+pub const DEFAULT_MAX_SNAPSHOTS: usize = 16;
+
+/// 模拟会话配置
+#[derive(Clone, Debug)]
+pub struct SessionConfig {
+    /// 未使用的会话保留的时间
+    pub ttl: Duration,
+    /// 同时保留的最大会话数，超出后删除最久未使用的会话
+    pub max_sessions: usize,
+    /// 每个会话最多保存的快照数
+    pub max_snapshots: usize,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            ttl: Duration::from_secs(DEFAULT_SESSION_TTL_SECS),
+            max_sessions: DEFAULT_MAX_SESSIONS,
+            max_snapshots: DEFAULT_MAX_SNAPSHOTS,
+        }
+    }
+}
+
+impl SessionConfig {
+    /// 从环境变量读取配置：
+    /// `MOVE_SIMULATION_SESSION_TTL_SECS`、`MOVE_SIMULATION_MAX_SESSIONS`、`MOVE_SIMULATION_MAX_SNAPSHOTS`
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Some(ttl) = env_parse::<u64>("MOVE_SIMULATION_SESSION_TTL_SECS") {
+            config.ttl = Duration::from_secs(ttl.max(1));
+        }
+        if let Some(max_sessions) = env_parse::<usize>("MOVE_SIMULATION_MAX_SESSIONS") {
+            config.max_sessions = max_sessions.max(1);
+        }
+        if let Some(max_snapshots) = env_parse("MOVE_SIMULATION_MAX_SNAPSHOTS") {
+            config.max_snapshots = max_snapshots;
+        }
+        config
+    }
+}
+
+/// 在会话中执行函数的请求
+#[derive(Clone, Debug, Deserialize)]
+pub struct SessionCallRequest {
+    /// 要执行的函数，对象参数可以使用之前的调用创建的对象
+    #[serde(flatten)]
+    pub call: SimulatedCall,
+    /// gas预算（MIST），默认使用创建会话时的预算
+    pub gas_budget: Option<u64>,
+}
+
+/// 模拟会话：一条本地模拟链，多次调用之间保留对象
+///
+/// 创建时发布包，之后的调用都针对这个包。发布后的状态作为初始快照，
+/// 重置时恢复到这个状态。
+pub struct SimulationSession {
+    simulator: LocalSimulator,
+    package_id: ObjectID,
+    gas_budget: u64,
+    initial: SimulatorSnapshot,
+    snapshots: BTreeMap<String, SimulatorSnapshot>,
+    max_snapshots: usize,
+}
+
+impl SimulationSession {
+    /// 发布包并创建会话，发布失败时只返回发布交易的结果
+    pub fn create(
+        package: &SimulatedPackage,
+        max_snapshots: usize,
+    ) -> Result<(Option<Self>, SimulationReport), OperationError> {
+        let (simulator, publish) = LocalSimulator::with_package(package)?;
+        let package_id = match publish.package_id.as_deref() {
+            Some(package_id) if publish.is_success() => transaction::parse_object_id(package_id)?,
+            _ => return Ok((None, publish)),
+        };
+        let session = Self {
+            initial: simulator.snapshot(),
+            simulator,
+            package_id,
+            gas_budget: package.gas_budget,
+            snapshots: BTreeMap::new(),
+            max_snapshots,
+        };
+        Ok((Some(session), publish))
+    }
+
+    /// 发布的包ID
+    pub fn package_id(&self) -> ObjectID {
+        self.package_id
+    }
+
+    /// 发送方地址
+    pub fn sender(&self) -> String {
+        self.simulator.sender().to_string()
+    }
+
+    /// 执行包中的函数，成功时写入的对象保留到之后的调用
+    pub fn call(&mut self, request: &SessionCallRequest) -> Result<SimulationReport, OperationError> {
+        let gas_budget = request.gas_budget.unwrap_or(self.gas_budget);
+        self.simulator.call(self.package_id, &request.call, gas_budget)
+    }
+
+    /// 会话中的对象，不包括框架包
+    pub fn objects(&self) -> Vec<ObjectSummary> {
+        self.simulator.objects()
+    }
+
+    /// 读取对象并解码字段
+    pub fn object(&self, object_id: &str) -> Result<ObjectDetail, OperationError> {
+        self.simulator.object(transaction::parse_object_id(object_id)?)
+    }
+
+    /// 恢复到刚发布后的状态，保留已保存的快照
+    pub fn reset(&mut self) {
+        self.simulator.restore(&self.initial);
+    }
+
+    /// 保存当前状态，未指定名称时按序号生成未使用的名称，指定的名称已存在时覆盖该快照
+    ///
+    /// 名称出现在恢复快照的路径中，不能包含`/`或控制字符。
+    pub fn save_snapshot(&mut self, name: Option<&str>) -> Result<String, OperationError> {
+        let name = match name.map(str::trim) {
+            Some(name) if name.contains('/') || name.chars().any(char::is_control) => {
+                return Err(invalid_argument(format!("快照名称不能包含/或控制字符: {:?}", name)));
+            }
+            Some(name) if !name.is_empty() => name.to_string(),
+            _ => (self.snapshots.len() + 1..)
+                .map(|index| format!("snapshot-{}", index))
+                .find(|name| !self.snapshots.contains_key(name))
+                .unwrap_or_default(),
+        };
+        if !self.snapshots.contains_key(&name) && self.snapshots.len() >= self.max_snapshots {
+            return Err(invalid_argument(format!("每个会话最多保存 {} 个快照", self.max_snapshots)));
+        }
+        self.snapshots.insert(name.clone(), self.simulator.snapshot());
+        Ok(name)
+    }
+
+    /// 恢复到已保存的快照
+    pub fn restore_snapshot(&mut self, name: &str) -> Result<(), OperationError> {
+        let snapshot = self
+            .snapshots
+            .get(name)
+            .ok_or_else(|| invalid_argument(format!("快照不存在: {}", name)))?;
+        self.simulator.restore(snapshot);
+        Ok(())
+    }
+
+    /// 已保存的快照名称
+    pub fn snapshot_names(&self) -> Vec<String> {
+        self.snapshots.keys().cloned().collect()
+    }
+}
+
+// 会话及其最后使用时间
+struct SessionEntry {
+    session: Arc<Mutex<SimulationSession>>,
+    last_used: Instant,
+}
+
+/// 保存在内存中的模拟会话，服务重启后丢失
+pub struct SessionStore {
+    config: SessionConfig,
+    sessions: Mutex<HashMap<String, SessionEntry>>,
+}
+
+impl SessionStore {
+    /// 按配置创建空的会话存储
+    pub fn new(config: SessionConfig) -> Self {
+        Self {
+            config,
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 会话配置
+    pub fn config(&self) -> &SessionConfig {
+        &self.config
+    }
+
+    /// 保存新会话并返回会话ID，会话数达到上限时删除最久未使用的会话
+    pub fn insert(&self, session: SimulationSession) -> String {
+        let mut sessions = self.lock();
+        self.remove_expired(&mut sessions);
+        while sessions.len() >= self.config.max_sessions {
+            let oldest = sessions
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(id, _)| id.clone());
+            match oldest {
+                Some(id) => sessions.remove(&id),
+                None => break,
+            };
+        }
+
+        let id = format!("{:032x}", rand::random::<u128>());
+        sessions.insert(
+            id.clone(),
+            SessionEntry {
+                session: Arc::new(Mutex::new(session)),
+                last_used: Instant::now(),
+            },
+        );
+        id
+    }
+
+    /// 查找会话并更新最后使用时间
+    pub fn get(&self, id: &str) -> Result<Arc<Mutex<SimulationSession>>, OperationError> {
+        let mut sessions = self.lock();
+        self.remove_expired(&mut sessions);
+        let entry = sessions.get_mut(id).ok_or_else(|| {
+            OperationError::new(ErrorCode::SessionNotFound, format!("会话不存在或已过期: {}", id))
+        })?;
+        entry.last_used = Instant::now();
+        Ok(entry.session.clone())
+    }
+
+    /// 删除会话，返回会话是否存在
+    pub fn remove(&self, id: &str) -> bool {
+        self.lock().remove(id).is_some()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, SessionEntry>> {
+        self.sessions.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn remove_expired(&self, sessions: &mut HashMap<String, SessionEntry>) {
+        let ttl = self.config.ttl;
+        sessions.retain(|_, entry| entry.last_used.elapsed() < ttl);
+    }
+}
+
+/// 锁定会话，持有锁的线程异常退出时继续使用会话
+pub fn lock_session(session: &Mutex<SimulationSession>) -> MutexGuard<'_, SimulationSession> {
+    session.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn invalid_argument(message: impl Into<String>) -> OperationError {
+    OperationError::new(ErrorCode::InvalidArgument, message)
+}
+
+fn env_parse<T: std::str::FromStr>(name: &str) -> Option<T> {
+    std::env::var(name).ok().and_then(|v| v.trim().parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    use crate::compiler;
+
+    // 不依赖框架的最小模块，发布后本地存储中多出包对象和UpgradeCap
+    const MODULE: &str = "module 0x0::counter { public fun value(): u64 { 1 } }";
+
+    fn package() -> SimulatedPackage {
+        let dir = std::env::temp_dir().join(format!("move-session-test-{:016x}", rand::random::<u64>()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("counter.move");
+        std::fs::write(&source, MODULE).unwrap();
+        let result = compiler::build_sources(&[source], &[], &[], &dir);
+        let _ = std::fs::remove_dir_all(&dir);
+        let (modules, _) = result.unwrap();
+        let modules: Vec<String> = modules.into_iter().map(|module| module.base64).collect();
+        serde_json::from_value(json!({ "modules": modules, "dependencies": ["0x1", "0x2"] })).unwrap()
+    }
+
+    fn session(max_snapshots: usize) -> SimulationSession {
+        let (session, publish) = SimulationSession::create(&package(), max_snapshots).unwrap();
+        session.unwrap_or_else(|| panic!("发布失败: {:?}", publish.error))
+    }
+
+    // 再发布一次，改变会话状态
+    fn publish_again(session: &mut SimulationSession) {
+        let package = package();
+        let modules = transaction::decode_modules(&package.modules).unwrap();
+        let dependencies = vec![ObjectID::from_single_byte(1), ObjectID::from_single_byte(2)];
+        let report = session.simulator.publish(modules, dependencies, package.gas_budget).unwrap();
+        assert!(report.is_success());
+    }
+
+    fn object_ids(session: &SimulationSession) -> Vec<String> {
+        session.objects().into_iter().map(|object| object.object_id).collect()
+    }
+
+    #[test]
+    fn restores_saved_snapshot() {
+        let mut session = session(DEFAULT_MAX_SNAPSHOTS);
+        let published = object_ids(&session);
+        assert!(!published.is_empty());
+
+        assert_eq!(session.save_snapshot(Some(" before ")).unwrap(), "before");
+        publish_again(&mut session);
+        let changed = object_ids(&session);
+        assert!(changed.len() > published.len());
+
+        session.restore_snapshot("before").unwrap();
+        assert_eq!(object_ids(&session), published);
+
+        // 恢复后再执行相同的交易得到相同的对象
+        publish_again(&mut session);
+        assert_eq!(object_ids(&session), changed);
+
+        let error = session.restore_snapshot("missing").unwrap_err();
+        assert_eq!(error.code, ErrorCode::InvalidArgument);
+    }
+
+    #[test]
+    fn reset_keeps_snapshots() {
+        let mut session = session(DEFAULT_MAX_SNAPSHOTS);
+        let published = object_ids(&session);
+        publish_again(&mut session);
+        let changed = object_ids(&session);
+        session.save_snapshot(Some("changed")).unwrap();
+
+        session.reset();
+        assert_eq!(object_ids(&session), published);
+        assert_eq!(session.snapshot_names(), ["changed"]);
+
+        session.restore_snapshot("changed").unwrap();
+        assert_eq!(object_ids(&session), changed);
+    }
+
+    #[test]
+    fn generated_names_do_not_overwrite_snapshots() {
+        let mut session = session(DEFAULT_MAX_SNAPSHOTS);
+        session.save_snapshot(Some("snapshot-2")).unwrap();
+        assert_eq!(session.save_snapshot(None).unwrap(), "snapshot-3");
+        assert_eq!(session.save_snapshot(Some("")).unwrap(), "snapshot-4");
+        assert_eq!(session.snapshot_names(), ["snapshot-2", "snapshot-3", "snapshot-4"]);
+    }
+
+    #[test]
+    fn rejects_names_the_restore_route_cannot_address() {
+        let mut session = session(DEFAULT_MAX_SNAPSHOTS);
+        for name in ["a/b", "/", "a\nb"] {
+            assert_eq!(session.save_snapshot(Some(name)).unwrap_err().code, ErrorCode::InvalidArgument);
+        }
+        assert!(session.snapshot_names().is_empty());
+    }
+
+    #[test]
+    fn limits_snapshot_count() {
+        let mut session = session(2);
+        session.save_snapshot(Some("a")).unwrap();
+        session.save_snapshot(None).unwrap();
+        assert_eq!(session.save_snapshot(Some("c")).unwrap_err().code, ErrorCode::InvalidArgument);
+        assert_eq!(session.save_snapshot(None).unwrap_err().code, ErrorCode::InvalidArgument);
+
+        // 覆盖已有的快照不受数量限制
+        publish_again(&mut session);
+        let changed = object_ids(&session);
+        session.save_snapshot(Some("a")).unwrap();
+        session.reset();
+        session.restore_snapshot("a").unwrap();
+        assert_eq!(object_ids(&session), changed);
+        assert_eq!(session.snapshot_names().len(), 2);
+    }
+
+    #[test]
+    fn store_expires_and_evicts_sessions() {
+        let store = SessionStore::new(SessionConfig {
+            ttl: Duration::from_secs(60),
+            max_sessions: 1,
+            max_snapshots: DEFAULT_MAX_SNAPSHOTS,
+        });
+        let first = store.insert(session(DEFAULT_MAX_SNAPSHOTS));
+        assert!(store.get(&first).is_ok());
+        let second = store.insert(session(DEFAULT_MAX_SNAPSHOTS));
+        assert_eq!(store.get(&first).err().map(|e| e.code), Some(ErrorCode::SessionNotFound));
+        assert!(store.remove(&second));
+        assert!(!store.remove(&second));
+    }
+}
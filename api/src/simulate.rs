@@ -0,0 +1,639 @@
+// Copyright (c) Move Web Compiler
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use move_core_types::annotated_value::{MoveTypeLayout, MoveValue};
+use move_core_types::language_storage::TypeTag;
+use serde::{Deserialize, Serialize};
+use sui_execution::Executor;
+use sui_framework::BuiltInFramework;
+use sui_json::{resolve_move_function_args, ResolvedCallArg};
+use sui_protocol_config::ProtocolConfig;
+use sui_sdk::rpc_types::{SuiMoveStruct, SuiMoveValue};
+use sui_types::base_types::{ObjectID, ObjectRef, SuiAddress, TransactionDigest};
+use sui_types::effects::{TransactionEffects, TransactionEffectsAPI};
+use sui_types::error::ExecutionError;
+use sui_types::event::Event;
+use sui_types::execution::ExecutionResult;
+use sui_types::execution_status::ExecutionStatus;
+use sui_types::gas::SuiGasStatus;
+use sui_types::in_memory_storage::InMemoryStorage;
+use sui_types::inner_temporary_store::InnerTemporaryStore;
+use sui_types::layout_resolver::LayoutResolver;
+use sui_types::metrics::LimitsMetrics;
+use sui_types::object::{MoveObject, Object, Owner, OBJECT_START_VERSION};
+use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
+use sui_types::transaction::{
+    CallArg, CheckedInputObjects, InputObjectKind, InputObjects, ObjectArg, ObjectReadResult, ProgrammableTransaction,
+    TransactionKind,
+};
+
+use crate::call;
+use crate::gas_profile::{GasProfile, GasProfileOptions, ProfileDir};
+use crate::operations::{ErrorCode, OperationError, DEFAULT_GAS_BUDGET, MAX_GAS_BUDGET};
+use crate::rpc::{EventSummary, GasSummary, ObjectSummary};
+use crate::transaction;
+
+/// 未指定发送方时使用的地址
+pub const DEFAULT_SIMULATION_SENDER: &str = "0xa11ce";
+
+/// 本地执行使用的gas价格（MIST），同时作为参考gas价格
+pub const SIMULATION_GAS_PRICE: u64 = 1_000;
+
+// 每笔交易临时创建的gas币余额，足够支付最大gas预算
+const SIMULATION_GAS_COIN_VALUE: u64 = 1_000_000_000_000_000;
+
+/// 本地执行的函数调用
+#[derive(Clone, Debug, Deserialize)]
+pub struct SimulatedCall {
+    /// 模块名
+    pub module: String,
+    /// 函数名
+    pub function: String,
+    /// 类型参数，例如`0x2::sui::SUI`
+    #[serde(default)]
+    pub type_args: Vec<String>,
+    /// JSON格式的参数，对象参数传本地对象的ID，不包括`TxContext`
+    #[serde(default)]
+    pub args: Vec<serde_json::Value>,
+}
+
+/// 发布到本地模拟链的包
+#[derive(Clone, Debug, Deserialize)]
+pub struct SimulatedPackage {
+    /// 编译接口返回的`bytecode_base64`
+    pub modules: Vec<String>,
+    /// 依赖的包ID，即编译接口返回的`dependencies`，只支持内置的框架包
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+    /// 发送方地址，默认为`DEFAULT_SIMULATION_SENDER`
+    pub sender: Option<String>,
+    /// 按函数分析每笔交易的gas
+    #[serde(flatten)]
+    pub profile: GasProfileOptions,
+    /// 每笔交易的gas预算（MIST）
+    #[serde(default = "default_gas_budget")]
+    pub gas_budget: u64,
+}
+
+/// 发布包后执行其中一个函数的请求
+#[derive(Clone, Debug, Deserialize)]
+pub struct SimulateRequest {
+    /// 要发布的包
+    #[serde(flatten)]
+    pub package: SimulatedPackage,
+    /// 要执行的函数
+    #[serde(flatten)]
+    pub call: SimulatedCall,
+}
+
+/// 返回值
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SimulatedValue {
+    /// Move类型
+    #[serde(rename = "type")]
+    pub type_: String,
+    /// BCS编码的值（Base64）
+    pub bcs: String,
+    /// 解码后的值，无法解析类型布局时为空
+    pub json: Option<serde_json::Value>,
+}
+
+/// 一笔本地交易的执行结果
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SimulationReport {
+    /// 本地生成的交易摘要
+    pub digest: String,
+    /// 执行状态：`success`或`failure`
+    pub status: String,
+    /// 执行失败的原因，例如abort码
+    pub execution_error: Option<String>,
+    /// 发布交易中新包的ID
+    pub package_id: Option<String>,
+    /// 函数的返回值
+    pub return_values: Vec<SimulatedValue>,
+    /// 创建的对象
+    pub created: Vec<ObjectSummary>,
+    /// 修改的对象，不包括临时的gas币
+    pub mutated: Vec<ObjectSummary>,
+    /// 删除或被包装的对象ID
+    pub deleted: Vec<String>,
+    /// 产生的事件
+    pub events: Vec<EventSummary>,
+    /// gas费用
+    pub gas: GasSummary,
+    /// 按函数统计的gas，开启gas分析时才有
+    #[serde(default)]
+    pub gas_profile: Option<GasProfile>,
+}
+
+impl SimulationReport {
+    /// 执行是否成功
+    pub fn is_success(&self) -> bool {
+        self.status == "success"
+    }
+}
+
+/// 本地对象的内容
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ObjectDetail {
+    /// 对象ID、类型、所有者和版本
+    #[serde(flatten)]
+    pub summary: ObjectSummary,
+    /// 解码后的字段，包没有字段
+    pub fields: Option<serde_json::Value>,
+}
+
+/// 本地存储的快照，用于恢复到之前的状态
+///
+/// 只保存框架包以外的对象，框架包不会被交易修改，恢复时从模拟链补回。
+#[derive(Clone, Debug)]
+pub struct SimulatorSnapshot {
+    objects: Vec<Object>,
+    transaction_count: u64,
+}
+
+/// 发布并执行函数的结果
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SimulationOutcome {
+    /// 发送方地址
+    pub sender: String,
+    /// 发布交易的结果，包括`init`函数创建的对象
+    pub publish: SimulationReport,
+    /// 函数调用的结果，发布失败时为空
+    pub call: Option<SimulationReport>,
+}
+
+// 一笔交易执行后的原始结果
+struct Execution {
+    digest: TransactionDigest,
+    effects: TransactionEffects,
+    store: InnerTemporaryStore,
+    results: Result<Vec<ExecutionResult>, ExecutionError>,
+    gas_profile: Option<GasProfile>,
+}
+
+/// 本地模拟链：内存中的对象存储加Sui执行器
+///
+/// 存储中预置了内置的框架包（`0x1`、`0x2`、`0x3`等），没有时钟等系统对象。
+/// 交易通过与`devInspect`相同的方式执行，写入的对象保存到本地存储，
+/// 每笔交易使用临时创建的gas币，执行后丢弃。交易摘要按序号生成，
+/// 相同的操作序列总是得到相同的对象ID。
+pub struct LocalSimulator {
+    executor: Arc<dyn Executor + Send + Sync>,
+    protocol_config: ProtocolConfig,
+    metrics: Arc<LimitsMetrics>,
+    store: InMemoryStorage,
+    // 预置的框架对象，列出本地对象和保存快照时跳过
+    genesis: BTreeMap<ObjectID, Object>,
+    sender: SuiAddress,
+    epoch_timestamp_ms: u64,
+    transaction_count: u64,
+    profile: GasProfileOptions,
+    // 执行器写入gas分析结果的目录，开启gas分析时才有
+    profile_dir: Option<ProfileDir>,
+}
+
+impl LocalSimulator {
+    /// 创建只包含框架包的本地存储，开启gas分析时执行器把每笔交易的分析结果写到临时目录
+    pub fn new(sender: SuiAddress, profile: GasProfileOptions) -> Result<Self, OperationError> {
+        let protocol_config = ProtocolConfig::get_for_max_version_UNSAFE();
+        let profile_dir = match profile.enabled() {
+            true => Some(ProfileDir::create().map_err(|e| simulation_error(format!("无法创建gas分析目录: {}", e)))?),
+            false => None,
+        };
+        let executor = sui_execution::executor(&protocol_config, true, profile_dir.as_ref().map(|dir| dir.path().to_path_buf()))
+            .map_err(|e| simulation_error(format!("无法创建执行器: {}", e)))?;
+        let epoch_timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as u64)
+            .unwrap_or_default();
+        let genesis: BTreeMap<ObjectID, Object> =
+            BuiltInFramework::genesis_objects().map(|object| (object.id(), object)).collect();
+        Ok(Self {
+            executor,
+            protocol_config,
+            metrics: Arc::new(LimitsMetrics::new(&prometheus::Registry::new())),
+            store: InMemoryStorage::new(genesis.values().cloned().collect()),
+            genesis,
+            sender,
+            epoch_timestamp_ms,
+            transaction_count: 0,
+            profile,
+            profile_dir,
+        })
+    }
+
+    /// 创建本地存储并发布包，返回模拟链和发布交易的结果
+    pub fn with_package(package: &SimulatedPackage) -> Result<(Self, SimulationReport), OperationError> {
+        let sender = transaction::parse_address(package.sender.as_deref().unwrap_or(DEFAULT_SIMULATION_SENDER))?;
+        let modules = transaction::decode_modules(&package.modules)?;
+        let dependencies = package
+            .dependencies
+            .iter()
+            .map(|id| transaction::parse_object_id(id))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut simulator = Self::new(sender, package.profile)?;
+        let publish = simulator.publish(modules, dependencies, package.gas_budget)?;
+        Ok((simulator, publish))
+    }
+
+    /// 发送方地址
+    pub fn sender(&self) -> SuiAddress {
+        self.sender
+    }
+
+    /// 保存当前的本地存储，不包括框架包
+    pub fn snapshot(&self) -> SimulatorSnapshot {
+        SimulatorSnapshot {
+            objects: self
+                .store
+                .objects()
+                .values()
+                .filter(|object| !self.genesis.contains_key(&object.id()))
+                .cloned()
+                .collect(),
+            transaction_count: self.transaction_count,
+        }
+    }
+
+    /// 恢复到快照时的状态，之后的交易摘要和对象ID与快照后第一次执行时相同
+    pub fn restore(&mut self, snapshot: &SimulatorSnapshot) {
+        let objects = self.genesis.values().chain(&snapshot.objects).cloned().collect();
+        self.store = InMemoryStorage::new(objects);
+        self.transaction_count = snapshot.transaction_count;
+    }
+
+    /// 框架包以外的本地对象，包括发布的包
+    pub fn objects(&self) -> Vec<ObjectSummary> {
+        self.store
+            .objects()
+            .values()
+            .filter(|object| !self.genesis.contains_key(&object.id()))
+            .map(object_summary)
+            .collect()
+    }
+
+    /// 读取本地对象并解码字段
+    pub fn object(&self, id: ObjectID) -> Result<ObjectDetail, OperationError> {
+        let object = self
+            .store
+            .get_object(&id)
+            .ok_or_else(|| invalid_argument(format!("本地存储中没有对象 {}", id)))?;
+        let fields = object.data.try_as_move().and_then(|move_object| {
+            let mut resolver = self.executor.type_layout_resolver(Box::new(&self.store));
+            let type_ = TypeTag::Struct(Box::new(move_object.type_().clone().into()));
+            decode_value(resolver.as_mut(), &type_, move_object.contents())
+        });
+        Ok(ObjectDetail {
+            summary: object_summary(object),
+            fields,
+        })
+    }
+
+    /// 发布包并运行`init`函数，`UpgradeCap`转给发送方
+    pub fn publish(
+        &mut self,
+        modules: Vec<Vec<u8>>,
+        dependencies: Vec<ObjectID>,
+        gas_budget: u64,
+    ) -> Result<SimulationReport, OperationError> {
+        for dependency in &dependencies {
+            if self.store.get_object(dependency).is_none() {
+                return Err(invalid_argument(format!(
+                    "依赖的包 {} 不在本地存储中，本地执行只支持内置的框架包",
+                    dependency
+                )));
+            }
+        }
+        let mut builder = ProgrammableTransactionBuilder::new();
+        let upgrade_cap = builder.publish_upgradeable(modules, dependencies);
+        builder.transfer_arg(self.sender, upgrade_cap);
+        let execution = self.execute(builder.finish(), gas_budget, false)?;
+        Ok(self.report(&execution))
+    }
+
+    /// 调用包中的函数，和`devInspect`一样不检查函数的可见性和返回值的能力
+    pub fn call(
+        &mut self,
+        package_id: ObjectID,
+        move_call: &SimulatedCall,
+        gas_budget: u64,
+    ) -> Result<SimulationReport, OperationError> {
+        let module = call::parse_identifier(&move_call.module)?;
+        let function = call::parse_identifier(&move_call.function)?;
+        let type_args = call::parse_type_args(&move_call.type_args)?;
+        let args = call::parse_json_args(&move_call.args)?;
+
+        let package = self
+            .store
+            .get_object(&package_id)
+            .and_then(|object| object.data.try_as_package().cloned())
+            .ok_or_else(|| invalid_argument(format!("本地存储中没有包 {}", package_id)))?;
+        let resolved = resolve_move_function_args(&package, module.clone(), function.clone(), &type_args, args)
+            .map_err(|e| invalid_argument(format!("参数与函数签名不匹配: {}", e)))?;
+
+        let mut builder = ProgrammableTransactionBuilder::new();
+        let mut arguments = Vec::with_capacity(resolved.len());
+        for (arg, signature) in resolved {
+            let mutable = call::is_mutable(&arg, &signature);
+            let argument = match arg {
+                ResolvedCallArg::Pure(bytes) => builder.input(CallArg::Pure(bytes)),
+                ResolvedCallArg::Object(id) => builder.obj(self.object_arg(id, mutable)?),
+                ResolvedCallArg::ObjVec(ids) => {
+                    let objects = ids
+                        .into_iter()
+                        .map(|id| self.object_arg(id, mutable))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    builder.make_obj_vec(objects)
+                }
+            }
+            .map_err(|e| invalid_argument(e.to_string()))?;
+            arguments.push(argument);
+        }
+        builder.programmable_move_call(package_id, module, function, type_args, arguments);
+
+        let execution = self.execute(builder.finish(), gas_budget, true)?;
+        Ok(self.report(&execution))
+    }
+
+    // 本地对象作为参数：共享对象需要初始共享版本，其他对象使用当前的对象引用
+    fn object_arg(&self, id: ObjectID, mutable: bool) -> Result<ObjectArg, OperationError> {
+        let object = self
+            .store
+            .get_object(&id)
+            .ok_or_else(|| invalid_argument(format!("本地存储中没有对象 {}", id)))?;
+        Ok(match object.owner {
+            Owner::Shared { initial_shared_version } => ObjectArg::SharedObject {
+                id,
+                initial_shared_version,
+                mutable,
+            },
+            _ => ObjectArg::ImmOrOwnedObject(object.compute_object_reference()),
+        })
+    }
+
+    // 执行交易并把写入的对象保存到本地存储
+    fn execute(
+        &mut self,
+        transaction: ProgrammableTransaction,
+        gas_budget: u64,
+        skip_checks: bool,
+    ) -> Result<Execution, OperationError> {
+        if gas_budget == 0 || gas_budget > MAX_GAS_BUDGET {
+            return Err(invalid_argument(format!("gas预算必须在1到{}之间", MAX_GAS_BUDGET)));
+        }
+        self.transaction_count += 1;
+        let mut digest = [0u8; 32];
+        digest[..8].copy_from_slice(&self.transaction_count.to_be_bytes());
+        let digest = TransactionDigest::new(digest);
+
+        let mut objects = Vec::new();
+        for kind in transaction
+            .input_objects()
+            .map_err(|e| invalid_argument(format!("无效的交易输入: {}", e)))?
+        {
+            let id = kind.object_id();
+            let object = self
+                .store
+                .get_object(&id)
+                .ok_or_else(|| invalid_argument(format!("本地存储中没有对象 {}", id)))?;
+            objects.push(ObjectReadResult::new(kind, object.clone().into()));
+        }
+        let gas_coin = Object::new_move(
+            MoveObject::new_gas_coin(OBJECT_START_VERSION, ObjectID::MAX, SIMULATION_GAS_COIN_VALUE),
+            Owner::AddressOwner(self.sender),
+            TransactionDigest::genesis_marker(),
+        );
+        let gas_ref = gas_coin.compute_object_reference();
+        objects.push(ObjectReadResult::new(InputObjectKind::ImmOrOwnedMoveObject(gas_ref), gas_coin.into()));
+
+        let gas_status = SuiGasStatus::new(gas_budget, SIMULATION_GAS_PRICE, SIMULATION_GAS_PRICE, &self.protocol_config)
+            .map_err(|e| invalid_argument(format!("无效的gas参数: {}", e)))?;
+        let (store, _, effects, results) = self.executor.dev_inspect_transaction(
+            &self.store,
+            &self.protocol_config,
+            self.metrics.clone(),
+            false,
+            &HashSet::new(),
+            &0,
+            self.epoch_timestamp_ms,
+            CheckedInputObjects::new_for_replay(InputObjects::new(objects)),
+            vec![gas_ref],
+            gas_status,
+            TransactionKind::ProgrammableTransaction(transaction),
+            self.sender,
+            digest,
+            skip_checks,
+        );
+
+        let gas_profile = match &self.profile_dir {
+            Some(profile_dir) => profile_dir
+                .take_profiles(&self.profile)
+                .map_err(|e| simulation_error(format!("无法读取gas分析结果: {}", e)))?
+                .pop()
+                .map(|profile| GasProfile {
+                    storage_bytes: Some(
+                        store
+                            .written
+                            .iter()
+                            .filter(|(id, _)| **id != ObjectID::MAX)
+                            .map(|(_, object)| object.object_size_for_gas_metering() as u64)
+                            .sum(),
+                    ),
+                    ..profile
+                }),
+            None => None,
+        };
+
+        if effects.status().is_ok() {
+            for (object_ref, _) in effects.all_removed_objects() {
+                self.store.remove_object(object_ref.0);
+            }
+            for (id, object) in &store.written {
+                if *id != ObjectID::MAX {
+                    self.store.insert_object(object.clone());
+                }
+            }
+        }
+        Ok(Execution {
+            digest,
+            effects,
+            store,
+            results,
+            gas_profile,
+        })
+    }
+
+    fn report(&self, execution: &Execution) -> SimulationReport {
+        let written = &execution.store.written;
+        let mut resolver = self.executor.type_layout_resolver(Box::new(&self.store));
+
+        let summarize = |(object_ref, owner): &(ObjectRef, Owner)| {
+            let (id, version, _) = object_ref;
+            ObjectSummary {
+                object_id: id.to_string(),
+                object_type: written.get(id).map(object_type),
+                owner: serde_json::to_value(owner).ok(),
+                version: version.value(),
+            }
+        };
+        let created: Vec<ObjectSummary> = execution.effects.created().iter().map(summarize).collect();
+        let mutated = execution
+            .effects
+            .mutated()
+            .iter()
+            .filter(|((id, _, _), _)| *id != ObjectID::MAX)
+            .map(summarize)
+            .collect();
+        let package_id = execution
+            .effects
+            .created()
+            .iter()
+            .map(|((id, _, _), _)| id)
+            .find(|id| written.get(*id).is_some_and(Object::is_package))
+            .map(ObjectID::to_string);
+
+        let (status, execution_error) = match execution.effects.status() {
+            ExecutionStatus::Success => ("success".to_string(), None),
+            ExecutionStatus::Failure { error, command } => {
+                let error = match command {
+                    Some(command) => format!("{} (命令 {})", error, command),
+                    None => error.to_string(),
+                };
+                ("failure".to_string(), Some(error))
+            }
+        };
+
+        // 只有一条命令，返回值取最后一条命令的结果
+        let return_values = match &execution.results {
+            Ok(results) => results
+                .last()
+                .map(|(_, values)| {
+                    values
+                        .iter()
+                        .map(|(bytes, type_)| SimulatedValue {
+                            type_: type_.to_string(),
+                            bcs: BASE64_STANDARD.encode(bytes),
+                            json: decode_value(resolver.as_mut(), type_, bytes),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+            Err(_) => vec![],
+        };
+
+        let events = execution
+            .store
+            .events
+            .data
+            .iter()
+            .map(|event| EventSummary {
+                event_type: event.type_.to_string(),
+                package_id: event.package_id.to_string(),
+                module: event.transaction_module.to_string(),
+                sender: event.sender.to_string(),
+                parsed_json: resolver
+                    .get_annotated_layout(&event.type_)
+                    .ok()
+                    .and_then(|layout| Event::move_event_to_move_value(&event.contents, layout).ok())
+                    .and_then(|value| SuiMoveStruct::from(value).to_json_value().ok())
+                    .unwrap_or(serde_json::Value::Null),
+            })
+            .collect();
+
+        SimulationReport {
+            digest: execution.digest.to_string(),
+            status,
+            execution_error,
+            package_id,
+            return_values,
+            created,
+            mutated,
+            deleted: execution
+                .effects
+                .all_removed_objects()
+                .iter()
+                .map(|((id, _, _), _)| id.to_string())
+                .collect(),
+            events,
+            gas: GasSummary::from(execution.effects.gas_cost_summary()),
+            gas_profile: execution.gas_profile.clone(),
+        }
+    }
+}
+
+/// 在新的本地存储中发布包，再执行指定的函数
+pub fn simulate(request: &SimulateRequest) -> Result<SimulationOutcome, OperationError> {
+    let (mut simulator, publish) = LocalSimulator::with_package(&request.package)?;
+    let call = match publish.package_id.as_deref() {
+        Some(package_id) if publish.is_success() => {
+            let package_id = transaction::parse_object_id(package_id)?;
+            Some(simulator.call(package_id, &request.call, request.package.gas_budget)?)
+        }
+        _ => None,
+    };
+    Ok(SimulationOutcome {
+        sender: simulator.sender().to_string(),
+        publish,
+        call,
+    })
+}
+
+// 对象类型，包没有Move类型
+fn object_type(object: &Object) -> String {
+    object
+        .type_()
+        .map(ToString::to_string)
+        .unwrap_or_else(|| "package".to_string())
+}
+
+fn object_summary(object: &Object) -> ObjectSummary {
+    ObjectSummary {
+        object_id: object.id().to_string(),
+        object_type: Some(object_type(object)),
+        owner: serde_json::to_value(&object.owner).ok(),
+        version: object.version().value(),
+    }
+}
+
+// 按类型解码BCS值，无法解析类型布局时为空
+fn decode_value(resolver: &mut dyn LayoutResolver, type_: &TypeTag, bytes: &[u8]) -> Option<serde_json::Value> {
+    let layout = type_layout(resolver, type_)?;
+    let value = MoveValue::simple_deserialize(bytes, &layout).ok()?;
+    SuiMoveValue::from(value).to_json_value().ok()
+}
+
+// 值类型的布局，结构体从本地存储中的包解析
+fn type_layout(resolver: &mut dyn LayoutResolver, type_: &TypeTag) -> Option<MoveTypeLayout> {
+    Some(match type_ {
+        TypeTag::Bool => MoveTypeLayout::Bool,
+        TypeTag::U8 => MoveTypeLayout::U8,
+        TypeTag::U16 => MoveTypeLayout::U16,
+        TypeTag::U32 => MoveTypeLayout::U32,
+        TypeTag::U64 => MoveTypeLayout::U64,
+        TypeTag::U128 => MoveTypeLayout::U128,
+        TypeTag::U256 => MoveTypeLayout::U256,
+        TypeTag::Address => MoveTypeLayout::Address,
+        TypeTag::Signer => MoveTypeLayout::Signer,
+        TypeTag::Vector(inner) => MoveTypeLayout::Vector(Box::new(type_layout(resolver, inner)?)),
+        TypeTag::Struct(tag) => resolver.get_annotated_layout(tag).ok()?.into_layout(),
+    })
+}
+
+fn invalid_argument(message: impl Into<String>) -> OperationError {
+    OperationError::new(ErrorCode::InvalidArgument, message)
+}
+
+fn simulation_error(message: impl Into<String>) -> OperationError {
+    OperationError::new(ErrorCode::SimulationError, message)
+}
+
+fn default_gas_budget() -> u64 {
+    DEFAULT_GAS_BUDGET
+}
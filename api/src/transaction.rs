@@ -0,0 +1,311 @@
+// Copyright (c) Move Web Compiler
+// SPDX-License-Identifier: Apache-2.0
+
+use std::str::FromStr;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use move_binary_format::access::ModuleAccess;
+use move_binary_format::file_format::CompiledModule;
+use move_core_types::account_address::AccountAddress;
+use serde::{Deserialize, Serialize};
+use sui_types::base_types::{ObjectDigest, ObjectID, ObjectRef, SequenceNumber, SuiAddress};
+use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
+use sui_types::transaction::{TransactionData, TransactionDataAPI};
+
+use crate::operations::{ErrorCode, OperationError, DEFAULT_GAS_BUDGET, MAX_GAS_BUDGET};
+
+/// 请求中的对象引用，例如用于支付gas的币
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ObjectRefInput {
+    /// 对象ID
+    pub object_id: String,
+    /// 对象版本
+    pub version: u64,
+    /// 对象摘要（Base58）
+    pub digest: String,
+}
+
+impl ObjectRefInput {
+    /// 解析为Sui的对象引用
+    pub fn to_object_ref(&self) -> Result<ObjectRef, OperationError> {
+        let object_id = parse_object_id(&self.object_id)?;
+        let digest = ObjectDigest::from_str(self.digest.trim())
+            .map_err(|e| invalid_argument(format!("无效的对象摘要 {}: {}", self.digest, e)))?;
+        Ok((object_id, SequenceNumber::from_u64(self.version), digest))
+    }
+}
+
+/// 交易的发送方和gas参数，由签名的钱包提供
+#[derive(Clone, Debug, Deserialize)]
+pub struct GasParams {
+    /// 发送方地址
+    pub sender: String,
+    /// gas预算（MIST）
+    #[serde(default = "default_gas_budget")]
+    pub gas_budget: u64,
+    /// gas价格（MIST），应不低于当前纪元的参考gas价格
+    pub gas_price: u64,
+    /// 用于支付gas的币，必须属于发送方
+    pub gas_payment: Vec<ObjectRefInput>,
+}
+
+impl GasParams {
+    /// 检查并解析发送方和gas币
+    pub fn resolve(&self) -> Result<(SuiAddress, Vec<ObjectRef>), OperationError> {
+        let sender = parse_address(&self.sender)?;
+        if self.gas_budget == 0 || self.gas_budget > MAX_GAS_BUDGET {
+            return Err(invalid_argument(format!("gas预算必须在1到{}之间", MAX_GAS_BUDGET)));
+        }
+        if self.gas_price == 0 {
+            return Err(invalid_argument("gas价格必须大于0"));
+        }
+        if self.gas_payment.is_empty() {
+            return Err(invalid_argument("缺少用于支付gas的币"));
+        }
+        let gas_payment = self
+            .gas_payment
+            .iter()
+            .map(ObjectRefInput::to_object_ref)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok((sender, gas_payment))
+    }
+}
+
+/// 构建发布交易的请求
+#[derive(Clone, Debug, Deserialize)]
+pub struct PublishTransactionRequest {
+    /// Base64编码的模块字节码，按编译接口返回的发布顺序排列
+    pub modules: Vec<String>,
+    /// 依赖的已发布包ID，即编译接口返回的`dependencies`
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+    /// 发送方和gas参数
+    #[serde(flatten)]
+    pub gas: GasParams,
+}
+
+/// 等待钱包签名的交易
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UnsignedTransaction {
+    /// BCS序列化的`TransactionData`（Base64），钱包对其签名
+    pub tx_bytes: String,
+    /// 交易摘要（Base58），签名并执行后即为交易ID
+    pub digest: String,
+    /// 发送方地址
+    pub sender: String,
+    /// gas预算（MIST）
+    pub gas_budget: u64,
+    /// gas价格（MIST）
+    pub gas_price: u64,
+    /// 交易引用的依赖包ID
+    pub dependencies: Vec<String>,
+}
+
+impl UnsignedTransaction {
+    /// 序列化交易数据并计算摘要
+    pub fn new(data: &TransactionData, dependencies: &[ObjectID]) -> Result<Self, OperationError> {
+        let bytes = bcs::to_bytes(data)
+            .map_err(|e| OperationError::new(ErrorCode::InvalidRequest, format!("无法序列化交易: {}", e)))?;
+        Ok(Self {
+            tx_bytes: BASE64_STANDARD.encode(bytes),
+            digest: data.digest().to_string(),
+            sender: data.sender().to_string(),
+            gas_budget: data.gas_budget(),
+            gas_price: data.gas_price(),
+            dependencies: dependencies.iter().map(ObjectID::to_string).collect(),
+        })
+    }
+}
+
+/// 构建发布包的未签名交易，发布后`UpgradeCap`转给发送方
+pub fn build_publish_transaction(request: &PublishTransactionRequest) -> Result<UnsignedTransaction, OperationError> {
+    let (sender, gas_payment) = request.gas.resolve()?;
+    let modules = decode_modules(&request.modules)?;
+
+    // 发布前模块地址必须是0x0，由链上分配包ID
+    for bytes in &modules {
+        let module = CompiledModule::deserialize(bytes)
+            .map_err(|e| invalid_argument(format!("无法解析模块字节码: {}", e)))?;
+        if *module.address() != AccountAddress::ZERO {
+            return Err(invalid_argument(format!(
+                "模块 {} 的地址为 {}，发布时包地址必须为0x0，已发布的包请使用升级",
+                module.self_id().name(),
+                module.address().to_hex_literal()
+            )));
+        }
+    }
+    let dependencies = request
+        .dependencies
+        .iter()
+        .map(|id| parse_object_id(id))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut builder = ProgrammableTransactionBuilder::new();
+    let upgrade_cap = builder.publish_upgradeable(modules, dependencies.clone());
+    builder.transfer_arg(sender, upgrade_cap);
+    let data = TransactionData::new_programmable(
+        sender,
+        gas_payment,
+        builder.finish(),
+        request.gas.gas_budget,
+        request.gas.gas_price,
+    );
+    UnsignedTransaction::new(&data, &dependencies)
+}
+
+/// 解码Base64编码的模块字节码
+pub fn decode_modules(modules: &[String]) -> Result<Vec<Vec<u8>>, OperationError> {
+    if modules.is_empty() {
+        return Err(invalid_argument("没有要发布的模块"));
+    }
+    modules
+        .iter()
+        .map(|module| {
+            BASE64_STANDARD
+                .decode(module.trim())
+                .map_err(|e| invalid_argument(format!("无效的Base64字节码: {}", e)))
+        })
+        .collect()
+}
+
+/// 解析Sui地址
+pub fn parse_address(address: &str) -> Result<SuiAddress, OperationError> {
+    SuiAddress::from_str(address.trim()).map_err(|e| invalid_argument(format!("无效的地址 {}: {}", address, e)))
+}
+
+/// 解析对象ID或包ID
+pub fn parse_object_id(id: &str) -> Result<ObjectID, OperationError> {
+    ObjectID::from_hex_literal(id.trim()).map_err(|e| invalid_argument(format!("无效的对象ID {}: {}", id, e)))
+}
+
+fn invalid_argument(message: impl Into<String>) -> OperationError {
+    OperationError::new(ErrorCode::InvalidArgument, message)
+}
+
+fn default_gas_budget() -> u64 {
+    DEFAULT_GAS_BUDGET
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sui_types::base_types::random_object_ref;
+    use sui_types::transaction::{Command, TransactionKind};
+
+    use crate::compiler;
+
+    // 编译单个模块，返回Base64编码的字节码
+    fn compile(source: &str) -> Vec<String> {
+        let dir = std::env::temp_dir().join(format!("move-transaction-test-{:016x}", rand::random::<u64>()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("m.move");
+        std::fs::write(&path, source).unwrap();
+        let result = compiler::build_sources(&[path], &[], &[], &dir);
+        let _ = std::fs::remove_dir_all(&dir);
+        let (modules, _) = result.unwrap_or_else(|e| panic!("编译失败: {:?}", e.diagnostics));
+        modules.into_iter().map(|module| module.base64).collect()
+    }
+
+    fn gas() -> GasParams {
+        let (object_id, version, digest) = random_object_ref();
+        GasParams {
+            sender: SuiAddress::random_for_testing_only().to_string(),
+            gas_budget: DEFAULT_GAS_BUDGET,
+            gas_price: 1_000,
+            gas_payment: vec![ObjectRefInput {
+                object_id: object_id.to_string(),
+                version: version.value(),
+                digest: digest.to_string(),
+            }],
+        }
+    }
+
+    fn publish(source: &str, dependencies: Vec<String>) -> Result<UnsignedTransaction, OperationError> {
+        build_publish_transaction(&PublishTransactionRequest {
+            modules: compile(source),
+            dependencies,
+            gas: gas(),
+        })
+    }
+
+    #[test]
+    fn publishes_modules_at_zero_address() {
+        let dependency = ObjectID::from_single_byte(2).to_string();
+        let transaction = publish("module 0x0::m { public fun f(): u64 { 1 } }", vec![dependency.clone()]).unwrap();
+        assert_eq!(transaction.dependencies, [dependency]);
+
+        let bytes = BASE64_STANDARD.decode(&transaction.tx_bytes).unwrap();
+        let data: TransactionData = bcs::from_bytes(&bytes).unwrap();
+        assert_eq!(data.digest().to_string(), transaction.digest);
+        assert_eq!(data.sender().to_string(), transaction.sender);
+        let TransactionKind::ProgrammableTransaction(programmable) = data.kind() else {
+            panic!("不是可编程交易: {:?}", data.kind());
+        };
+        // 发布后把UpgradeCap转给发送方
+        match programmable.commands.as_slice() {
+            [Command::Publish(modules, dependencies), Command::TransferObjects(..)] => {
+                assert_eq!(modules.len(), 1);
+                assert_eq!(dependencies, &[ObjectID::from_single_byte(2)]);
+            }
+            commands => panic!("预期发布和转移命令: {:?}", commands),
+        }
+    }
+
+    #[test]
+    fn rejects_modules_with_an_assigned_address() {
+        let error = publish("module 0xcafe::m { public fun f(): u64 { 1 } }", vec![]).unwrap_err();
+        assert_eq!(error.code, ErrorCode::InvalidArgument);
+        assert!(error.message.contains("模块 m 的地址为 0xcafe"), "{}", error.message);
+    }
+
+    #[test]
+    fn rejects_missing_or_invalid_modules() {
+        let request = |modules: Vec<String>| PublishTransactionRequest { modules, dependencies: vec![], gas: gas() };
+        for modules in [vec![], vec!["not base64!".to_string()], vec![BASE64_STANDARD.encode(b"not a module")]] {
+            let error = build_publish_transaction(&request(modules)).unwrap_err();
+            assert_eq!(error.code, ErrorCode::InvalidArgument);
+        }
+    }
+
+    #[test]
+    fn gas_budget_must_be_within_bounds() {
+        for gas_budget in [1, DEFAULT_GAS_BUDGET, MAX_GAS_BUDGET] {
+            let params = GasParams { gas_budget, ..gas() };
+            assert!(params.resolve().is_ok(), "{}", gas_budget);
+        }
+        for gas_budget in [0, MAX_GAS_BUDGET + 1, u64::MAX] {
+            let params = GasParams { gas_budget, ..gas() };
+            assert_eq!(params.resolve().unwrap_err().code, ErrorCode::InvalidArgument, "{}", gas_budget);
+        }
+    }
+
+    #[test]
+    fn gas_params_require_price_payment_and_sender() {
+        let invalid = [
+            GasParams { gas_price: 0, ..gas() },
+            GasParams { gas_payment: vec![], ..gas() },
+            GasParams { sender: "0xnot-an-address".to_string(), ..gas() },
+        ];
+        for params in invalid {
+            assert_eq!(params.resolve().unwrap_err().code, ErrorCode::InvalidArgument);
+        }
+
+        let params = gas();
+        let (sender, gas_payment) = params.resolve().unwrap();
+        assert_eq!(sender.to_string(), params.sender);
+        assert_eq!(gas_payment[0].0.to_string(), params.gas_payment[0].object_id);
+        assert_eq!(gas_payment[0].2.to_string(), params.gas_payment[0].digest);
+    }
+
+    #[test]
+    fn default_gas_budget_applies_when_omitted() {
+        let (object_id, version, digest) = random_object_ref();
+        let params: GasParams = serde_json::from_value(serde_json::json!({
+            "sender": SuiAddress::random_for_testing_only().to_string(),
+            "gas_price": 1000,
+            "gas_payment": [{ "object_id": object_id.to_string(), "version": version.value(), "digest": digest.to_string() }],
+        }))
+        .unwrap();
+        assert_eq!(params.gas_budget, DEFAULT_GAS_BUDGET);
+    }
+}
@@ -0,0 +1,698 @@
+// Copyright (c) Move Web Compiler
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+use std::time::Instant;
+use anyhow::{Result, anyhow};
+use move_cli::base::test::UnitTestResult;
+use move_unit_test::UnitTestingConfig;
+use serde::{Deserialize, Serialize};
+use sui_move_build::CompiledPackage;
+use sui_protocol_config::ProtocolConfig;
+
+use crate::coverage::{self, CoverageReport};
+use crate::gas_profile::{self, GasProfile, GasProfileOptions, ProfileDir};
+use crate::operations::{self, ErrorCode, OperationError};
+use crate::scheduler;
+
+/// 运行单元测试的子命令名，由本程序的工作进程执行
+///
+/// 测试运行器会把`debug::print`直接写到进程的标准输出，并且在部分错误下直接退出进程，
+/// 因此在独立的工作进程中运行，既能捕获输出，也不会影响Web服务器。
+pub const WORKER_COMMAND: &str = "__unit-test";
+
+/// 单个测试默认允许执行的最大指令数
+pub const DEFAULT_TEST_GAS_LIMIT: u64 = 1_000_000;
+
+/// Web接口允许的单个测试最大指令数
+pub const MAX_TEST_GAS_LIMIT: u64 = 100_000_000;
+
+/// 单元测试选项
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UnitTestOptions {
+    /// 只运行名称包含该字符串的测试
+    pub filter: Option<String>,
+    /// 单个测试允许执行的最大指令数
+    pub gas_limit: u64,
+    /// 按函数分析每个测试的gas
+    #[serde(default)]
+    pub profile: GasProfileOptions,
+    /// 统计字节码覆盖率
+    #[serde(default)]
+    pub coverage: bool,
+}
+
+impl UnitTestOptions {
+    /// 检查Web请求给出的选项：限制指令数上限，过滤条件不能被工作进程当作参数解析
+    pub fn validate(&self) -> Result<(), OperationError> {
+        if self.gas_limit == 0 || self.gas_limit > MAX_TEST_GAS_LIMIT {
+            return Err(OperationError::new(
+                ErrorCode::InvalidArgument,
+                format!("gas_limit必须在 1 到 {} 之间", MAX_TEST_GAS_LIMIT),
+            ));
+        }
+        match &self.filter {
+            Some(filter) => operations::validate_test_filter(filter),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Default for UnitTestOptions {
+    fn default() -> Self {
+        Self {
+            filter: None,
+            gas_limit: DEFAULT_TEST_GAS_LIMIT,
+            profile: GasProfileOptions::default(),
+            coverage: false,
+        }
+    }
+}
+
+/// 测试状态
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TestStatus {
+    /// 通过
+    Pass,
+    /// 失败（未按预期出错、类型错误等）
+    Fail,
+    /// 以abort码中止
+    Abort,
+    /// 超出执行上限
+    Timeout,
+}
+
+impl TestStatus {
+    /// 状态名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TestStatus::Pass => "pass",
+            TestStatus::Fail => "fail",
+            TestStatus::Abort => "abort",
+            TestStatus::Timeout => "timeout",
+        }
+    }
+}
+
+/// 源码位置
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// 单个测试的结果
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TestCaseResult {
+    /// 测试全名，例如`0x0::hello::test_mint`
+    pub name: String,
+    /// 测试所在模块，例如`0x0::hello`
+    pub module: String,
+    /// 测试函数名
+    pub function: String,
+    /// 测试状态
+    pub status: TestStatus,
+    /// abort码
+    pub abort_code: Option<u64>,
+    /// 失败位置
+    pub location: Option<SourceLocation>,
+    /// 失败信息
+    pub message: Option<String>,
+    /// 消耗的gas
+    pub gas_used: Option<u64>,
+    /// 按函数统计的gas，开启gas分析时才有
+    #[serde(default)]
+    pub gas_profile: Option<GasProfile>,
+}
+
+/// 一次测试运行的报告
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct UnitTestReport {
+    /// 所有测试是否通过
+    pub success: bool,
+    /// 每个测试的结果
+    pub tests: Vec<TestCaseResult>,
+    /// 通过的测试数量
+    pub passed: usize,
+    /// 未通过的测试数量
+    pub failed: usize,
+    /// `debug::print`输出
+    pub debug_output: Vec<String>,
+    /// 测试运行器的原始报告
+    pub raw_output: String,
+    /// 根包源文件的行覆盖率，开启覆盖率统计时由调用方根据编译结果生成
+    #[serde(default)]
+    pub coverage: Option<CoverageReport>,
+}
+
+impl UnitTestReport {
+    /// 从测试运行器的文本报告构造结构化报告
+    pub fn from_runner_output(success: bool, raw_output: String) -> Self {
+        let tests = parse_runner_output(&raw_output);
+        let passed = tests.iter().filter(|t| t.status == TestStatus::Pass).count();
+        Self {
+            success,
+            failed: tests.len() - passed,
+            passed,
+            tests,
+            debug_output: vec![],
+            raw_output,
+            coverage: None,
+        }
+    }
+}
+
+/// 在当前进程中运行包的Move单元测试，返回是否全部通过和测试运行器的文本报告
+pub fn run_unit_tests_in_process(package_path: &Path, options: &UnitTestOptions) -> Result<(bool, String)> {
+    // 注册Sui对象运行时等测试所需的原生扩展
+    let () = *sui_move::unit_test::SET_EXTENSION_HOOK;
+
+    let mut build_config = sui_move_build::BuildConfig::new_for_testing().config;
+    build_config.test_mode = true;
+
+    let config = UnitTestingConfig {
+        filter: options.filter.clone(),
+        report_stacktrace_on_abort: true,
+        report_statistics: Some(None),
+        // 单线程运行，保证debug输出按测试顺序出现
+        num_threads: 1,
+        ..UnitTestingConfig::default_with_bound(Some(options.gas_limit))
+    };
+
+    let mut output = Vec::new();
+    let (result, _warnings) = move_cli::base::test::run_move_unit_tests(
+        package_path,
+        build_config,
+        config,
+        sui_move_natives::all_natives(/* silent */ false, &ProtocolConfig::get_for_max_version_UNSAFE()),
+        Some(sui_types::gas_model::tables::initial_cost_schedule_for_unit_tests()),
+        /* compute_coverage */ options.coverage,
+        /* save_disassembly */ false,
+        &mut output,
+    )?;
+
+    Ok((
+        matches!(result, UnitTestResult::Success),
+        String::from_utf8_lossy(&output).to_string(),
+    ))
+}
+
+/// 工作进程没有写入报告就退出了
+///
+/// 测试运行器遇到编译错误时只把诊断打印到标准错误后退出进程，
+/// 调用方可以再编译一次取得结构化诊断。
+#[derive(Debug)]
+pub struct WorkerExited {
+    /// 工作进程的退出状态
+    pub status: ExitStatus,
+    /// 工作进程的标准错误输出
+    pub stderr: String,
+}
+
+impl fmt::Display for WorkerExited {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "测试进程异常退出({}): {}", self.status, self.stderr)
+    }
+}
+
+impl std::error::Error for WorkerExited {}
+
+/// 在工作进程中运行单元测试，捕获`debug::print`输出
+///
+/// 包由工作进程中的测试运行器编译，每次运行只编译一遍。
+/// 开启覆盖率统计时，`package`必须是调用方以测试模式编译的结果，用来把覆盖率数据换算为源码行。
+/// 报告和覆盖率数据写在每次运行独立的临时目录中，同一个包可以同时运行多次。
+/// 开启gas分析时，工作进程在临时目录中运行，虚拟机把每个测试的分析结果写到该目录。
+/// 超过`deadline`时终止工作进程。
+pub fn run_unit_tests(
+    package: Option<&CompiledPackage>,
+    package_path: &Path,
+    options: &UnitTestOptions,
+    deadline: Option<Instant>,
+) -> Result<UnitTestReport> {
+    let worker_package_path = package_path.canonicalize()?;
+    let run_dir = RunDir::create()?;
+    let report_path = run_dir.path().join("unit_test_report.json");
+    let coverage_map_path = run_dir.path().join(coverage::COVERAGE_MAP_FILE);
+
+    let mut command = Command::new(std::env::current_exe()?);
+    command
+        .arg(WORKER_COMMAND)
+        .arg(&worker_package_path)
+        .arg(&report_path)
+        .arg(options.gas_limit.to_string());
+    if options.coverage {
+        command.arg("--coverage").arg(&coverage_map_path);
+    }
+    // 过滤条件放在`--`之后，不会被当作工作进程的参数
+    if let Some(filter) = &options.filter {
+        command.arg("--").arg(filter);
+    }
+    let profile_dir = match options.profile.enabled() {
+        true => Some(ProfileDir::create()?),
+        false => None,
+    };
+    if let Some(profile_dir) = &profile_dir {
+        command.current_dir(profile_dir.path()).env(gas_profile::PROFILER_ENV_VAR, "1");
+    }
+
+    // 测试运行器把覆盖率的跟踪文件固定写在包目录下，同一个包同时只能有一次统计覆盖率的运行
+    let package_lock = options.coverage.then(|| package_lock(&worker_package_path));
+    let _package_guard = package_lock.as_ref().map(|lock| lock.lock().unwrap_or_else(|e| e.into_inner()));
+    let output = scheduler::wait_with_deadline(command.stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?, deadline)
+        .map_err(|e| anyhow!("测试{}", e))?;
+
+    let mut report: UnitTestReport = match std::fs::read_to_string(&report_path) {
+        Ok(content) => serde_json::from_str(&content)?,
+        Err(_) => {
+            return Err(WorkerExited {
+                status: output.status,
+                stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            }
+            .into())
+        }
+    };
+
+    report.debug_output = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.strip_prefix("[debug] "))
+        .map(str::to_string)
+        .collect();
+    if let Some(profile_dir) = &profile_dir {
+        attach_gas_profiles(&mut report.tests, profile_dir.take_profiles(&options.profile)?);
+    }
+    // 覆盖率数据中的字节码偏移按测试模式的编译结果换算为源码行
+    if options.coverage {
+        let package = package.ok_or_else(|| anyhow!("统计覆盖率需要测试模式的编译结果"))?;
+        report.coverage = Some(
+            coverage::package_coverage(package, package_path, &coverage_map_path)
+                .map_err(|e| anyhow!("生成覆盖率报告失败: {}", e))?,
+        );
+    }
+    Ok(report)
+}
+
+// 一次测试运行的临时目录，存放工作进程写入的报告和覆盖率数据，删除时一并删除其中的文件
+struct RunDir {
+    path: PathBuf,
+}
+
+impl RunDir {
+    fn create() -> Result<Self> {
+        let path = std::env::temp_dir().join(format!("move-unit-test-{:016x}", rand::random::<u64>()));
+        std::fs::create_dir_all(&path)?;
+        Ok(Self { path })
+    }
+
+    fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for RunDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
+
+// 每个包目录一把锁，没有运行在使用时自动释放
+fn package_lock(package_path: &Path) -> Arc<Mutex<()>> {
+    static LOCKS: OnceLock<Mutex<HashMap<PathBuf, Weak<Mutex<()>>>>> = OnceLock::new();
+    let mut locks = LOCKS.get_or_init(Default::default).lock().unwrap_or_else(|e| e.into_inner());
+    locks.retain(|_, lock| lock.strong_count() > 0);
+    if let Some(lock) = locks.get(package_path).and_then(Weak::upgrade) {
+        return lock;
+    }
+    let lock = Arc::new(Mutex::new(()));
+    locks.insert(package_path.to_path_buf(), Arc::downgrade(&lock));
+    lock
+}
+
+// 分析结果以测试函数名命名（可能带模块名），按名称对应到测试结果
+//
+// 不同模块中的同名测试都能匹配同一个名称，这时只附加到消耗的gas与分析结果一致的那个测试；
+// 仍无法区分时不附加，避免把一个测试的分析结果显示在另一个测试上。
+fn attach_gas_profiles(tests: &mut [TestCaseResult], profiles: Vec<GasProfile>) {
+    for profile in profiles {
+        let suffix = format!("::{}", profile.name);
+        let candidates: Vec<usize> = tests
+            .iter()
+            .enumerate()
+            .filter(|(_, test)| test.gas_profile.is_none() && (test.name == profile.name || test.name.ends_with(&suffix)))
+            .map(|(index, _)| index)
+            .collect();
+        let index = match candidates.as_slice() {
+            [index] => Some(*index),
+            _ => {
+                let same_gas: Vec<usize> = candidates
+                    .into_iter()
+                    .filter(|index| tests[*index].gas_used == Some(profile.total_gas))
+                    .collect();
+                match same_gas.as_slice() {
+                    [index] => Some(*index),
+                    _ => None,
+                }
+            }
+        };
+        if let Some(index) = index {
+            tests[index].gas_profile = Some(profile);
+        }
+    }
+}
+
+/// 工作进程入口：运行测试并把报告写入`report_path`，运行失败时不写报告
+///
+/// 开启覆盖率统计时，把测试运行器写在包目录下的覆盖率数据移动到`coverage_path`。
+pub fn run_worker(package_path: &Path, report_path: &Path, coverage_path: Option<&Path>, options: &UnitTestOptions) -> Result<()> {
+    let map_path = package_path.join(coverage::COVERAGE_MAP_FILE);
+    if coverage_path.is_some() {
+        let _ = std::fs::remove_file(&map_path);
+    }
+    // 运行失败时不写报告，由调用方按编译错误处理
+    let (success, raw_output) = run_unit_tests_in_process(package_path, options)?;
+    let report = UnitTestReport::from_runner_output(success, raw_output);
+    if let Some(coverage_path) = coverage_path {
+        if map_path.exists() {
+            std::fs::copy(&map_path, coverage_path)?;
+            let _ = std::fs::remove_file(&map_path);
+        }
+    }
+    std::fs::write(report_path, serde_json::to_string(&report)?)?;
+    Ok(())
+}
+
+/// 解析测试运行器的文本报告
+///
+/// 报告由三部分组成：每个测试一行的`[ PASS    ] 0x0::m::t`，
+/// 测试统计表（测试名、耗时、gas），以及每个失败测试以`┌── name ──`开头的诊断块。
+pub fn parse_runner_output(output: &str) -> Vec<TestCaseResult> {
+    let mut tests = Vec::new();
+    let mut current_module = String::new();
+    let mut failure: Option<(String, Vec<&str>)> = None;
+
+    for line in output.lines() {
+        let trimmed = line.trim();
+
+        // 测试结果行
+        if let Some((status, name)) = parse_status_line(trimmed) {
+            let (module, function) = split_test_name(name);
+            tests.push(TestCaseResult {
+                name: name.to_string(),
+                module,
+                function,
+                status,
+                abort_code: None,
+                location: None,
+                message: None,
+                gas_used: None,
+                gas_profile: None,
+            });
+            continue;
+        }
+
+        // 统计表中的行：│ 0x0::m::t │ 0.003 │ 12 │
+        if trimmed.starts_with('│') && failure.is_none() {
+            let cells: Vec<&str> = trimmed.split('│').map(str::trim).filter(|c| !c.is_empty()).collect();
+            if let [name, _time, gas] = cells.as_slice() {
+                if let (Some(test), Ok(gas)) = (tests.iter_mut().find(|t| t.name == *name), gas.parse::<u64>()) {
+                    test.gas_used = Some(gas);
+                }
+            }
+            continue;
+        }
+
+        // 失败块
+        if let Some(module) = trimmed.strip_prefix("Failures in ").and_then(|m| m.strip_suffix(':')) {
+            current_module = module.to_string();
+            continue;
+        }
+        if let Some(rest) = trimmed.strip_prefix("┌── ") {
+            let function = rest.trim_end_matches(|c| c == '─' || c == ' ').to_string();
+            failure = Some((function, Vec::new()));
+            continue;
+        }
+        if trimmed.starts_with('└') {
+            if let Some((function, body)) = failure.take() {
+                let name = format!("{}::{}", current_module, function);
+                if let Some(test) = tests.iter_mut().find(|t| t.name == name) {
+                    apply_failure_details(test, &body);
+                }
+            }
+            continue;
+        }
+        if let Some((_, body)) = failure.as_mut() {
+            body.push(trimmed.trim_start_matches('│').trim_start());
+        }
+    }
+
+    tests
+}
+
+// 解析`[ PASS    ] 0x0::m::t`形式的结果行
+fn parse_status_line(line: &str) -> Option<(TestStatus, &str)> {
+    let rest = line.strip_prefix('[')?;
+    let (status, name) = rest.split_once(']')?;
+    let status = match status.trim() {
+        "PASS" => TestStatus::Pass,
+        "FAIL" => TestStatus::Fail,
+        "TIMEOUT" => TestStatus::Timeout,
+        _ => return None,
+    };
+    let name = name.trim();
+    if name.is_empty() || name.contains(' ') {
+        return None;
+    }
+    Some((status, name))
+}
+
+// 将`0x0::m::t`拆分为模块和函数名
+fn split_test_name(name: &str) -> (String, String) {
+    match name.rsplit_once("::") {
+        Some((module, function)) => (module.to_string(), function.to_string()),
+        None => (String::new(), name.to_string()),
+    }
+}
+
+// 从失败块中提取abort码、位置和失败信息
+fn apply_failure_details(test: &mut TestCaseResult, body: &[&str]) {
+    for line in body {
+        // 位置：┌─ ./sources/m.move:10:9
+        if test.location.is_none() {
+            if let Some(location) = line.strip_prefix("┌─ ") {
+                let mut parts = location.trim().rsplitn(3, ':');
+                if let (Some(column), Some(line_no), Some(file)) = (parts.next(), parts.next(), parts.next()) {
+                    if let (Ok(line_no), Ok(column)) = (line_no.parse(), column.parse()) {
+                        test.location = Some(SourceLocation {
+                            file: file.trim_start_matches("./").to_string(),
+                            line: line_no,
+                            column,
+                        });
+                    }
+                }
+            }
+        }
+
+        // 标注行：│ ^^^^ Test was not expected to error, but it aborted with code 42 originating ...
+        if test.message.is_none() {
+            if let Some(idx) = line.find('^') {
+                let message = line[idx..].trim_start_matches('^').trim();
+                if !message.is_empty() {
+                    test.message = Some(message.to_string());
+                }
+            }
+        }
+
+        // 只取实际的abort码，`Expected test to abort with code 1`中是预期的abort码
+        if test.abort_code.is_none() {
+            if let Some(idx) = line.find("aborted with code ") {
+                let code: String = line[idx + "aborted with code ".len()..]
+                    .chars()
+                    .take_while(|c| c.is_ascii_alphanumeric())
+                    .collect();
+                test.abort_code = parse_abort_code(&code);
+            }
+        }
+    }
+
+    // 超时等没有源码标注的失败块只有一行说明
+    if test.message.is_none() {
+        test.message = body.iter().find(|line| !line.is_empty()).map(|line| line.to_string());
+    }
+    if test.status == TestStatus::Fail && test.abort_code.is_some() {
+        test.status = TestStatus::Abort;
+    }
+}
+
+// abort码可能是十进制或0x开头的十六进制
+fn parse_abort_code(code: &str) -> Option<u64> {
+    match code.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => code.parse().ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // `sui move test`对一个包含通过、abort、未按预期abort和超时测试的包输出的报告
+    const RUNNER_OUTPUT: &str = include_str!("../tests/golden/unit_test/runner_output.txt");
+
+    fn find<'a>(tests: &'a [TestCaseResult], function: &str) -> &'a TestCaseResult {
+        tests.iter().find(|test| test.function == function).unwrap()
+    }
+
+    fn test_case(module: &str, function: &str, gas_used: u64) -> TestCaseResult {
+        TestCaseResult {
+            name: format!("{}::{}", module, function),
+            module: module.to_string(),
+            function: function.to_string(),
+            status: TestStatus::Pass,
+            abort_code: None,
+            location: None,
+            message: None,
+            gas_used: Some(gas_used),
+            gas_profile: None,
+        }
+    }
+
+    fn profile(name: &str, total_gas: u64) -> GasProfile {
+        GasProfile {
+            name: name.to_string(),
+            total_gas,
+            instructions: 0,
+            native_calls: 0,
+            storage_bytes: None,
+            functions: vec![],
+            folded_stacks: None,
+        }
+    }
+
+    // 每个测试附加的分析结果的总gas
+    fn attached(tests: &[TestCaseResult]) -> Vec<Option<u64>> {
+        tests.iter().map(|test| test.gas_profile.as_ref().map(|p| p.total_gas)).collect()
+    }
+
+    #[test]
+    fn parses_status_lines() {
+        let tests = parse_runner_output(RUNNER_OUTPUT);
+        let statuses: Vec<(&str, &str, TestStatus)> = tests
+            .iter()
+            .map(|test| (test.module.as_str(), test.function.as_str(), test.status))
+            .collect();
+        assert_eq!(statuses, [
+            ("0x0::hello", "test_mint", TestStatus::Pass),
+            ("0x0::hello", "test_abort", TestStatus::Abort),
+            ("0x0::hello", "test_no_abort", TestStatus::Fail),
+            ("0x0::hello", "test_loop", TestStatus::Timeout),
+        ]);
+        assert_eq!(tests[0].name, "0x0::hello::test_mint");
+    }
+
+    #[test]
+    fn parses_statistics_table() {
+        let tests = parse_runner_output(RUNNER_OUTPUT);
+        assert_eq!(find(&tests, "test_mint").gas_used, Some(29));
+        assert_eq!(find(&tests, "test_abort").gas_used, Some(14));
+        assert_eq!(find(&tests, "test_no_abort").gas_used, Some(3));
+        assert_eq!(find(&tests, "test_loop").gas_used, Some(1_000_001));
+    }
+
+    #[test]
+    fn parses_abort_failure() {
+        let tests = parse_runner_output(RUNNER_OUTPUT);
+        let test = find(&tests, "test_abort");
+        assert_eq!(test.abort_code, Some(42));
+        assert_eq!(test.location, Some(SourceLocation {
+            file: "sources/hello.move".to_string(),
+            line: 31,
+            column: 9,
+        }));
+        assert_eq!(
+            test.message.as_deref(),
+            Some("Test was not expected to error, but it aborted with code 42 originating in the module 0x0::hello rooted here")
+        );
+
+        let passed = find(&tests, "test_mint");
+        assert_eq!((passed.abort_code, &passed.location, &passed.message), (None, &None, &None));
+    }
+
+    #[test]
+    fn ignores_expected_abort_code() {
+        let tests = parse_runner_output(RUNNER_OUTPUT);
+        let test = find(&tests, "test_no_abort");
+        assert_eq!(test.abort_code, None);
+        assert_eq!(test.location.as_ref().map(|location| location.line), Some(36));
+        assert_eq!(
+            test.message.as_deref(),
+            Some("Test did not error as expected. Expected test to abort with code 1")
+        );
+    }
+
+    #[test]
+    fn parses_timeout() {
+        let tests = parse_runner_output(RUNNER_OUTPUT);
+        let test = find(&tests, "test_loop");
+        assert_eq!(test.status, TestStatus::Timeout);
+        assert_eq!(test.message.as_deref(), Some("Test timed out"));
+        assert_eq!(test.location, None);
+    }
+
+    #[test]
+    fn counts_results() {
+        let report = UnitTestReport::from_runner_output(false, RUNNER_OUTPUT.to_string());
+        assert_eq!((report.passed, report.failed), (1, 3));
+        assert_eq!(report.tests.len(), 4);
+    }
+
+    #[test]
+    fn parses_hex_abort_codes() {
+        assert_eq!(parse_abort_code("42"), Some(42));
+        assert_eq!(parse_abort_code("0x2a"), Some(42));
+        assert_eq!(parse_abort_code("x"), None);
+    }
+
+    #[test]
+    fn attaches_profiles_by_name() {
+        let mut tests = vec![
+            test_case("0x0::hello", "test_mint", 29),
+            test_case("0x0::hello", "test_mint_twice", 58),
+            test_case("0x0::coin_tests", "test_burn", 12),
+        ];
+        attach_gas_profiles(&mut tests, vec![
+            profile("test_mint_twice", 58),
+            profile("coin_tests::test_burn", 12),
+            profile("0x0::hello::test_mint", 29),
+            profile("test_missing", 1),
+        ]);
+        assert_eq!(attached(&tests), [Some(29), Some(58), Some(12)]);
+    }
+
+    #[test]
+    fn attaches_same_named_tests_by_gas() {
+        let mut tests = vec![
+            test_case("0x0::a", "test_mint", 10),
+            test_case("0x0::b", "test_mint", 20),
+            test_case("0x0::b_tests", "test_mint", 30),
+        ];
+        attach_gas_profiles(&mut tests, vec![profile("test_mint", 20), profile("test_mint", 10)]);
+        assert_eq!(attached(&tests), [Some(10), Some(20), None]);
+
+        // 模块名也能区分时不需要比较gas
+        attach_gas_profiles(&mut tests, vec![profile("b_tests::test_mint", 31)]);
+        assert_eq!(attached(&tests), [Some(10), Some(20), Some(31)]);
+    }
+
+    #[test]
+    fn skips_ambiguous_profiles() {
+        let mut tests = vec![test_case("0x0::a", "test_mint", 10), test_case("0x0::b", "test_mint", 10)];
+        attach_gas_profiles(&mut tests, vec![profile("test_mint", 10), profile("test_mint", 99)]);
+        assert_eq!(attached(&tests), [None, None]);
+    }
+}
@@ -0,0 +1,593 @@
+// Copyright (c) Move Web Compiler
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::BTreeMap;
+use std::fmt;
+use move_binary_format::access::ModuleAccess;
+use move_binary_format::file_format::{CompiledModule, Visibility};
+use move_binary_format::normalized;
+use move_core_types::account_address::AccountAddress;
+use move_core_types::ident_str;
+use serde::{Deserialize, Serialize};
+use sui_types::SUI_FRAMEWORK_PACKAGE_ID;
+use sui_types::move_package::MovePackage;
+use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
+use sui_types::transaction::{ObjectArg, TransactionData};
+
+use crate::operations::{ErrorCode, OperationError};
+use crate::rpc::SuiRpc;
+use crate::transaction::{self, GasParams, UnsignedTransaction};
+
+/// 升级策略，只能比`UpgradeCap`当前的策略更严格
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpgradePolicy {
+    /// 可以修改函数实现、增加新的类型和函数，公开的函数签名和结构体布局保持不变
+    Compatible,
+    /// 只能增加新的类型和函数，已有的部分保持不变
+    Additive,
+    /// 只能更新依赖，模块本身保持不变
+    DepOnly,
+}
+
+impl UpgradePolicy {
+    /// 链上`0x2::package`使用的策略编号
+    pub fn as_u8(self) -> u8 {
+        match self {
+            UpgradePolicy::Compatible => 0,
+            UpgradePolicy::Additive => 128,
+            UpgradePolicy::DepOnly => 192,
+        }
+    }
+
+    /// 从链上的策略编号解析
+    pub fn from_u8(policy: u8) -> Option<Self> {
+        match policy {
+            0 => Some(UpgradePolicy::Compatible),
+            128 => Some(UpgradePolicy::Additive),
+            192 => Some(UpgradePolicy::DepOnly),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for UpgradePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UpgradePolicy::Compatible => write!(f, "compatible"),
+            UpgradePolicy::Additive => write!(f, "additive"),
+            UpgradePolicy::DepOnly => write!(f, "dep_only"),
+        }
+    }
+}
+
+/// 违反的兼容性规则
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CompatibilityRule {
+    /// 删除了已发布的模块
+    ModuleRemoved,
+    /// dep_only策略下增加了模块
+    ModuleAdded,
+    /// 删除了结构体
+    StructRemoved,
+    /// 修改了结构体的能力
+    StructAbilitiesChanged,
+    /// 修改了结构体的类型参数或其约束
+    StructTypeParametersChanged,
+    /// 修改了结构体的字段
+    StructLayoutChanged,
+    /// dep_only策略下增加了结构体
+    StructAdded,
+    /// 删除了公开函数，或将其改为非公开
+    PublicFunctionRemoved,
+    /// 修改了公开函数的参数、返回值或类型参数约束
+    PublicFunctionSignatureChanged,
+    /// additive或dep_only策略下删除或修改了friend/entry函数
+    FunctionChanged,
+    /// dep_only策略下增加了函数
+    FunctionAdded,
+    /// additive或dep_only策略下删除了friend声明
+    FriendRemoved,
+    /// dep_only策略下增加了friend声明
+    FriendAdded,
+}
+
+/// 一处兼容性问题
+#[derive(Clone, Debug, Serialize)]
+pub struct CompatibilityViolation {
+    /// 违反的规则
+    pub rule: CompatibilityRule,
+    /// 模块名
+    pub module: String,
+    /// 结构体、函数或friend的名称，模块级问题时为空
+    pub item: Option<String>,
+    /// 说明
+    pub message: String,
+}
+
+/// 按策略检查新模块与链上模块的兼容性，返回全部问题
+///
+/// 只检查接口层面的规则（结构体布局、函数签名、增删的模块和成员），
+/// additive和dep_only策略下函数体是否变化由链上在执行升级时校验。
+/// 新模块通常以`0x0`编译，与链上执行升级时一样，先把它们的自身地址替换为原始包ID再比较。
+pub fn check_compatibility(
+    package_address: AccountAddress,
+    old_modules: &[CompiledModule],
+    new_modules: &[CompiledModule],
+    policy: UpgradePolicy,
+) -> Vec<CompatibilityViolation> {
+    let mut violations = Vec::new();
+    let old: BTreeMap<String, normalized::Module> = old_modules
+        .iter()
+        .map(|module| (module.self_id().name().to_string(), normalized::Module::new(module)))
+        .collect();
+    let new: BTreeMap<String, normalized::Module> = new_modules
+        .iter()
+        .map(|module| {
+            let module = with_self_address(module, package_address);
+            (module.self_id().name().to_string(), normalized::Module::new(&module))
+        })
+        .collect();
+
+    if policy == UpgradePolicy::DepOnly {
+        for name in new.keys().filter(|name| !old.contains_key(*name)) {
+            violations.push(violation(CompatibilityRule::ModuleAdded, name, None, "dep_only策略不允许增加模块"));
+        }
+    }
+
+    for (name, old_module) in &old {
+        match new.get(name) {
+            Some(new_module) => check_module(name, old_module, new_module, policy, &mut violations),
+            None => violations.push(violation(CompatibilityRule::ModuleRemoved, name, None, "不允许删除已发布的模块")),
+        }
+    }
+    violations
+}
+
+// 把模块的自身地址替换为`address`，同一地址下对包内其他模块的引用共用这一项，随之一起替换
+fn with_self_address(module: &CompiledModule, address: AccountAddress) -> CompiledModule {
+    let mut module = module.clone();
+    let index = module.self_handle().address.0 as usize;
+    module.address_identifiers[index] = address;
+    module
+}
+
+// 检查单个模块，按名称比较成员
+fn check_module(
+    module: &str,
+    old: &normalized::Module,
+    new: &normalized::Module,
+    policy: UpgradePolicy,
+    violations: &mut Vec<CompatibilityViolation>,
+) {
+    for (name, old_struct) in &old.structs {
+        let item = Some(name.to_string());
+        let Some(new_struct) = new.structs.get(name) else {
+            violations.push(violation(CompatibilityRule::StructRemoved, module, item, "不允许删除结构体"));
+            continue;
+        };
+        if old_struct.abilities != new_struct.abilities {
+            violations.push(violation(
+                CompatibilityRule::StructAbilitiesChanged,
+                module,
+                item.clone(),
+                format!("能力从 {:?} 变为 {:?}", old_struct.abilities, new_struct.abilities),
+            ));
+        }
+        if old_struct.type_parameters != new_struct.type_parameters {
+            violations.push(violation(
+                CompatibilityRule::StructTypeParametersChanged,
+                module,
+                item.clone(),
+                "不允许修改类型参数、约束或phantom标记",
+            ));
+        }
+        if old_struct.fields != new_struct.fields {
+            violations.push(violation(
+                CompatibilityRule::StructLayoutChanged,
+                module,
+                item,
+                "不允许增加、删除、重命名、重排字段或修改字段类型",
+            ));
+        }
+    }
+    if policy == UpgradePolicy::DepOnly {
+        for name in new.structs.keys().filter(|name| !old.structs.contains_key(*name)) {
+            violations.push(violation(
+                CompatibilityRule::StructAdded,
+                module,
+                Some(name.to_string()),
+                "dep_only策略不允许增加结构体",
+            ));
+        }
+    }
+
+    for (name, old_function) in &old.exposed_functions {
+        let item = Some(name.to_string());
+        let new_function = new.exposed_functions.get(name);
+        if old_function.visibility == Visibility::Public {
+            match new_function {
+                Some(new_function) if new_function.visibility == Visibility::Public => {
+                    if !same_signature(old_function, new_function) {
+                        violations.push(violation(
+                            CompatibilityRule::PublicFunctionSignatureChanged,
+                            module,
+                            item.clone(),
+                            "不允许修改公开函数的参数、返回值或类型参数约束",
+                        ));
+                    } else if policy != UpgradePolicy::Compatible && old_function.is_entry != new_function.is_entry {
+                        violations.push(violation(
+                            CompatibilityRule::FunctionChanged,
+                            module,
+                            item.clone(),
+                            format!("{}策略不允许修改entry标记", policy),
+                        ));
+                    }
+                }
+                _ => violations.push(violation(
+                    CompatibilityRule::PublicFunctionRemoved,
+                    module,
+                    item.clone(),
+                    "不允许删除公开函数或将其改为非公开",
+                )),
+            }
+        } else if policy != UpgradePolicy::Compatible {
+            let unchanged = new_function.is_some_and(|new_function| {
+                new_function.visibility == old_function.visibility
+                    && new_function.is_entry == old_function.is_entry
+                    && same_signature(old_function, new_function)
+            });
+            if !unchanged {
+                violations.push(violation(
+                    CompatibilityRule::FunctionChanged,
+                    module,
+                    item,
+                    format!("{}策略不允许删除或修改已有的函数", policy),
+                ));
+            }
+        }
+    }
+    if policy == UpgradePolicy::DepOnly {
+        for name in new.exposed_functions.keys().filter(|name| !old.exposed_functions.contains_key(*name)) {
+            violations.push(violation(
+                CompatibilityRule::FunctionAdded,
+                module,
+                Some(name.to_string()),
+                "dep_only策略不允许增加函数",
+            ));
+        }
+    }
+
+    if policy != UpgradePolicy::Compatible {
+        for friend in old.friends.iter().filter(|friend| !new.friends.contains(friend)) {
+            violations.push(violation(
+                CompatibilityRule::FriendRemoved,
+                module,
+                Some(friend.name().to_string()),
+                format!("{}策略不允许删除friend声明", policy),
+            ));
+        }
+    }
+    if policy == UpgradePolicy::DepOnly {
+        for friend in new.friends.iter().filter(|friend| !old.friends.contains(friend)) {
+            violations.push(violation(
+                CompatibilityRule::FriendAdded,
+                module,
+                Some(friend.name().to_string()),
+                "dep_only策略不允许增加friend声明",
+            ));
+        }
+    }
+}
+
+fn same_signature(old: &normalized::Function, new: &normalized::Function) -> bool {
+    old.type_parameters == new.type_parameters && old.parameters == new.parameters && old.return_ == new.return_
+}
+
+fn violation(
+    rule: CompatibilityRule,
+    module: &str,
+    item: Option<String>,
+    message: impl Into<String>,
+) -> CompatibilityViolation {
+    CompatibilityViolation {
+        rule,
+        module: module.to_string(),
+        item,
+        message: message.into(),
+    }
+}
+
+/// 构建升级交易的请求
+#[derive(Clone, Debug, Deserialize)]
+pub struct UpgradeTransactionRequest {
+    /// Base64编码的新模块字节码，按编译接口返回的发布顺序排列
+    pub modules: Vec<String>,
+    /// 依赖的已发布包ID，即编译接口返回的`dependencies`
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+    /// 原始包ID，即第一次发布时的包ID，升级后模块的自身地址会被替换为它
+    pub package_id: String,
+    /// 包的`UpgradeCap`对象ID
+    pub upgrade_cap: String,
+    /// 升级策略
+    pub policy: UpgradePolicy,
+    /// 发送方和gas参数，发送方必须持有`UpgradeCap`
+    #[serde(flatten)]
+    pub gas: GasParams,
+}
+
+/// 升级检查和交易构建的结果
+#[derive(Clone, Debug, Serialize)]
+pub struct UpgradeOutcome {
+    /// 当前最新版本的包ID，升级交易基于它
+    pub current_package_id: String,
+    /// 当前版本号
+    pub current_version: u64,
+    /// `UpgradeCap`当前的策略
+    pub cap_policy: Option<UpgradePolicy>,
+    /// 兼容性问题，非空时不构建交易
+    pub violations: Vec<CompatibilityViolation>,
+    /// 未签名的升级交易
+    pub transaction: Option<UnsignedTransaction>,
+}
+
+/// 从节点读取`UpgradeCap`和链上模块，检查兼容性后构建未签名的升级交易
+///
+/// 交易依次调用`0x2::package::authorize_upgrade`、升级命令和`0x2::package::commit_upgrade`，
+/// 和`sui client upgrade`生成的交易相同。
+pub async fn build_upgrade_transaction(
+    rpc: &SuiRpc,
+    request: &UpgradeTransactionRequest,
+) -> Result<UpgradeOutcome, OperationError> {
+    let (sender, gas_payment) = request.gas.resolve()?;
+    let original_id = transaction::parse_object_id(&request.package_id)?;
+    let cap_id = transaction::parse_object_id(&request.upgrade_cap)?;
+    let dependencies = request
+        .dependencies
+        .iter()
+        .map(|id| transaction::parse_object_id(id))
+        .collect::<Result<Vec<_>, _>>()?;
+    let module_bytes = transaction::decode_modules(&request.modules)?;
+    let new_modules = deserialize_modules(&module_bytes)?;
+
+    let (cap_ref, cap) = rpc.get_upgrade_cap(cap_id).await?;
+    let cap_policy = UpgradePolicy::from_u8(cap.policy);
+    if request.policy.as_u8() < cap.policy {
+        return Err(OperationError::new(
+            ErrorCode::InvalidArgument,
+            format!(
+                "UpgradeCap的策略为 {}，不能使用更宽松的 {}",
+                cap_policy.map_or_else(|| cap.policy.to_string(), |policy| policy.to_string()),
+                request.policy
+            ),
+        ));
+    }
+    let current_id = cap.package.bytes;
+    let old_modules = deserialize_modules(&rpc.get_package_modules(current_id).await?)?;
+
+    let mut outcome = UpgradeOutcome {
+        current_package_id: current_id.to_string(),
+        current_version: cap.version,
+        cap_policy,
+        violations: check_compatibility(original_id.into(), &old_modules, &new_modules, request.policy),
+        transaction: None,
+    };
+    if !outcome.violations.is_empty() {
+        return Ok(outcome);
+    }
+
+    let digest = MovePackage::compute_digest_for_modules_and_deps(&module_bytes, &dependencies, true);
+    let mut builder = ProgrammableTransactionBuilder::new();
+    let cap_arg = builder
+        .obj(ObjectArg::ImmOrOwnedObject(cap_ref))
+        .map_err(|e| OperationError::new(ErrorCode::InvalidArgument, e.to_string()))?;
+    let policy_arg = builder
+        .pure(request.policy.as_u8())
+        .map_err(|e| OperationError::new(ErrorCode::InvalidArgument, e.to_string()))?;
+    let digest_arg = builder
+        .pure(digest.to_vec())
+        .map_err(|e| OperationError::new(ErrorCode::InvalidArgument, e.to_string()))?;
+    let ticket = builder.programmable_move_call(
+        SUI_FRAMEWORK_PACKAGE_ID,
+        ident_str!("package").to_owned(),
+        ident_str!("authorize_upgrade").to_owned(),
+        vec![],
+        vec![cap_arg, policy_arg, digest_arg],
+    );
+    let receipt = builder.upgrade(current_id, ticket, dependencies.clone(), module_bytes);
+    builder.programmable_move_call(
+        SUI_FRAMEWORK_PACKAGE_ID,
+        ident_str!("package").to_owned(),
+        ident_str!("commit_upgrade").to_owned(),
+        vec![],
+        vec![cap_arg, receipt],
+    );
+
+    let data = TransactionData::new_programmable(
+        sender,
+        gas_payment,
+        builder.finish(),
+        request.gas.gas_budget,
+        request.gas.gas_price,
+    );
+    outcome.transaction = Some(UnsignedTransaction::new(&data, &dependencies)?);
+    Ok(outcome)
+}
+
+fn deserialize_modules(modules: &[Vec<u8>]) -> Result<Vec<CompiledModule>, OperationError> {
+    modules
+        .iter()
+        .map(|bytes| {
+            CompiledModule::deserialize(bytes).map_err(|e| {
+                OperationError::new(ErrorCode::InvalidArgument, format!("无法解析模块字节码: {}", e))
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    use crate::compiler;
+
+    // 链上的原始包ID，旧模块以它编译，新模块与发布前一样以0x0编译
+    const ORIGINAL: &str = "0xcafe";
+
+    fn compile(sources: &[&str], address: &str) -> Vec<CompiledModule> {
+        let dir = std::env::temp_dir().join(format!("move-upgrade-test-{:016x}", rand::random::<u64>()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let targets: Vec<PathBuf> = sources
+            .iter()
+            .enumerate()
+            .map(|(index, source)| {
+                let path = dir.join(format!("m{}.move", index));
+                std::fs::write(&path, source.replace("0x0::", &format!("{}::", address))).unwrap();
+                path
+            })
+            .collect();
+        let result = compiler::build_sources(&targets, &[], &[], &dir);
+        let _ = std::fs::remove_dir_all(&dir);
+        let (modules, _) = result.unwrap_or_else(|e| panic!("编译失败: {:?}", e.diagnostics));
+        let base64: Vec<String> = modules.into_iter().map(|module| module.base64).collect();
+        deserialize_modules(&transaction::decode_modules(&base64).unwrap()).unwrap()
+    }
+
+    fn check_at(package_address: &str, old: &[&str], new: &[&str], policy: UpgradePolicy) -> Vec<(CompatibilityRule, String)> {
+        check_compatibility(
+            AccountAddress::from_hex_literal(package_address).unwrap(),
+            &compile(old, ORIGINAL),
+            &compile(new, "0x0"),
+            policy,
+        )
+        .into_iter()
+        .map(|violation| (violation.rule, violation.item.unwrap_or(violation.module)))
+        .collect()
+    }
+
+    fn check(old: &str, new: &str, policy: UpgradePolicy) -> Vec<(CompatibilityRule, String)> {
+        check_at(ORIGINAL, &[old], &[new], policy)
+    }
+
+    fn rules(rule: CompatibilityRule, item: &str) -> Vec<(CompatibilityRule, String)> {
+        vec![(rule, item.to_string())]
+    }
+
+    #[test]
+    fn unchanged_package_is_compatible_under_every_policy() {
+        let source = "module 0x0::m { struct A has copy, drop { x: u64 } public fun f(a: A): u64 { a.x } }";
+        for policy in [UpgradePolicy::Compatible, UpgradePolicy::Additive, UpgradePolicy::DepOnly] {
+            assert!(check(source, source, policy).is_empty(), "{}", policy);
+        }
+    }
+
+    #[test]
+    fn detects_removed_struct() {
+        let old = "module 0x0::m { struct A has drop { x: u64 } struct B has drop { x: u64 } }";
+        let new = "module 0x0::m { struct A has drop { x: u64 } }";
+        assert_eq!(check(old, new, UpgradePolicy::Compatible), rules(CompatibilityRule::StructRemoved, "B"));
+    }
+
+    #[test]
+    fn detects_changed_abilities() {
+        let old = "module 0x0::m { struct A has copy, drop { x: u64 } }";
+        let new = "module 0x0::m { struct A has drop { x: u64 } }";
+        assert_eq!(check(old, new, UpgradePolicy::Compatible), rules(CompatibilityRule::StructAbilitiesChanged, "A"));
+    }
+
+    #[test]
+    fn detects_reordered_fields() {
+        let old = "module 0x0::m { struct A has drop { x: u64, y: bool } }";
+        let new = "module 0x0::m { struct A has drop { y: bool, x: u64 } }";
+        assert_eq!(check(old, new, UpgradePolicy::Compatible), rules(CompatibilityRule::StructLayoutChanged, "A"));
+    }
+
+    #[test]
+    fn detects_changed_public_signature() {
+        let old = "module 0x0::m { public fun f(x: u64): u64 { x } }";
+        let new = "module 0x0::m { public fun f(x: u8): u64 { (x as u64) } }";
+        assert_eq!(
+            check(old, new, UpgradePolicy::Compatible),
+            rules(CompatibilityRule::PublicFunctionSignatureChanged, "f")
+        );
+
+        // 只修改函数体是兼容的
+        let new = "module 0x0::m { public fun f(x: u64): u64 { x + 1 } }";
+        assert!(check(old, new, UpgradePolicy::Compatible).is_empty());
+    }
+
+    #[test]
+    fn detects_public_function_made_non_public() {
+        let old = "module 0x0::m { public fun f(): u64 { 1 } }";
+        for new in [
+            "module 0x0::m { fun f(): u64 { 1 } }",
+            "module 0x0::m { public(friend) fun f(): u64 { 1 } }",
+            "module 0x0::m { }",
+        ] {
+            assert_eq!(
+                check(old, new, UpgradePolicy::Compatible),
+                rules(CompatibilityRule::PublicFunctionRemoved, "f"),
+                "{}",
+                new
+            );
+        }
+    }
+
+    #[test]
+    fn entry_changes_are_rejected_under_additive() {
+        let old = "module 0x0::m { public fun f() {} entry fun g() {} }";
+        let new = "module 0x0::m { public entry fun f() {} fun g() {} }";
+        assert!(check(old, new, UpgradePolicy::Compatible).is_empty());
+        assert_eq!(
+            check(old, new, UpgradePolicy::Additive),
+            vec![
+                (CompatibilityRule::FunctionChanged, "f".to_string()),
+                (CompatibilityRule::FunctionChanged, "g".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn dep_only_rejects_additions() {
+        let old = "module 0x0::m { public fun f() {} }";
+        let new = "module 0x0::m { public fun f() {} public fun g() {} }";
+        assert!(check(old, new, UpgradePolicy::Additive).is_empty());
+        assert_eq!(check(old, new, UpgradePolicy::DepOnly), rules(CompatibilityRule::FunctionAdded, "g"));
+
+        let added = "module 0x0::n { }";
+        assert!(check_at(ORIGINAL, &[old], &[old, added], UpgradePolicy::Additive).is_empty());
+        assert_eq!(
+            check_at(ORIGINAL, &[old], &[old, added], UpgradePolicy::DepOnly),
+            rules(CompatibilityRule::ModuleAdded, "n")
+        );
+
+        let befriended = "module 0x0::m { friend 0x0::n; public fun f() {} }";
+        assert!(check_at(ORIGINAL, &[old, added], &[befriended, added], UpgradePolicy::Additive).is_empty());
+        assert_eq!(
+            check_at(ORIGINAL, &[old, added], &[befriended, added], UpgradePolicy::DepOnly),
+            rules(CompatibilityRule::FriendAdded, "n")
+        );
+        assert_eq!(
+            check_at(ORIGINAL, &[befriended, added], &[old, added], UpgradePolicy::Additive),
+            rules(CompatibilityRule::FriendRemoved, "n")
+        );
+    }
+
+    #[test]
+    fn zero_address_modules_match_the_original_package() {
+        let m = "module 0x0::m { friend 0x0::n; struct S has drop { x: u64 } public fun new(): S { S { x: 1 } } }";
+        let n = "module 0x0::n { use 0x0::m; public fun make(): m::S { m::new() } public fun take(_s: m::S) {} }";
+        for policy in [UpgradePolicy::Compatible, UpgradePolicy::Additive, UpgradePolicy::DepOnly] {
+            assert!(check_at(ORIGINAL, &[m, n], &[m, n], policy).is_empty(), "{}", policy);
+        }
+
+        // 按其他地址比较时，引用包内类型的签名和friend声明都不一致
+        let violations = check_at("0xbeef", &[m, n], &[m, n], UpgradePolicy::Additive);
+        assert!(violations.contains(&(CompatibilityRule::PublicFunctionSignatureChanged, "make".to_string())));
+        assert!(violations.contains(&(CompatibilityRule::PublicFunctionSignatureChanged, "take".to_string())));
+        assert!(violations.contains(&(CompatibilityRule::FriendRemoved, "n".to_string())));
+    }
+}
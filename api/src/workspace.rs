@@ -0,0 +1,364 @@
+// Copyright (c) Move Web Compiler
+// SPDX-License-Identifier: Apache-2.0
+
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use anyhow::{Result, anyhow};
+use walkdir::WalkDir;
+
+/// 默认的工作区保留时间（秒），创建超过该时间的工作区会被清理
+pub const DEFAULT_WORKSPACE_TTL_SECS: u64 = 3600;
+
+/// 默认的工作区磁盘配额（MB）
+pub const DEFAULT_WORKSPACE_MAX_MB: u64 = 2048;
+
+/// 默认的清理间隔（秒）
+pub const DEFAULT_REAP_INTERVAL_SECS: u64 = 60;
+
+/// 超出配额时不会清理最近这段时间内创建的工作区，避免删除正在编译的目录
+pub const MIN_EVICTION_AGE: Duration = Duration::from_secs(300);
+
+/// 每个请求独立的构建工作区配置
+#[derive(Clone, Debug)]
+pub struct WorkspaceConfig {
+    /// 工作区根目录，每个请求占用一个随机命名的子目录
+    pub root: PathBuf,
+    /// 保留时间，创建超过该时间的工作区会被清理
+    pub ttl: Duration,
+    /// 所有工作区占用的最大磁盘空间（字节）
+    pub max_total_bytes: u64,
+    /// 后台清理的间隔
+    pub reap_interval: Duration,
+}
+
+/// 一次清理的结果
+#[derive(Clone, Debug, Default)]
+pub struct ReapStats {
+    /// 删除的工作区数量
+    pub removed: usize,
+    /// 释放的字节数
+    pub freed_bytes: u64,
+    /// 清理后剩余占用的字节数
+    pub remaining_bytes: u64,
+}
+
+// 根目录下的单个工作区
+struct WorkspaceEntry {
+    path: PathBuf,
+    created: SystemTime,
+    size: u64,
+}
+
+impl Default for WorkspaceConfig {
+    fn default() -> Self {
+        Self {
+            root: std::env::temp_dir().join("move-web-compiler-workspaces"),
+            ttl: Duration::from_secs(DEFAULT_WORKSPACE_TTL_SECS),
+            max_total_bytes: DEFAULT_WORKSPACE_MAX_MB * 1024 * 1024,
+            reap_interval: Duration::from_secs(DEFAULT_REAP_INTERVAL_SECS),
+        }
+    }
+}
+
+impl WorkspaceConfig {
+    /// 从环境变量读取配置：
+    /// `MOVE_WORKSPACE_ROOT`、`MOVE_WORKSPACE_TTL_SECS`、`MOVE_WORKSPACE_MAX_MB`、`MOVE_WORKSPACE_REAP_INTERVAL_SECS`
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Some(root) = std::env::var_os("MOVE_WORKSPACE_ROOT") {
+            config.root = PathBuf::from(root);
+        }
+        if let Some(ttl) = env_u64("MOVE_WORKSPACE_TTL_SECS") {
+            config.ttl = Duration::from_secs(ttl);
+        }
+        if let Some(max_mb) = env_u64("MOVE_WORKSPACE_MAX_MB") {
+            config.max_total_bytes = max_mb * 1024 * 1024;
+        }
+        if let Some(interval) = env_u64("MOVE_WORKSPACE_REAP_INTERVAL_SECS") {
+            config.reap_interval = Duration::from_secs(interval.max(1));
+        }
+        config
+    }
+
+    /// 创建一个以创建时间和随机数命名的工作区，超出磁盘配额时先清理旧工作区
+    ///
+    /// 每次创建前重新统计占用，统计、清理和创建在同一把锁内完成，并发的请求不会同时通过配额检查。
+    /// 已创建的工作区在之后的编译中继续增长，下一次创建时计入。
+    pub fn create(&self) -> Result<PathBuf> {
+        std::fs::create_dir_all(&self.root)?;
+
+        let _lock = quota_lock();
+        if self.usage()? >= self.max_total_bytes {
+            let stats = self.reap_unlocked()?;
+            if stats.remaining_bytes >= self.max_total_bytes {
+                return Err(anyhow!(
+                    "构建目录已超出磁盘配额（{} MB），请稍后重试",
+                    self.max_total_bytes / 1024 / 1024
+                ));
+            }
+        }
+
+        // create_dir在目录已存在时报错，保证不会与其他请求共用目录
+        for _ in 0..8 {
+            let path = self.root.join(workspace_name(SystemTime::now()));
+            match std::fs::create_dir(&path) {
+                Ok(()) => return Ok(path),
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Err(anyhow!("无法创建唯一的工作区目录"))
+    }
+
+    /// 路径是否是根目录下的某个工作区
+    pub fn contains(&self, path: &Path) -> bool {
+        match (path.canonicalize(), self.root.canonicalize()) {
+            (Ok(path), Ok(root)) => path.parent() == Some(root.as_path()),
+            _ => false,
+        }
+    }
+
+    /// 删除一个工作区，不在根目录下的路径会被拒绝
+    pub fn remove(&self, path: &Path) -> Result<()> {
+        if !self.contains(path) {
+            return Err(anyhow!("{:?} 不是工作区目录", path));
+        }
+        std::fs::remove_dir_all(path)?;
+        Ok(())
+    }
+
+    /// 所有工作区占用的字节数
+    pub fn usage(&self) -> Result<u64> {
+        Ok(self.entries()?.iter().map(|entry| entry.size).sum())
+    }
+
+    /// 删除过期的工作区，仍超出配额时从最早创建的开始删除
+    pub fn reap(&self) -> Result<ReapStats> {
+        let _lock = quota_lock();
+        self.reap_unlocked()
+    }
+
+    fn reap_unlocked(&self) -> Result<ReapStats> {
+        let now = SystemTime::now();
+        let age = |entry: &WorkspaceEntry| now.duration_since(entry.created).unwrap_or_default();
+
+        let mut entries = self.entries()?;
+        entries.sort_by_key(|entry| entry.created);
+
+        let mut stats = ReapStats::default();
+        let mut remaining = Vec::new();
+        for entry in entries {
+            if age(&entry) >= self.ttl && remove_entry(&entry, &mut stats) {
+                continue;
+            }
+            remaining.push(entry);
+        }
+
+        let mut usage: u64 = remaining.iter().map(|entry| entry.size).sum();
+        for entry in &remaining {
+            if usage < self.max_total_bytes {
+                break;
+            }
+            if age(entry) >= MIN_EVICTION_AGE && remove_entry(entry, &mut stats) {
+                usage -= entry.size;
+            }
+        }
+
+        stats.remaining_bytes = usage;
+        Ok(stats)
+    }
+
+    /// 启动后台清理任务
+    pub fn spawn_reaper(self) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.reap_interval);
+            loop {
+                interval.tick().await;
+                let config = self.clone();
+                match tokio::task::spawn_blocking(move || config.reap()).await {
+                    Ok(Ok(stats)) if stats.removed > 0 => println!(
+                        "清理了 {} 个工作区，释放 {} 字节，剩余 {} 字节",
+                        stats.removed, stats.freed_bytes, stats.remaining_bytes
+                    ),
+                    Ok(Ok(_)) => {}
+                    Ok(Err(e)) => eprintln!("清理工作区失败: {}", e),
+                    Err(e) => eprintln!("清理任务异常: {}", e),
+                }
+            }
+        })
+    }
+
+    // 列出根目录下的所有工作区
+    fn entries(&self) -> Result<Vec<WorkspaceEntry>> {
+        if !self.root.exists() {
+            return Ok(vec![]);
+        }
+
+        let mut entries = Vec::new();
+        for entry in std::fs::read_dir(&self.root)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            if !metadata.is_dir() {
+                continue;
+            }
+            // 不是按创建时间命名的目录（例如旧版本创建的工作区）使用修改时间
+            let created = match entry.file_name().to_str().and_then(created_at) {
+                Some(created) => created,
+                None => metadata.modified()?,
+            };
+            entries.push(WorkspaceEntry {
+                size: dir_size(&entry.path()),
+                created,
+                path: entry.path(),
+            });
+        }
+        Ok(entries)
+    }
+}
+
+// 配额检查和清理的锁，配置每次从环境变量读取，因此在进程内共用一把
+fn quota_lock() -> MutexGuard<'static, ()> {
+    static LOCK: Mutex<()> = Mutex::new(());
+    LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// 工作区目录名：创建时间（Unix毫秒）和随机数，例如`1760000000000-9f86d081884c7d65`
+fn workspace_name(created: SystemTime) -> String {
+    let millis = created.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis();
+    format!("{}-{}", millis, hex::encode(rand::random::<[u8; 16]>()))
+}
+
+// 从目录名读取创建时间
+fn created_at(name: &str) -> Option<SystemTime> {
+    let (millis, random) = name.split_once('-')?;
+    if random.len() != 32 || !random.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    Some(UNIX_EPOCH + Duration::from_millis(millis.parse().ok()?))
+}
+
+// 删除工作区并记录统计，删除失败时（例如已被其他请求删除）跳过
+fn remove_entry(entry: &WorkspaceEntry, stats: &mut ReapStats) -> bool {
+    match std::fs::remove_dir_all(&entry.path) {
+        Ok(()) => {
+            stats.removed += 1;
+            stats.freed_bytes += entry.size;
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+fn dir_size(path: &Path) -> u64 {
+    WalkDir::new(path)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+fn env_u64(name: &str) -> Option<u64> {
+    std::env::var(name).ok().and_then(|value| value.trim().parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    // 独立的根目录，离开作用域时删除
+    struct Root(WorkspaceConfig);
+
+    impl Root {
+        fn new(ttl: Duration, max_total_bytes: u64) -> Self {
+            Self(WorkspaceConfig {
+                root: std::env::temp_dir().join(format!("move-workspace-test-{:016x}", rand::random::<u64>())),
+                ttl,
+                max_total_bytes,
+                reap_interval: MINUTE,
+            })
+        }
+
+        // 创建一个`age`之前创建、占用`size`字节的工作区
+        fn workspace(&self, age: Duration, size: usize) -> PathBuf {
+            let path = self.0.root.join(workspace_name(SystemTime::now() - age));
+            std::fs::create_dir_all(path.join("build")).unwrap();
+            std::fs::write(path.join("build").join("module.mv"), vec![0u8; size]).unwrap();
+            path
+        }
+    }
+
+    impl Drop for Root {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0.root);
+        }
+    }
+
+    #[test]
+    fn names_record_the_creation_time() {
+        let root = Root::new(MINUTE, u64::MAX);
+        let before = SystemTime::now() - Duration::from_millis(1);
+        let path = root.0.create().unwrap();
+        let created = created_at(path.file_name().unwrap().to_str().unwrap()).unwrap();
+        assert!(created >= before && created <= SystemTime::now());
+
+        assert!(root.0.contains(&path));
+        assert!(!root.0.contains(&root.0.root));
+        assert!(root.0.remove(&root.0.root).is_err());
+        root.0.remove(&path).unwrap();
+        assert!(!path.exists());
+
+        assert_eq!(created_at("not-a-workspace"), None);
+        assert_eq!(created_at("9f86d081884c7d659a2feaa0c55ad015"), None);
+    }
+
+    #[test]
+    fn reaps_expired_workspaces() {
+        let root = Root::new(30 * MINUTE, u64::MAX);
+        let expired = root.workspace(60 * MINUTE, 10);
+        let fresh = root.workspace(10 * MINUTE, 20);
+        // 修改旧工作区中的文件不会延长保留时间
+        std::fs::write(expired.join("Move.toml"), "[package]").unwrap();
+
+        let stats = root.0.reap().unwrap();
+        assert_eq!(stats.removed, 1);
+        assert_eq!(stats.freed_bytes, 10 + "[package]".len() as u64);
+        assert_eq!(stats.remaining_bytes, 20);
+        assert!(!expired.exists());
+        assert!(fresh.exists());
+        assert_eq!(root.0.usage().unwrap(), 20);
+    }
+
+    #[test]
+    fn evicts_oldest_workspaces_over_quota() {
+        let root = Root::new(24 * 60 * MINUTE, 100);
+        let oldest = root.workspace(30 * MINUTE, 60);
+        let older = root.workspace(20 * MINUTE, 60);
+        let old = root.workspace(10 * MINUTE, 60);
+        let recent = root.workspace(MINUTE, 60);
+
+        // 从最早创建的开始删除，直到低于配额；最近创建的不会被删除
+        let stats = root.0.reap().unwrap();
+        assert_eq!(stats.removed, 3);
+        assert_eq!(stats.remaining_bytes, 60);
+        assert!(!oldest.exists() && !older.exists() && !old.exists());
+        assert!(recent.exists());
+    }
+
+    #[test]
+    fn create_reaps_before_checking_the_quota() {
+        let root = Root::new(30 * MINUTE, 100);
+        let expired = root.workspace(60 * MINUTE, 200);
+        let path = root.0.create().unwrap();
+        assert!(!expired.exists());
+        assert!(path.exists());
+
+        // 只剩最近创建的工作区时无法腾出空间，拒绝创建
+        root.workspace(MINUTE, 200);
+        assert!(root.0.create().is_err());
+    }
+}
@@ -0,0 +1,34 @@
+// 由 move-web-compiler 根据编译后的Move包生成，请勿手动修改
+import { bcs } from '@mysten/sui/bcs';
+import type { BcsType } from '@mysten/sui/bcs';
+import type { Transaction, TransactionArgument, TransactionObjectInput } from '@mysten/sui/transactions';
+
+/** 调用使用的包ID，发布或升级后可以通过`setPackageId`修改 */
+export let PACKAGE_ID = '0x0';
+
+export function setPackageId(packageId: string) {
+  PACKAGE_ID = packageId;
+}
+
+/** 是否为交易中已有的参数，例如之前调用的返回值 */
+export function isArgument(value: unknown): value is TransactionArgument {
+  return typeof value === 'object' && value !== null && '$kind' in value;
+}
+
+/** 按BCS类型序列化纯值参数，已有的交易参数原样传递 */
+export function pure<Input>(tx: Transaction, type: BcsType<any, Input>, value: Input | TransactionArgument): TransactionArgument {
+  return isArgument(value) ? value : tx.pure(type.serialize(value as Input));
+}
+
+/** 对象数组参数 */
+export function objectVector(tx: Transaction, value: TransactionObjectInput[] | TransactionArgument): TransactionArgument {
+  return isArgument(value) ? value : tx.makeMoveVec({ elements: value.map((object) => tx.object(object)) });
+}
+
+/** 无法生成定义的外部类型，使用时抛出异常 */
+export function unsupported(type: string): BcsType<any> {
+  const fail = (): never => {
+    throw new Error(`无法解析外部类型 ${type}，请手动提供BCS定义`);
+  };
+  return bcs.u8().transform({ input: fail, output: fail });
+}
@@ -0,0 +1,21 @@
+// 由 move-web-compiler 根据编译后的Move包生成，请勿手动修改
+import { bcs } from '@mysten/sui/bcs';
+import type { Transaction } from '@mysten/sui/transactions';
+import { PACKAGE_ID } from './_package';
+
+export const MODULE_NAME = 'hello';
+
+/** struct HelloWorldObject has store, key */
+export const HelloWorldObject = bcs.struct('HelloWorldObject', {
+  id: bcs.Address,
+  text: bcs.string(),
+});
+
+/** public fun mint(ctx: &mut 0x2::tx_context::TxContext) */
+export function mint(
+  tx: Transaction,
+) {
+  return tx.moveCall({
+    target: `${PACKAGE_ID}::hello::mint`,
+  });
+}
@@ -0,0 +1,30 @@
+// 测试用的框架模块，只保留生成绑定时用到的声明
+module 0x1::vector {
+    native public fun push_back<Element>(v: &mut vector<Element>, e: Element);
+    native public fun append<Element>(lhs: &mut vector<Element>, other: vector<Element>);
+}
+
+module 0x2::object {
+    struct UID has store {
+        id: address,
+    }
+}
+
+module 0x2::tx_context {
+    struct TxContext has drop {
+        sender: address,
+    }
+
+    native public fun sender(self: &TxContext): address;
+}
+
+module 0x2::coin {
+    use 0x2::object::UID;
+
+    struct Coin<phantom T> has key, store {
+        id: UID,
+        value: u64,
+    }
+
+    native public fun value<T>(self: &Coin<T>): u64;
+}
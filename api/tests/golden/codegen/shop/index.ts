@@ -0,0 +1,4 @@
+// 由 move-web-compiler 根据编译后的Move包生成，请勿手动修改
+export { PACKAGE_ID, setPackageId } from './_package';
+export * as item from './item';
+export * as shop from './shop';
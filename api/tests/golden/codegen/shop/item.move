@@ -0,0 +1,12 @@
+module 0x0::item {
+    use 0x2::object::UID;
+
+    struct Item has key, store {
+        id: UID,
+        power: u64,
+    }
+
+    public fun power(item: &Item): u64 {
+        item.power
+    }
+}
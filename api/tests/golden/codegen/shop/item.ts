@@ -0,0 +1,27 @@
+// 由 move-web-compiler 根据编译后的Move包生成，请勿手动修改
+import { bcs } from '@mysten/sui/bcs';
+import type { Transaction, TransactionObjectInput } from '@mysten/sui/transactions';
+import { PACKAGE_ID } from './_package';
+
+export const MODULE_NAME = 'item';
+
+/** struct Item has store, key */
+export const Item = bcs.struct('Item', {
+  id: bcs.Address,
+  power: bcs.u64(),
+});
+
+/** public fun power(item: &0x0::item::Item): u64 */
+export function power(
+  tx: Transaction,
+  args: {
+    item: TransactionObjectInput;
+  },
+) {
+  return tx.moveCall({
+    target: `${PACKAGE_ID}::item::power`,
+    arguments: [
+      tx.object(args.item),
+    ],
+  });
+}
@@ -0,0 +1,56 @@
+module 0x0::shop {
+    use 0x1::vector;
+    use 0x0::item::Item;
+    use 0x2::coin::{Self, Coin};
+    use 0x2::object::UID;
+    use 0x2::tx_context::{Self, TxContext};
+
+    struct Shop has key {
+        id: UID,
+        owner: address,
+        price: u64,
+        note: vector<u8>,
+        items: vector<Item>,
+        sold: u64,
+    }
+
+    struct Till<phantom T> has key {
+        id: UID,
+        coins: vector<Coin<T>>,
+    }
+
+    struct Tag<T> has drop, store {
+        value: T,
+    }
+
+    public fun sold(shop: &Shop): u64 {
+        shop.sold
+    }
+
+    public fun stock(shop: &mut Shop, item: Item) {
+        vector::push_back(&mut shop.items, item)
+    }
+
+    public entry fun restock(shop: &mut Shop, items: vector<Item>) {
+        vector::append(&mut shop.items, items)
+    }
+
+    public entry fun set_price(shop: &mut Shop, price: u64, note: vector<u8>, ctx: &mut TxContext) {
+        assert_owner(shop, ctx);
+        shop.price = price;
+        shop.note = note;
+    }
+
+    public fun pay<T>(shop: &mut Shop, payment: Coin<T>): Coin<T> {
+        shop.sold = shop.sold + coin::value(&payment);
+        payment
+    }
+
+    public fun tag<T: drop + store>(value: T): Tag<T> {
+        Tag { value }
+    }
+
+    fun assert_owner(shop: &Shop, ctx: &TxContext) {
+        assert!(tx_context::sender(ctx) == shop.owner, 0);
+    }
+}
@@ -0,0 +1,135 @@
+// 由 move-web-compiler 根据编译后的Move包生成，请勿手动修改
+import { bcs } from '@mysten/sui/bcs';
+import type { BcsType } from '@mysten/sui/bcs';
+import type { Transaction, TransactionArgument, TransactionObjectInput } from '@mysten/sui/transactions';
+import { PACKAGE_ID, objectVector, pure } from './_package';
+import * as item_module from './item';
+
+export const MODULE_NAME = 'shop';
+
+/** struct Shop has key */
+export const Shop = bcs.struct('Shop', {
+  id: bcs.Address,
+  owner: bcs.Address,
+  price: bcs.u64(),
+  note: bcs.vector(bcs.u8()),
+  items: bcs.vector(item_module.Item),
+  sold: bcs.u64(),
+});
+
+/** struct Tag<T0> has drop, store */
+export function Tag<T0 extends BcsType<any>>(T0: T0) {
+  return bcs.struct('Tag', {
+    value: T0,
+  });
+}
+
+/** struct Till<phantom T0> has key */
+export const Till = bcs.struct('Till', {
+  id: bcs.Address,
+  coins: bcs.vector(bcs.struct('Coin', { id: bcs.Address, balance: bcs.struct('Balance', { value: bcs.u64() }) })),
+});
+
+/** public fun pay<T0>(shop: &mut 0x0::shop::Shop, payment: 0x2::coin::Coin<T0>): 0x2::coin::Coin<T0> */
+export function pay(
+  tx: Transaction,
+  args: {
+    shop: TransactionObjectInput;
+    payment: TransactionObjectInput;
+  },
+  typeArguments: [string],
+) {
+  return tx.moveCall({
+    target: `${PACKAGE_ID}::shop::pay`,
+    typeArguments,
+    arguments: [
+      tx.object(args.shop),
+      tx.object(args.payment),
+    ],
+  });
+}
+
+/** public entry fun restock(shop: &mut 0x0::shop::Shop, items: vector<0x0::item::Item>) */
+export function restock(
+  tx: Transaction,
+  args: {
+    shop: TransactionObjectInput;
+    items: TransactionObjectInput[] | TransactionArgument;
+  },
+) {
+  return tx.moveCall({
+    target: `${PACKAGE_ID}::shop::restock`,
+    arguments: [
+      tx.object(args.shop),
+      objectVector(tx, args.items),
+    ],
+  });
+}
+
+/** public entry fun set_price(shop: &mut 0x0::shop::Shop, price: u64, note: vector<u8>, ctx: &mut 0x2::tx_context::TxContext) */
+export function set_price(
+  tx: Transaction,
+  args: {
+    shop: TransactionObjectInput;
+    price: bigint | number | string | TransactionArgument;
+    note: number[] | Uint8Array | TransactionArgument;
+  },
+) {
+  return tx.moveCall({
+    target: `${PACKAGE_ID}::shop::set_price`,
+    arguments: [
+      tx.object(args.shop),
+      pure(tx, bcs.u64(), args.price),
+      pure(tx, bcs.vector(bcs.u8()), args.note),
+    ],
+  });
+}
+
+/** public fun sold(shop: &0x0::shop::Shop): u64 */
+export function sold(
+  tx: Transaction,
+  args: {
+    shop: TransactionObjectInput;
+  },
+) {
+  return tx.moveCall({
+    target: `${PACKAGE_ID}::shop::sold`,
+    arguments: [
+      tx.object(args.shop),
+    ],
+  });
+}
+
+/** public fun stock(shop: &mut 0x0::shop::Shop, item: 0x0::item::Item) */
+export function stock(
+  tx: Transaction,
+  args: {
+    shop: TransactionObjectInput;
+    item: TransactionObjectInput;
+  },
+) {
+  return tx.moveCall({
+    target: `${PACKAGE_ID}::shop::stock`,
+    arguments: [
+      tx.object(args.shop),
+      tx.object(args.item),
+    ],
+  });
+}
+
+/** public fun tag<T0: drop + store>(value: T0): 0x0::shop::Tag<T0> */
+export function tag(
+  tx: Transaction,
+  args: {
+    value: TransactionArgument;
+  },
+  typeArguments: [string],
+) {
+  return tx.moveCall({
+    target: `${PACKAGE_ID}::shop::tag`,
+    typeArguments,
+    arguments: [
+      args.value,
+    ],
+  });
+}
@@ -0,0 +1,83 @@
+#!/bin/bash
+
+# Sui框架缓存预置脚本
+# 将指定版本的 move-stdlib / sui-framework / deepbook 包下载到本地缓存，
+# 后端编译时直接使用本地副本，不再每次从GitHub拉取（适用于离线构建机）。
+#
+# 用法: ./seed-framework.sh [版本...]
+#   版本可以是分支、标签或提交哈希，默认预置 framework/testnet framework/mainnet framework/devnet
+#
+# 环境变量:
+#   MOVE_FRAMEWORK_CACHE  缓存根目录（默认 ~/.move-web-compiler/framework，需与后端一致）
+#   SUI_REPOSITORY        Sui仓库地址（默认 https://github.com/MystenLabs/sui.git，可指向内部镜像）
+
+set -e
+
+CACHE_DIR="${MOVE_FRAMEWORK_CACHE:-$HOME/.move-web-compiler/framework}"
+REPOSITORY="${SUI_REPOSITORY:-https://github.com/MystenLabs/sui.git}"
+PACKAGES_DIR="crates/sui-framework/packages"
+PACKAGES="move-stdlib sui-framework deepbook"
+
+if [ $# -gt 0 ]; then
+    REVISIONS=("$@")
+else
+    REVISIONS=("framework/testnet" "framework/mainnet" "framework/devnet")
+fi
+
+if ! command -v git &> /dev/null; then
+    echo "错误: 未找到 git"
+    exit 1
+fi
+
+mkdir -p "$CACHE_DIR"
+echo "框架缓存目录: $CACHE_DIR"
+
+# 目录名规则与后端一致：[A-Za-z0-9._-] 以外的字节按 %XX 编码，开头的 . 也编码
+revision_dir_name() {
+    local LC_ALL=C revision="$1" name="" c i
+    for ((i = 0; i < ${#revision}; i++)); do
+        c="${revision:i:1}"
+        case "$c" in
+            [A-Za-z0-9_-]) name+="$c" ;;
+            .) if [ "$i" -eq 0 ]; then name+="%2E"; else name+="."; fi ;;
+            *) name+=$(printf '%%%02X' "'$c") ;;
+        esac
+    done
+    echo "$name"
+}
+
+for revision in "${REVISIONS[@]}"; do
+    dir_name=$(revision_dir_name "$revision")
+    target="$CACHE_DIR/$dir_name"
+    work_dir=$(mktemp -d)
+
+    echo "正在预置框架版本 $revision -> $target"
+
+    git init --quiet "$work_dir/sui"
+    git -C "$work_dir/sui" remote add origin "$REPOSITORY"
+    git -C "$work_dir/sui" sparse-checkout set $(for p in $PACKAGES; do echo "$PACKAGES_DIR/$p"; done)
+    git -C "$work_dir/sui" fetch --quiet --depth 1 --filter=blob:none origin "$revision"
+    git -C "$work_dir/sui" checkout --quiet FETCH_HEAD
+    commit=$(git -C "$work_dir/sui" rev-parse HEAD)
+
+    # 先写入临时目录再整体替换，避免编译时读到一半的缓存
+    staging="$target.tmp.$$"
+    rm -rf "$staging"
+    mkdir -p "$staging"
+    for package in $PACKAGES; do
+        if [ -d "$work_dir/sui/$PACKAGES_DIR/$package" ]; then
+            cp -r "$work_dir/sui/$PACKAGES_DIR/$package" "$staging/$package"
+        else
+            echo "警告: 版本 $revision 中没有 $package 包，已跳过"
+        fi
+    done
+    echo "$commit" > "$staging/COMMIT"
+
+    rm -rf "$target"
+    mv "$staging" "$target"
+    rm -rf "$work_dir"
+
+    echo "已预置 $revision (提交 $commit)"
+done
+
+echo "框架缓存预置完成"
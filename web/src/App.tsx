@@ -163,14 +163,14 @@ function App() {
     setEditorKey(prev => prev + 1); // Update key to re-initialize editor
   };
 
-  const handleTest = async (command: string) => {
-    await tester.test(command);
+  const handleTest = async (packagePath: string) => {
+    await tester.test(packagePath);
   };
   
-  const handleDeploy = async (command: string) => {
+  const handleDeploy = async (packagePath: string) => {
     // TODO: Differentiate mainnet/testnet if DeployOptions passes this distinction
     // const isMainnet = _isMainnet; // Re-add if needed
-    await deployer.deploy(command);
+    await deployer.deploy(packagePath);
   };
   
   const handleExampleSelect = (code: string, exampleFileName: string) => {
@@ -179,6 +179,11 @@ function App() {
     setEditorKey(prev => prev + 1); // Force re-initialization of editor with new content
   };
 
+  // 当前文件在最近一次测试中的覆盖率，编辑后行号可能错位，直到重新测试
+  const coverageLines = tester.result?.coverage?.modules
+    .filter(module => module.source_file.split('/').pop() === fileName)
+    .flatMap(module => module.line_hits);
+
   // DEBUG: Log props for child components
   console.log('[App.tsx] FileSystemPanel props:', { currentFileName: fileName });
   console.log('[App.tsx] MainEditorPanel props:', { sourceCode, fileName, isCompiling: compiler.isCompiling, editorKey });
@@ -214,6 +219,7 @@ function App() {
             onCompile={handleCompile}
             isCompiling={compiler.isCompiling}
             initialEditorContent={sourceCode} // Pass initial content for editor
+            coverageLines={coverageLines}
             className="bg-panel border border-border rounded-md shadow-sm flex flex-col h-full" // Added flex flex-col for internal layout
           />
         </div>
@@ -1,17 +1,39 @@
-import { useState, useRef } from 'react';
+import { useState, useRef, useEffect } from 'react';
 import Editor from '@monaco-editor/react';
+import type { LineHits } from '../types/api';
 
 interface CodeEditorProps {
   initialValue?: string;
   onChange?: (value: string) => void;
+  coverageLines?: LineHits[];
 }
 
 
-const CodeEditor = ({ initialValue = '', onChange }: CodeEditorProps) => {
+const CodeEditor = ({ initialValue = '', onChange, coverageLines }: CodeEditorProps) => {
   const [value, setValue] = useState(initialValue);
+  const [isMounted, setIsMounted] = useState(false);
   // 添加ref来存储monaco和editor实例
   const monacoRef = useRef<any>(null);
   const editorRef = useRef<any>(null);
+  // 当前的覆盖率标记
+  const coverageDecorationsRef = useRef<string[]>([]);
+
+  // 在行号旁标记测试覆盖和未覆盖的行
+  useEffect(() => {
+    const editor = editorRef.current;
+    const monaco = monacoRef.current;
+    if (!isMounted || !editor || !monaco) return;
+
+    const decorations = (coverageLines ?? []).map(({ line, hits }) => ({
+      range: new monaco.Range(line, 1, line, 1),
+      options: {
+        isWholeLine: true,
+        linesDecorationsClassName: hits > 0 ? 'coverage-line-covered' : 'coverage-line-uncovered',
+        hoverMessage: { value: hits > 0 ? `已覆盖：执行 ${hits} 次` : '未覆盖' },
+      },
+    }));
+    coverageDecorationsRef.current = editor.deltaDecorations(coverageDecorationsRef.current, decorations);
+  }, [coverageLines, isMounted]);
 
   const handleEditorChange = (value: string | undefined) => {
     const newValue = value ?? '';
@@ -27,6 +49,7 @@ const CodeEditor = ({ initialValue = '', onChange }: CodeEditorProps) => {
     // 存储monaco和editor实例供后续使用
     monacoRef.current = monaco;
     editorRef.current = editor;
+    setIsMounted(true);
     
     // 关键：在这里注册Move语言和主题，确保使用正确的Monaco实例
     try {
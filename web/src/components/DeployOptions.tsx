@@ -3,8 +3,8 @@ import { useState } from 'react';
 interface DeployOptionsProps {
   bytecodePathOrCommand?: string;
   // onTest: (command: string) => void; // Removed as test button is in RightPanel's Test tab
-  onDeployTestnet: (command: string) => void;
-  onDeployMainnet: (command: string) => void;
+  onDeployTestnet: (packagePath: string) => void;
+  onDeployMainnet: (packagePath: string) => void;
   isCompileSuccess: boolean;
   // isTesting: boolean; // Removed as test button is in RightPanel's Test tab
   isDeploying: boolean;
@@ -56,19 +56,7 @@ const DeployOptions = ({
 }: DeployOptionsProps) => {
   const [isMainnetModalOpen, setIsMainnetModalOpen] = useState(false);
   
-  // Example commands, adjust as needed for actual CLI usage
-  const getDeployTestnetCommand = () => {
-    if (!bytecodePathOrCommand) return '';
-    // Example: `sui client publish --gas-budget 10000000 --json <path-to-bytecode>`
-    // If bytecodePathOrCommand is the full command, just return it.
-    // For now, assuming it's a path that needs to be part of a command:
-    return `sui client publish --gas-budget 200000000 ${bytecodePathOrCommand}`; // Increased gas budget
-  };
-  
-  const getDeployMainnetCommand = () => {
-    if (!bytecodePathOrCommand) return '';
-    return `sui client publish --gas-budget 200000000 ${bytecodePathOrCommand}`; // Increased gas budget
-  };
+  // The backend publishes the package directory returned by /api/compile as a typed `publish` operation
 
   // Test button logic is now in RightPanel.tsx, so handleTest and getTestCommand might be redundant here
   // unless DeployOptions is meant to have its own test button.
@@ -78,8 +66,7 @@ const DeployOptions = ({
   // };
 
   const handleDeployTestnet = () => {
-    const command = getDeployTestnetCommand();
-    if (command) onDeployTestnet(command);
+    if (bytecodePathOrCommand) onDeployTestnet(bytecodePathOrCommand);
   };
 
   const handleDeployMainnet = () => {
@@ -87,8 +74,7 @@ const DeployOptions = ({
   };
 
   const confirmMainnetDeploy = () => {
-    const command = getDeployMainnetCommand();
-    if (command) onDeployMainnet(command);
+    if (bytecodePathOrCommand) onDeployMainnet(bytecodePathOrCommand);
     setIsMainnetModalOpen(false);
   };
 
@@ -1,5 +1,6 @@
 import CodeEditor from './CodeEditor';
 import { PlayIcon, FileTextIcon, ClockIcon } from '@radix-ui/react-icons';
+import type { LineHits } from '../types/api';
 
 interface MainEditorPanelProps {
   sourceCode: string;
@@ -8,6 +9,7 @@ interface MainEditorPanelProps {
   onCompile: () => void;
   isCompiling: boolean;
   initialEditorContent: string;
+  coverageLines?: LineHits[];
   className?: string;
 }
 
@@ -18,6 +20,7 @@ const MainEditorPanel = ({
   onCompile,
   isCompiling,
   initialEditorContent,
+  coverageLines,
   className,
 }: MainEditorPanelProps) => {
   // 计算代码统计信息
@@ -72,6 +75,7 @@ const MainEditorPanel = ({
         <CodeEditor
           initialValue={initialEditorContent}
           onChange={onSourceCodeChange}
+          coverageLines={coverageLines}
         />
       </div>
     </div>
@@ -9,8 +9,8 @@ interface RightPanelProps {
   compileResultData: { result: CompileResponse | null; error: string | null; isCompiling: boolean; };
   deployResultData: { result: DeployResponse | null; error: string | null; isDeploying: boolean; };
   testResultData: { result: TestResponse | null; error: string | null; isTesting: boolean; };
-  onTest: (command: string) => void;
-  onDeploy: (command: string, isMainnet: boolean) => void;
+  onTest: (packagePath: string) => void;
+  onDeploy: (packagePath: string, isMainnet: boolean) => void;
   isCompileSuccess: boolean;
   className?: string;
 }
@@ -84,7 +84,7 @@ const RightPanel = ({
               <LightningBoltIcon className="w-5 h-5 mr-2 text-warning" /> Test Execution
             </h3>
             <button
-              onClick={() => bytecodePath && onTest(`sui move test --path ${bytecodePath}`)}
+              onClick={() => bytecodePath && onTest(bytecodePath)}
               disabled={!isCompileSuccess || testResultData.isTesting || !bytecodePath}
               className="w-full flex items-center justify-center bg-warning text-background py-2.5 px-4 rounded-md hover:bg-warning/90 disabled:bg-panel disabled:text-foreground/50 disabled:cursor-not-allowed text-sm font-medium transition-colors duration-150 shadow hover:shadow-md focus:outline-none focus:ring-2 focus:ring-warning/70 focus:ring-opacity-75"
             >
@@ -117,8 +117,8 @@ const RightPanel = ({
             </h3>
             <DeployOptions
               bytecodePathOrCommand={bytecodePath ?? undefined}
-              onDeployTestnet={(command: string) => onDeploy(command, false)}
-              onDeployMainnet={(command: string) => onDeploy(command, true)}
+              onDeployTestnet={(packagePath: string) => onDeploy(packagePath, false)}
+              onDeployMainnet={(packagePath: string) => onDeploy(packagePath, true)}
               isCompileSuccess={isCompileSuccess}
               isDeploying={deployResultData.isDeploying}
             />
@@ -7,12 +7,13 @@ export function useDeployer() {
   const [result, setResult] = useState<DeployResponse | null>(null);
   const [error, setError] = useState<string | null>(null);
 
-  const deploy = async (command: string) => {
+  // 发布编译接口返回的包目录
+  const deploy = async (packagePath: string, gasBudget = 200000000) => {
     setIsDeploying(true);
     setError(null);
     
     try {
-      const response = await deployCode({ command });
+      const response = await deployCode({ operation: 'publish', package_path: packagePath, gas_budget: gasBudget });
       setResult(response);
       return response;
     } catch (err) {
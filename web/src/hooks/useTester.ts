@@ -7,12 +7,13 @@ export function useTester() {
   const [result, setResult] = useState<TestResponse | null>(null);
   const [error, setError] = useState<string | null>(null);
 
-  const test = async (command: string) => {
+  // 运行编译接口返回的包目录中的单元测试，同时统计覆盖率用于在编辑器中标记
+  const test = async (packagePath: string) => {
     setIsTesting(true);
     setError(null);
     
     try {
-      const response = await testCode({ command });
+      const response = await testCode({ package_path: packagePath, coverage: true });
       setResult(response);
       return response;
     } catch (err) {
@@ -4,22 +4,42 @@ import type {
   DeployRequest, 
   DeployResponse, 
   TestRequest, 
-  TestResponse 
+  TestResponse,
+  DisassembleRequest,
+  DisassembleResponse,
+  FormatRequest,
+  FormatResponse,
+  AbiRequest,
+  AbiResponse,
+  CodegenRequest,
+  CodegenResponse,
+  PublishBuildRequest,
+  PublishBuildResponse,
+  ExecuteRequest,
+  ExecuteResponse,
+  UpgradeBuildRequest,
+  UpgradeBuildResponse,
+  CallBuildRequest,
+  SimulateRequest,
+  SimulateResponse,
+  SessionCreateRequest,
+  SessionCallRequest,
+  SessionResponse
 } from '../types/api';
 import { API_BASE_URL, API_PATHS, API_TIMEOUT } from './config';
 
-// 通用的API请求处理函数
-async function fetchAPI<T, R>(endpoint: string, data: T): Promise<R> {
+// 通用的API请求处理函数，GET和DELETE请求不带请求体
+async function fetchAPI<T, R>(endpoint: string, data: T, method: 'GET' | 'POST' | 'DELETE' = 'POST'): Promise<R> {
   const controller = new AbortController();
   const timeoutId = setTimeout(() => controller.abort(), API_TIMEOUT);
   
   try {
     const response = await fetch(`${API_BASE_URL}${endpoint}`, {
-      method: 'POST',
+      method,
       headers: {
         'Content-Type': 'application/json',
       },
-      body: JSON.stringify(data),
+      body: method === 'POST' ? JSON.stringify(data) : undefined,
       signal: controller.signal,
     });
 
@@ -57,6 +77,102 @@ export async function testCode(request: TestRequest): Promise<TestResponse> {
   return fetchAPI<TestRequest, TestResponse>(API_PATHS.TEST, request);
 }
 
+// 反汇编编译后的字节码
+export async function disassembleCode(request: DisassembleRequest): Promise<DisassembleResponse> {
+  return fetchAPI<DisassembleRequest, DisassembleResponse>(API_PATHS.DISASSEMBLE, request);
+}
+
+// 格式化Move源码
+export async function formatCode(request: FormatRequest): Promise<FormatResponse> {
+  return fetchAPI<FormatRequest, FormatResponse>(API_PATHS.FORMAT, request);
+}
+
+// 提取编译后模块的接口描述
+export async function getAbi(request: AbiRequest): Promise<AbiResponse> {
+  return fetchAPI<AbiRequest, AbiResponse>(API_PATHS.ABI, request);
+}
+
+// 生成TypeScript客户端绑定
+export async function generateBindings(request: CodegenRequest): Promise<CodegenResponse> {
+  return fetchAPI<CodegenRequest, CodegenResponse>(API_PATHS.CODEGEN, request);
+}
+
+// 构建未签名的发布交易，由钱包签名
+export async function buildPublishTransaction(request: PublishBuildRequest): Promise<PublishBuildResponse> {
+  return fetchAPI<PublishBuildRequest, PublishBuildResponse>(API_PATHS.PUBLISH_BUILD, request);
+}
+
+// 提交钱包签名后的交易
+export async function executeTransaction(request: ExecuteRequest): Promise<ExecuteResponse> {
+  return fetchAPI<ExecuteRequest, ExecuteResponse>(API_PATHS.EXECUTE, request);
+}
+
+// 检查兼容性并构建未签名的升级交易
+export async function buildUpgradeTransaction(request: UpgradeBuildRequest): Promise<UpgradeBuildResponse> {
+  return fetchAPI<UpgradeBuildRequest, UpgradeBuildResponse>(API_PATHS.UPGRADE_BUILD, request);
+}
+
+// 按函数签名校验参数并构建未签名的调用交易
+export async function buildCallTransaction(request: CallBuildRequest): Promise<PublishBuildResponse> {
+  return fetchAPI<CallBuildRequest, PublishBuildResponse>(API_PATHS.CALL_BUILD, request);
+}
+
+// 在本地模拟链上发布包并执行函数，不访问网络
+export async function simulateCall(request: SimulateRequest): Promise<SimulateResponse> {
+  return fetchAPI<SimulateRequest, SimulateResponse>(API_PATHS.SIMULATE, request);
+}
+
+// 创建本地模拟会话并发布包，之后的调用共用会话中的对象
+export async function createSimulationSession(request: SessionCreateRequest): Promise<SessionResponse> {
+  return fetchAPI<SessionCreateRequest, SessionResponse>(API_PATHS.SIMULATE_SESSIONS, request);
+}
+
+// 在模拟会话中执行函数
+export async function callInSession(sessionId: string, request: SessionCallRequest): Promise<SessionResponse> {
+  return fetchAPI<SessionCallRequest, SessionResponse>(`${sessionPath(sessionId)}/call`, request);
+}
+
+// 列出模拟会话中的对象
+export async function listSessionObjects(sessionId: string): Promise<SessionResponse> {
+  return fetchAPI<undefined, SessionResponse>(`${sessionPath(sessionId)}/objects`, undefined, 'GET');
+}
+
+// 读取模拟会话中的对象及解码后的字段
+export async function getSessionObject(sessionId: string, objectId: string): Promise<SessionResponse> {
+  return fetchAPI<undefined, SessionResponse>(
+    `${sessionPath(sessionId)}/objects/${encodeURIComponent(objectId)}`,
+    undefined,
+    'GET'
+  );
+}
+
+// 将模拟会话恢复到刚发布包后的状态
+export async function resetSession(sessionId: string): Promise<SessionResponse> {
+  return fetchAPI<Record<string, never>, SessionResponse>(`${sessionPath(sessionId)}/reset`, {});
+}
+
+// 保存模拟会话的快照，未指定名称时自动命名
+export async function snapshotSession(sessionId: string, name?: string): Promise<SessionResponse> {
+  return fetchAPI<{ name?: string }, SessionResponse>(`${sessionPath(sessionId)}/snapshots`, { name });
+}
+
+// 将模拟会话恢复到快照
+export async function restoreSessionSnapshot(sessionId: string, name: string): Promise<SessionResponse> {
+  return fetchAPI<Record<string, never>, SessionResponse>(
+    `${sessionPath(sessionId)}/snapshots/${encodeURIComponent(name)}/restore`,
+    {}
+  );
+}
+
+// 删除模拟会话
+export async function deleteSession(sessionId: string): Promise<SessionResponse> {
+  return fetchAPI<undefined, SessionResponse>(sessionPath(sessionId), undefined, 'DELETE');
+}
+
+function sessionPath(sessionId: string): string {
+  return `${API_PATHS.SIMULATE_SESSIONS}/${encodeURIComponent(sessionId)}`;
+}
+
 // 示例代码列表
 export const codeExamples = [
   {
@@ -8,6 +8,16 @@ export const API_PATHS = {
   COMPILE: '/api/compile',
   DEPLOY: '/api/deploy',
   TEST: '/api/test',
+  DISASSEMBLE: '/api/disassemble',
+  FORMAT: '/api/format',
+  ABI: '/api/abi',
+  CODEGEN: '/api/codegen',
+  PUBLISH_BUILD: '/api/publish/build',
+  EXECUTE: '/api/execute',
+  UPGRADE_BUILD: '/api/upgrade/build',
+  CALL_BUILD: '/api/call/build',
+  SIMULATE: '/api/simulate',
+  SIMULATE_SESSIONS: '/api/simulate/sessions',
 };
 
 // 超时设置（毫秒）
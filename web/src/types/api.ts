@@ -1,8 +1,10 @@
 // 编译请求的数据结构
 export interface CompileRequest {
-  source_code: string;
+  source_code?: string;
   file_name?: string;
+  files?: Record<string, string>;
   addresses_toml_content?: string;
+  framework?: string;
 }
 
 // 编译结果的数据结构
@@ -15,24 +17,144 @@ export interface CompileResponse {
   error_message?: string;
   warnings: string[];
   bytecode_path?: string;
+  modules: CompiledModuleInfo[];
+  dependencies: string[];
+  abi: ModuleAbi[];
+  diagnostics: Diagnostic[];
+  framework?: FrameworkInfo;
+  cache_hit: boolean;
+}
+
+// 编译使用的Sui框架版本
+export interface FrameworkInfo {
+  revision: string;
+  network?: 'testnet' | 'mainnet' | 'devnet';
+  commit?: string;
+  source: 'cache' | 'git';
+}
+
+// 编译器诊断的标注区间
+export interface DiagnosticLabel {
+  file: string;
+  line: number;
+  column: number;
+  end_line: number;
+  end_column: number;
+  start: number;
+  end: number;
+  message: string;
+}
+
+// 结构化的编译器诊断
+export interface Diagnostic {
+  severity: 'bug' | 'error' | 'warning' | 'note' | 'help';
+  code: string;
+  message: string;
+  primary: DiagnosticLabel;
+  secondary: DiagnosticLabel[];
+  notes: string[];
 }
 
-// 部署请求的数据结构
-export interface DeployRequest {
-  command: string;
+// 根包中单个模块的字节码信息
+export interface CompiledModuleInfo {
+  address: string;
+  name: string;
+  size: number;
+  base64: string;
+  digest: string;
+  source_file: string;
+}
+
+// 操作被拒绝时的错误码
+export type OperationErrorCode =
+  | 'INVALID_REQUEST'
+  | 'INVALID_COMMAND'
+  | 'OPERATION_NOT_ALLOWED'
+  | 'INVALID_ARGUMENT'
+  | 'INVALID_PACKAGE_PATH'
+  | 'RPC_ERROR'
+  | 'INCOMPATIBLE_UPGRADE'
+  | 'SIMULATION_ERROR'
+  | 'SESSION_NOT_FOUND';
+
+// 发布编译后的包
+export interface PublishOperation {
+  operation: 'publish';
+  package_path: string;
+  gas_budget?: number;
 }
 
+// 调用已发布包中的函数
+export interface CallOperation {
+  operation: 'call';
+  package: string;
+  module: string;
+  function: string;
+  type_args?: string[];
+  args?: string[];
+  gas_budget?: number;
+}
+
+// 部署请求的数据结构，command为旧接口
+export type DeployRequest = PublishOperation | CallOperation | { command: string };
+
 // 部署响应的数据结构
-export interface DeployResponse {
+export interface DeployResponse extends Partial<TransactionSummary> {
   success: boolean;
-  package_id?: string;
   output?: string;
   error?: string;
+  error_code?: OperationErrorCode;
 }
 
 // 测试请求的数据结构
 export interface TestRequest {
-  command: string;
+  command?: string;
+  package_path?: string;
+  source_code?: string;
+  file_name?: string;
+  files?: Record<string, string>;
+  addresses_toml_content?: string;
+  framework?: string;
+  filter?: string;
+  gas_limit?: number;
+  gas_profile?: boolean;
+  folded_stacks?: boolean;
+  coverage?: boolean;
+}
+
+// 单个测试的结果
+export interface TestCaseResult {
+  name: string;
+  module: string;
+  function: string;
+  status: 'pass' | 'fail' | 'abort' | 'timeout';
+  abort_code?: number;
+  location?: { file: string; line: number; column: number };
+  message?: string;
+  gas_used?: number;
+  gas_profile?: GasProfile;
+}
+
+// 单个函数的gas统计
+export interface FunctionGasProfile {
+  function: string;
+  calls: number;
+  gas: number;
+  self_gas: number;
+  instructions: number;
+  native_calls: number;
+  native: boolean;
+}
+
+// 一个测试或一笔本地交易的gas分析结果
+export interface GasProfile {
+  name: string;
+  total_gas: number;
+  instructions: number;
+  native_calls: number;
+  storage_bytes?: number;
+  functions: FunctionGasProfile[];
+  folded_stacks?: string;
 }
 
 // 测试响应的数据结构
@@ -40,6 +162,399 @@ export interface TestResponse {
   success: boolean;
   output?: string;
   error?: string;
+  tests: TestCaseResult[];
+  passed: number;
+  failed: number;
+  debug_output: string[];
+  diagnostics: Diagnostic[];
+  framework?: FrameworkInfo;
+  coverage?: CoverageReport;
+  error_code?: OperationErrorCode;
+}
+
+// 一行源码的执行次数，hits为0表示未覆盖
+export interface LineHits {
+  line: number;
+  hits: number;
+}
+
+// 单个函数的行覆盖率
+export interface FunctionCoverage {
+  name: string;
+  line: number;
+  calls: number;
+  lines: number;
+  covered_lines: number;
+  percent: number;
+}
+
+// 单个模块的行覆盖率
+export interface ModuleCoverage {
+  address: string;
+  name: string;
+  source_file: string;
+  lines: number;
+  covered_lines: number;
+  percent: number;
+  functions: FunctionCoverage[];
+  line_hits: LineHits[];
+}
+
+// 一次测试运行的覆盖率报告
+export interface CoverageReport {
+  lines: number;
+  covered_lines: number;
+  percent: number;
+  modules: ModuleCoverage[];
+  lcov: string;
+}
+
+// 反汇编请求的数据结构，modules和package_path二选一
+export interface DisassembleRequest {
+  modules?: string[];
+  package_path?: string;
+  interleave_source?: boolean;
+  skip_code?: boolean;
+  skip_locals?: boolean;
+  skip_basic_blocks?: boolean;
+}
+
+// 单个模块的反汇编结果
+export interface DisassembledModule {
+  address: string;
+  name: string;
+  disassembly: string;
+}
+
+// 反汇编响应的数据结构
+export interface DisassembleResponse {
+  success: boolean;
+  modules: DisassembledModule[];
+  error?: string;
+  error_code?: OperationErrorCode;
+}
+
+// 格式化请求的数据结构
+export interface FormatRequest {
+  source_code: string;
+  file_name?: string;
+  indent_width?: number;
+  line_length?: number;
+}
+
+// 格式化响应的数据结构，语法错误时只有diagnostics和error
+export interface FormatResponse {
+  success: boolean;
+  formatted?: string;
+  changed: boolean;
+  diagnostics: Diagnostic[];
+  error?: string;
+}
+
+// Move类型
+export type MoveType =
+  | 'Bool'
+  | 'U8'
+  | 'U16'
+  | 'U32'
+  | 'U64'
+  | 'U128'
+  | 'U256'
+  | 'Address'
+  | 'Signer'
+  | { Vector: MoveType }
+  | { Reference: MoveType }
+  | { MutableReference: MoveType }
+  | { TypeParameter: number }
+  | { Struct: { address: string; module: string; name: string; type_arguments: MoveType[] } };
+
+// 模块的接口描述
+export interface ModuleAbi {
+  address: string;
+  name: string;
+  friends: string[];
+  structs: {
+    name: string;
+    abilities: string[];
+    type_parameters: { constraints: string[]; is_phantom: boolean }[];
+    fields: { name: string; type: MoveType }[];
+  }[];
+  functions: {
+    name: string;
+    visibility: 'public' | 'friend' | 'private';
+    is_entry: boolean;
+    type_parameters: string[][];
+    parameters: MoveType[];
+    parameter_names: string[];
+    returns: MoveType[];
+  }[];
+  constants: { name?: string; type: MoveType; value?: string }[];
+}
+
+// 接口描述请求，modules和package_path二选一
+export interface AbiRequest {
+  modules?: string[];
+  package_path?: string;
+}
+
+// 接口描述响应
+export interface AbiResponse {
+  success: boolean;
+  modules: ModuleAbi[];
+  error?: string;
+  error_code?: OperationErrorCode;
+}
+
+// TypeScript绑定生成请求，modules和package_path二选一
+export interface CodegenRequest {
+  modules?: string[];
+  package_path?: string;
+  package_id?: string;
+}
+
+// 生成的文件
+export interface GeneratedFile {
+  path: string;
+  content: string;
+}
+
+// TypeScript绑定生成响应
+export interface CodegenResponse {
+  success: boolean;
+  files: GeneratedFile[];
+  warnings: string[];
+  error?: string;
+  error_code?: OperationErrorCode;
+}
+
+// 对象引用，例如用于支付gas的币
+export interface ObjectRef {
+  object_id: string;
+  version: number;
+  digest: string;
+}
+
+// 构建发布交易的请求
+export interface PublishBuildRequest {
+  modules: string[];
+  dependencies: string[];
+  sender: string;
+  gas_budget?: number;
+  gas_price: number;
+  gas_payment: ObjectRef[];
+}
+
+// 构建发布交易的响应，tx_bytes交给钱包签名
+export interface PublishBuildResponse {
+  success: boolean;
+  tx_bytes?: string;
+  digest?: string;
+  sender?: string;
+  gas_budget?: number;
+  gas_price?: number;
+  dependencies?: string[];
+  error?: string;
+  error_code?: OperationErrorCode;
+}
+
+// 提交已签名交易的请求
+export interface ExecuteRequest {
+  tx_bytes: string;
+  signatures: string[];
+}
+
+// 交易中创建或修改的对象
+export interface ObjectSummary {
+  object_id: string;
+  object_type?: string;
+  owner?: unknown;
+  version: number;
+}
+
+// 交易产生的事件
+export interface EventSummary {
+  event_type: string;
+  package_id: string;
+  module: string;
+  sender: string;
+  parsed_json: unknown;
+}
+
+// gas费用明细（MIST）
+export interface GasSummary {
+  computation_cost: number;
+  storage_cost: number;
+  storage_rebate: number;
+  non_refundable_storage_fee: number;
+  total: number;
+}
+
+// 交易执行结果
+export interface TransactionSummary {
+  digest: string;
+  status: 'success' | 'failure';
+  execution_error?: string;
+  package_id?: string;
+  upgrade_cap_id?: string;
+  created: ObjectSummary[];
+  mutated: ObjectSummary[];
+  deleted: string[];
+  wrapped: string[];
+  events: EventSummary[];
+  gas: GasSummary;
+  effects?: unknown;
+}
+
+// 提交交易的响应，节点拒绝请求时只有error和error_code
+export interface ExecuteResponse extends Partial<TransactionSummary> {
+  success: boolean;
+  error?: string;
+  error_code?: OperationErrorCode;
+}
+
+// 构建函数调用交易的请求
+export interface CallBuildRequest {
+  package: string;
+  module: string;
+  function: string;
+  type_args?: string[];
+  args?: unknown[];
+  modules?: string[];
+  sender: string;
+  gas_budget?: number;
+  gas_price: number;
+  gas_payment: ObjectRef[];
+}
+
+// 在本地模拟链上发布包并执行函数的请求
+export interface SimulateRequest {
+  modules: string[];
+  dependencies?: string[];
+  module: string;
+  function: string;
+  type_args?: string[];
+  args?: unknown[];
+  sender?: string;
+  gas_budget?: number;
+  gas_profile?: boolean;
+  folded_stacks?: boolean;
+}
+
+// 本地执行的返回值
+export interface SimulatedValue {
+  type: string;
+  bcs: string;
+  json?: unknown;
+}
+
+// 一笔本地交易的执行结果
+export interface SimulationReport {
+  digest: string;
+  status: 'success' | 'failure';
+  execution_error?: string;
+  package_id?: string;
+  return_values: SimulatedValue[];
+  created: ObjectSummary[];
+  mutated: ObjectSummary[];
+  deleted: string[];
+  events: EventSummary[];
+  gas: GasSummary;
+  gas_profile?: GasProfile;
+}
+
+// 本地执行的响应，参数无效时只有error和error_code
+export interface SimulateResponse {
+  success: boolean;
+  sender?: string;
+  publish?: SimulationReport;
+  call?: SimulationReport;
+  error?: string;
+  error_code?: OperationErrorCode;
+}
+
+// 创建本地模拟会话的请求
+export interface SessionCreateRequest {
+  modules: string[];
+  dependencies?: string[];
+  sender?: string;
+  gas_budget?: number;
+  gas_profile?: boolean;
+  folded_stacks?: boolean;
+}
+
+// 在模拟会话中执行函数的请求，对象参数传会话中的对象ID
+export interface SessionCallRequest {
+  module: string;
+  function: string;
+  type_args?: string[];
+  args?: unknown[];
+  gas_budget?: number;
+}
+
+// 模拟会话中的对象及解码后的字段
+export interface SessionObject extends ObjectSummary {
+  fields?: unknown;
+}
+
+// 模拟会话接口的响应，各端点只返回相关的字段
+export interface SessionResponse {
+  success: boolean;
+  session_id?: string;
+  package_id?: string;
+  sender?: string;
+  publish?: SimulationReport;
+  call?: SimulationReport;
+  objects?: ObjectSummary[];
+  object?: SessionObject;
+  snapshot?: string;
+  snapshots?: string[];
+  error?: string;
+  error_code?: OperationErrorCode;
+}
+
+// 升级策略
+export type UpgradePolicy = 'compatible' | 'additive' | 'dep_only';
+
+// 构建升级交易的请求
+export interface UpgradeBuildRequest {
+  modules: string[];
+  dependencies: string[];
+  package_id: string;
+  upgrade_cap: string;
+  policy: UpgradePolicy;
+  sender: string;
+  gas_budget?: number;
+  gas_price: number;
+  gas_payment: ObjectRef[];
+}
+
+// 违反的兼容性规则
+export interface CompatibilityViolation {
+  rule:
+    | 'MODULE_REMOVED'
+    | 'MODULE_ADDED'
+    | 'STRUCT_REMOVED'
+    | 'STRUCT_ABILITIES_CHANGED'
+    | 'STRUCT_TYPE_PARAMETERS_CHANGED'
+    | 'STRUCT_LAYOUT_CHANGED'
+    | 'STRUCT_ADDED'
+    | 'PUBLIC_FUNCTION_REMOVED'
+    | 'PUBLIC_FUNCTION_SIGNATURE_CHANGED'
+    | 'FUNCTION_CHANGED'
+    | 'FUNCTION_ADDED'
+    | 'FRIEND_REMOVED'
+    | 'FRIEND_ADDED';
+  module: string;
+  item?: string;
+  message: string;
+}
+
+// 构建升级交易的响应，violations非空时没有交易
+export interface UpgradeBuildResponse extends PublishBuildResponse {
+  current_package_id?: string;
+  current_version?: number;
+  cap_policy?: UpgradePolicy;
+  violations: CompatibilityViolation[];
 }
 
 // 示例代码结构